@@ -0,0 +1,180 @@
+use network::NetworkId;
+use parse::{connected, event, Connected};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use super::Result;
+use wpactrl::WpaCtrlAttached;
+
+/// A change of connection state, as passed to action scripts by `wpa_cli -a`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Action {
+    /// A connection was established, or moved to another network
+    Connected {
+        /// The id of the network connected to
+        id: Option<NetworkId>,
+        /// The `id_str` variable of the network, if set
+        id_str: Option<String>,
+    },
+    /// The connection was lost
+    Disconnected,
+}
+
+impl Action {
+    /// The name of the action, `CONNECTED` or `DISCONNECTED`, as passed to action scripts
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Action::Connected { .. } => "CONNECTED",
+            Action::Disconnected => "DISCONNECTED",
+        }
+    }
+}
+
+/// Turns events into actions, reporting only changes of connection state
+#[derive(Default)]
+struct Actions {
+    /// Whether connected, or None before the first event
+    connected: Option<bool>,
+    last_id: Option<NetworkId>,
+}
+
+impl Actions {
+    fn feed(&mut self, msg: &str) -> Option<Action> {
+        let (event, body) = event(msg);
+        if event == "CTRL-EVENT-CONNECTED" {
+            let Connected { id, id_str, .. } = connected(body);
+            if self.connected == Some(true) && id == self.last_id {
+                return None;
+            }
+            self.connected = Some(true);
+            self.last_id = id;
+            Some(Action::Connected { id, id_str: id_str.map(str::to_owned) })
+        } else if event == "CTRL-EVENT-DISCONNECTED" {
+            if self.connected == Some(false) {
+                return None;
+            }
+            self.connected = Some(false);
+            Some(Action::Disconnected)
+        } else {
+            None
+        }
+    }
+}
+
+impl WpaCtrlAttached {
+    /// Call `f` with the interface name on each change of connection state
+    ///
+    /// A connection is reported once, even if several events announce it,
+    /// and again if the connection moves to another network. This blocks
+    /// until reading an event or `f` fails; after `Error::ConnectionLost`,
+    /// reopen the connection to carry on.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wpactrl::Action;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// wpa.watch_actions(|ifname, action| {
+    ///     if let Action::Connected { id, .. } = *action {
+    ///         println!("{} connected to network {:?}", ifname, id);
+    ///     }
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn watch_actions<F>(&mut self, mut f: F) -> Result<()>
+        where F: FnMut(&str, &Action) -> Result<()> {
+        let ifname = self.ctrl_path().file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut actions = Actions::default();
+        loop {
+            match self.recv()? {
+                Some((_, msg)) => {
+                    if let Some(action) = actions.feed(&msg) {
+                        f(&ifname, &action)?;
+                    }
+                }
+                None => {
+                    let queued = self.queued();
+                    self.wait_for(queued, Duration::from_secs(60), |_| Some(()))?;
+                }
+            }
+        }
+    }
+
+    /// Run `script` on each change of connection state, like `wpa_cli -a`
+    ///
+    /// The script is called with the interface name and `CONNECTED` or
+    /// `DISCONNECTED` as arguments, and `WPA_CTRL_DIR`, `WPA_ID` and
+    /// `WPA_ID_STR` in its environment, so existing `wpa_cli` action
+    /// scripts work unchanged. Scripts that exit unsuccessfully are logged;
+    /// scripts that cannot be run stop the watch with `Error::Io`. See
+    /// `watch_actions` for when this returns.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// wpa.run_action_script("/sbin/ifup-hook").unwrap();
+    /// ```
+    pub fn run_action_script<P: AsRef<Path>>(&mut self, script: P) -> Result<()> {
+        let ctrl_dir = self.ctrl_path().parent().map(Path::to_owned).unwrap_or_default();
+        self.watch_actions(|ifname, action| {
+            let mut command = Command::new(script.as_ref());
+            command.arg(ifname).arg(action.name()).env("WPA_CTRL_DIR", &ctrl_dir);
+            if let Action::Connected { id, ref id_str } = *action {
+                if let Some(id) = id {
+                    command.env("WPA_ID", id.to_string());
+                }
+                command.env("WPA_ID_STR", id_str.as_deref().unwrap_or(""));
+            }
+            let status = command.status()?;
+            if !status.success() {
+                warn!("Action script {:?} {} failed: {}", script.as_ref(), action.name(), status);
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONNECTED: &str = "<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:55 completed [id=1 id_str=home]";
+    const DISCONNECTED: &str = "<3>CTRL-EVENT-DISCONNECTED bssid=00:11:22:33:44:55 reason=3";
+
+    #[test]
+    fn actions() {
+        let mut actions = Actions::default();
+        assert_eq!(actions.feed(DISCONNECTED), Some(Action::Disconnected));
+        assert_eq!(actions.feed(DISCONNECTED), None);
+        assert_eq!(actions.feed(CONNECTED),
+                   Some(Action::Connected { id: Some(NetworkId(1)), id_str: Some("home".into()) }));
+        assert_eq!(actions.feed(CONNECTED), None);
+        assert_eq!(actions.feed("<3>CTRL-EVENT-SCAN-STARTED "), None);
+        assert_eq!(actions.feed("<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:66 completed [id=2 id_str=]"),
+                   Some(Action::Connected { id: Some(NetworkId(2)), id_str: None }));
+        assert_eq!(actions.feed(DISCONNECTED).map(|action| action.name()), Some("DISCONNECTED"));
+    }
+
+    #[test]
+    fn watch_actions() {
+        use error::Error;
+        use testing::MockSupplicant;
+        use wpactrl::WpaCtrl;
+
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_actions_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        mock.event(CONNECTED);
+        mock.event(DISCONNECTED);
+        let mut seen = Vec::new();
+        let r = wpa.watch_actions(|ifname, action| {
+            seen.push((ifname.to_owned(), action.name()));
+            if seen.len() == 2 { Err(Error::Timeout) } else { Ok(()) }
+        });
+        assert!(matches!(r, Err(Error::Timeout)));
+        let ifname = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(seen, vec![(ifname.clone(), "CONNECTED"), (ifname, "DISCONNECTED")]);
+    }
+}
@@ -0,0 +1,191 @@
+use error::Error;
+use network::{NetworkId, NetworkValue};
+use secret;
+use std::fmt;
+use super::Result;
+use wpactrl::{reply_error, Request, WpaCtrl, WpaCtrlAttached};
+
+/// A sequence of commands to send in one go, built from typed steps
+///
+/// Run with `WpaCtrl::run_batch`. Commands containing credentials are
+/// redacted when the batch is debug-printed.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::Batch;
+/// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+/// let id = wpa.add_network().unwrap();
+/// let batch = Batch::new()
+///     .set_network(id, "ssid", "home")
+///     .set_network(id, "psk", "hunter2")
+///     .set_network(id, "key_mgmt", wpactrl::NetworkValue::Raw("WPA-PSK".into()))
+///     .enable_network(id)
+///     .save_config();
+/// if let Err(e) = wpa.run_batch(&batch) {
+///     let _ = wpa.remove_network(id);
+///     panic!("{}", e);
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct Batch {
+    cmds: Vec<String>,
+}
+
+impl Batch {
+    /// An empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a raw command
+    pub fn request<S: Into<String>>(mut self, cmd: S) -> Self {
+        self.cmds.push(cmd.into());
+        self
+    }
+
+    /// Append `SET_NETWORK`
+    pub fn set_network<V: Into<NetworkValue>>(self, id: NetworkId, key: &str, value: V) -> Self {
+        self.request(format!("SET_NETWORK {} {} {}", id, key, value.into().to_arg()))
+    }
+
+    /// Append `ENABLE_NETWORK`
+    pub fn enable_network(self, id: NetworkId) -> Self {
+        self.request(format!("ENABLE_NETWORK {}", id))
+    }
+
+    /// Append `DISABLE_NETWORK`
+    pub fn disable_network(self, id: NetworkId) -> Self {
+        self.request(format!("DISABLE_NETWORK {}", id))
+    }
+
+    /// Append `SELECT_NETWORK`
+    pub fn select_network(self, id: NetworkId) -> Self {
+        self.request(format!("SELECT_NETWORK {}", id))
+    }
+
+    /// Append `REMOVE_NETWORK`
+    pub fn remove_network(self, id: NetworkId) -> Self {
+        self.request(format!("REMOVE_NETWORK {}", id))
+    }
+
+    /// Append `SET`, changing a global configuration variable
+    pub fn set(self, key: &str, value: &str) -> Self {
+        self.request(format!("SET {} {}", key, value))
+    }
+
+    /// Append `SAVE_CONFIG`
+    pub fn save_config(self) -> Self {
+        self.request("SAVE_CONFIG")
+    }
+
+    /// The commands of the batch, in order
+    pub fn commands(&self) -> &[String] {
+        &self.cmds
+    }
+}
+
+impl fmt::Debug for Batch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.cmds.iter().map(|cmd| secret::redact(cmd))).finish()
+    }
+}
+
+/// Wipes the commands from memory, as they may contain credentials
+#[cfg(feature = "zeroize")]
+impl Drop for Batch {
+    fn drop(&mut self) {
+        for cmd in &mut self.cmds {
+            secret::wipe(cmd);
+        }
+    }
+}
+
+/// Send `cmds` in order, stopping at the first that fails
+fn request_batch<C: Request, S: AsRef<str>>(ctrl: &mut C, cmds: &[S]) -> Result<Vec<String>> {
+    let mut replies = Vec::with_capacity(cmds.len());
+    for (i, cmd) in cmds.iter().enumerate() {
+        let reply = ctrl.request(cmd.as_ref()).map_err(|e| Error::Batch(i, Box::new(e)))?;
+        if let Some(e) = reply_error(&reply) {
+            return Err(Error::Batch(i, Box::new(e)));
+        }
+        replies.push(reply);
+    }
+    Ok(replies)
+}
+
+impl WpaCtrl {
+    /// Send a sequence of commands, returning all replies
+    ///
+    /// Stops at the first command that fails or is rejected, with
+    /// `Error::Batch` giving its index and the error; the commands before
+    /// it have taken effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let replies = wpa.request_batch(&["PING", "STATUS"]).unwrap();
+    /// assert_eq!(replies[0], "PONG\n");
+    /// ```
+    pub fn request_batch<S: AsRef<str>>(&mut self, cmds: &[S]) -> Result<Vec<String>> {
+        request_batch(self, cmds)
+    }
+
+    /// Send the commands of a `Batch`, returning all replies
+    ///
+    /// See `request_batch` for how failures are handled.
+    pub fn run_batch(&mut self, batch: &Batch) -> Result<Vec<String>> {
+        request_batch(self, &batch.cmds)
+    }
+}
+
+impl WpaCtrlAttached {
+    /// Send a sequence of commands, returning all replies
+    ///
+    /// See `WpaCtrl::request_batch` for details.
+    pub fn request_batch<S: AsRef<str>>(&mut self, cmds: &[S]) -> Result<Vec<String>> {
+        request_batch(self, cmds)
+    }
+
+    /// Send the commands of a `Batch`, returning all replies
+    ///
+    /// See `WpaCtrl::request_batch` for how failures are handled.
+    pub fn run_batch(&mut self, batch: &Batch) -> Result<Vec<String>> {
+        request_batch(self, &batch.cmds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn batch() {
+        let batch = Batch::new()
+            .set_network(NetworkId(0), "ssid", "home")
+            .set_network(NetworkId(0), "psk", "hunter2")
+            .enable_network(NetworkId(0));
+        assert_eq!(batch.commands(), ["SET_NETWORK 0 ssid \"home\"", "SET_NETWORK 0 psk \"hunter2\"", "ENABLE_NETWORK 0"]);
+        assert!(!format!("{:?}", batch).contains("hunter2"));
+    }
+
+    #[test]
+    fn request_batch() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_batch_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("SET_NETWORK 0 ssid \"home\"", "OK\n");
+        mock.reply("ENABLE_NETWORK 0", "OK\n");
+        mock.reply("SET_NETWORK 0 psk \"short\"", "FAIL\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert_eq!(wpa.request_batch(&["PING", "ENABLE_NETWORK 0"]).unwrap(), vec!["PONG\n", "OK\n"]);
+        let batch = Batch::new()
+            .set_network(NetworkId(0), "ssid", "home")
+            .set_network(NetworkId(0), "psk", "short")
+            .enable_network(NetworkId(0));
+        let e = wpa.run_batch(&batch).unwrap_err();
+        assert!(matches!(e, Error::Batch(1, ref e) if matches!(**e, Error::Failure)));
+        assert_eq!(mock.commands().len(), 4);
+    }
+}
@@ -0,0 +1,240 @@
+//! An interactive client for wpasupplicant, in the style of `wpa_cli`
+//!
+//! Built with the `cli` feature:
+//!
+//! ```text
+//! cargo run --features cli --bin wpactrl-cli -- -i wlan0
+//! ```
+extern crate nix;
+extern crate wpactrl;
+
+use nix::sys::select::{select, FdSet};
+use nix::sys::time::{TimeVal, TimeValLike};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+use wpactrl::{NetworkId, NetworkValue, WpaCtrl, WpaCtrlAttached};
+
+const USAGE: &str = "usage: wpactrl-cli [-p <ctrl dir>] [-i <ifname>] [-a <action script> | command..]";
+
+const HELP: &str = "\
+commands:
+  list_networks                   list configured networks
+  scan_results                    list the results of the last scan
+  add_network                     add a network, printing its id
+  set_network <id> <var> <value>  set a network variable; quote strings
+  enable_network <id>             enable a network
+  select_network <id>             select a network, disabling the others
+  remove_network <id>             remove a network
+  disconnect, reconnect, reassociate
+  save_config                     save the configuration
+  history                         show the command history
+  !!, !<n>                        repeat the last or the n-th command
+  help                            show this help
+  quit                            exit
+Any other command is sent as is, with its name in upper case.";
+
+fn main() {
+    let mut ctrl_dir = PathBuf::from("/var/run/wpa_supplicant");
+    let mut ifname = None;
+    let mut action_script = None;
+    let mut args = env::args().skip(1);
+    let mut command = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" => ctrl_dir = args.next().unwrap_or_else(|| usage()).into(),
+            "-i" => ifname = Some(args.next().unwrap_or_else(|| usage())),
+            "-a" => action_script = Some(args.next().unwrap_or_else(|| usage())),
+            "-h" | "--help" => usage(),
+            _ => {
+                command.push(arg);
+                command.extend(args.by_ref());
+            }
+        }
+    }
+
+    let builder = match ifname {
+        Some(ref ifname) => WpaCtrl::new().ctrl_path(ctrl_dir.join(ifname)),
+        None => WpaCtrl::new().ctrl_path(ctrl_dir).any_interface(),
+    };
+    let mut wpa = builder.open().unwrap_or_else(|e| fail(&e));
+
+    if let Some(script) = action_script {
+        let r = wpa.attach().and_then(|mut wpa| wpa.run_action_script(script));
+        if let Err(e) = r {
+            fail(&e);
+        }
+        return;
+    }
+
+    if !command.is_empty() {
+        if let Err(e) = run(&mut wpa, &command.join(" ")) {
+            fail(&e);
+        }
+        return;
+    }
+
+    let mut monitor = wpa.try_clone().and_then(WpaCtrl::attach).unwrap_or_else(|e| fail(&e));
+    let mut history: Vec<String> = Vec::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+        if let Err(e) = wait_for_input(&mut monitor) {
+            fail(&e);
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line.trim().to_owned(),
+            _ => break,
+        };
+        let line = match expand(&line, &history) {
+            Some(line) => line,
+            None => {
+                eprintln!("no such command in history");
+                continue;
+            }
+        };
+        match line.as_str() {
+            "" => continue,
+            "quit" | "exit" => break,
+            "help" => println!("{}", HELP),
+            "history" => {
+                for (n, cmd) in history.iter().enumerate() {
+                    println!("{:4}  {}", n + 1, cmd);
+                }
+            }
+            _ => {
+                if let Err(e) = run(&mut wpa, &line) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+        if line != "history" {
+            history.push(line);
+        }
+    }
+}
+
+/// Print events from `monitor` until a line of input is available
+fn wait_for_input(monitor: &mut WpaCtrlAttached) -> wpactrl::Result<()> {
+    loop {
+        let mut fd_set = FdSet::new();
+        fd_set.insert(0);
+        select(1, Some(&mut fd_set), None, None, Some(&mut TimeVal::milliseconds(100)))?;
+        let mut printed = false;
+        while let Some(msg) = monitor.raw_recv()? {
+            println!("\r{}", msg.trim_end());
+            printed = true;
+        }
+        if printed {
+            print!("> ");
+            let _ = io::stdout().flush();
+        }
+        if fd_set.contains(0) {
+            return Ok(());
+        }
+    }
+}
+
+/// Replace a `!!` or `!<n>` history reference with the command it refers to
+fn expand(line: &str, history: &[String]) -> Option<String> {
+    if line == "!!" {
+        history.last().cloned()
+    } else if let Some(n) = line.strip_prefix('!') {
+        let n: usize = n.parse().ok()?;
+        history.get(n.checked_sub(1)?).cloned()
+    } else {
+        Some(line.to_owned())
+    }
+}
+
+/// Run a command, through the typed API where there is one
+fn run(wpa: &mut WpaCtrl, line: &str) -> wpactrl::Result<()> {
+    let mut words = line.split_whitespace();
+    let name = words.next().unwrap_or("").to_lowercase();
+    let args: Vec<&str> = words.collect();
+    let id = args.first().and_then(|id| id.parse().ok()).map(NetworkId);
+    let takes_id = matches!(name.as_str(), "set_network" | "enable_network" | "select_network" | "remove_network");
+    if takes_id && id.is_none() {
+        eprintln!("usage: {} <id> ..", name);
+        return Ok(());
+    }
+    let id = || id.unwrap();
+    match name.as_str() {
+        "list_networks" => {
+            println!("id\tssid\tbssid\tflags");
+            for network in wpa.list_networks()? {
+                let mut flags: Vec<String> = network.flags.iter().map(|flag| format!("{:?}", flag)).collect();
+                flags.sort();
+                println!("{}\t{}\t{}\t{}", network.id, network.ssid, network.bssid.map_or_else(|| "any".to_owned(), |bssid| bssid.to_string()),
+                         flags.join(" "));
+            }
+        }
+        "scan_results" => {
+            println!("bssid\t\t\tfrequency\tsignal\tflags\tssid");
+            for bss in wpa.scan_results()? {
+                println!("{}\t{}\t\t{}\t{}\t{}", bss.bssid, bss.frequency, bss.signal, bss.flags.concat(), bss.ssid);
+            }
+        }
+        "add_network" => println!("{}", wpa.add_network()?),
+        "set_network" if args.len() >= 3 => {
+            let value = args[2..].join(" ");
+            let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                NetworkValue::Str(value[1..value.len() - 1].to_owned())
+            } else {
+                NetworkValue::Raw(value)
+            };
+            wpa.set_network(id(), args[1], value)?;
+            println!("OK");
+        }
+        "enable_network" => {
+            wpa.enable_network(id())?;
+            println!("OK");
+        }
+        "select_network" => {
+            wpa.select_network(id())?;
+            println!("OK");
+        }
+        "remove_network" => {
+            wpa.remove_network(id())?;
+            println!("OK");
+        }
+        "disconnect" => {
+            wpa.disconnect()?;
+            println!("OK");
+        }
+        "reconnect" => {
+            wpa.reconnect()?;
+            println!("OK");
+        }
+        "reassociate" => {
+            wpa.reassociate()?;
+            println!("OK");
+        }
+        "save_config" => {
+            wpa.save_config()?;
+            println!("OK");
+        }
+        _ => {
+            let mut cmd = name.to_uppercase();
+            for arg in &args {
+                cmd.push(' ');
+                cmd.push_str(arg);
+            }
+            print!("{}", wpa.raw_request(&cmd)?);
+        }
+    }
+    Ok(())
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
+
+fn fail(e: &wpactrl::Error) -> ! {
+    eprintln!("wpactrl-cli: {}", e);
+    process::exit(1)
+}
@@ -0,0 +1,188 @@
+use scan_flags::{Ciphers, KeyMgmt, Protocols};
+use super::Result;
+use wpactrl::{reply_error, WpaCtrl};
+
+bitflags! {
+    /// Operating modes the interface supports
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Modes: u8 {
+        /// Ad-hoc networks
+        const IBSS = 1;
+        /// Acting as an access point
+        const AP = 1 << 1;
+        /// 802.11s mesh networks
+        const MESH = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// FILS features wpasupplicant and the driver support
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct FilsSupport: u8 {
+        /// FILS shared key authentication
+        const FILS = 1;
+        /// FILS shared key authentication with perfect forward secrecy
+        const FILS_SK_PFS = 1 << 1;
+    }
+}
+
+/// What to ask `WpaCtrl::capabilities` about
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CapabilityKind {
+    /// Key management suites (`key_mgmt`)
+    KeyMgmt,
+    /// Pairwise ciphers (`pairwise`)
+    Pairwise,
+    /// Group ciphers (`group`)
+    Group,
+    /// Security protocols (`proto`)
+    Proto,
+    /// Operating modes (`modes`)
+    Modes,
+    /// FILS features (`fils`)
+    Fils,
+}
+
+impl CapabilityKind {
+    /// The argument of `GET_CAPABILITY`
+    fn name(&self) -> &'static str {
+        match *self {
+            CapabilityKind::KeyMgmt => "key_mgmt",
+            CapabilityKind::Pairwise => "pairwise",
+            CapabilityKind::Group => "group",
+            CapabilityKind::Proto => "proto",
+            CapabilityKind::Modes => "modes",
+            CapabilityKind::Fils => "fils",
+        }
+    }
+}
+
+/// What wpasupplicant and the driver support, as shown by `GET_CAPABILITY`
+///
+/// Names this crate does not know, such as the WEP group ciphers, are
+/// ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CapabilitySet {
+    /// The supported key management suites
+    KeyMgmt(KeyMgmt),
+    /// The supported pairwise ciphers
+    Pairwise(Ciphers),
+    /// The supported group ciphers
+    Group(Ciphers),
+    /// The supported security protocols
+    Proto(Protocols),
+    /// The supported operating modes
+    Modes(Modes),
+    /// The supported FILS features
+    Fils(FilsSupport),
+}
+
+/// Key management suites by name, as used by `GET_CAPABILITY` and the `key_mgmt` network variable
+pub(crate) const KEY_MGMT_NAMES: &[(&str, KeyMgmt)] = &[
+    ("WPA-EAP", KeyMgmt::EAP),
+    ("WPA-PSK", KeyMgmt::PSK),
+    ("FT-EAP", KeyMgmt::FT_EAP),
+    ("FT-PSK", KeyMgmt::FT_PSK),
+    ("WPA-EAP-SHA256", KeyMgmt::EAP_SHA256),
+    ("WPA-PSK-SHA256", KeyMgmt::PSK_SHA256),
+    ("SAE", KeyMgmt::SAE),
+    ("FT-SAE", KeyMgmt::FT_SAE),
+    ("SAE-EXT-KEY", KeyMgmt::SAE_EXT_KEY),
+    ("FT-SAE-EXT-KEY", KeyMgmt::SAE_EXT_KEY),
+    ("WPA-EAP-SUITE-B", KeyMgmt::EAP_SUITE_B),
+    ("WPA-EAP-SUITE-B-192", KeyMgmt::EAP_SUITE_B_192),
+    ("OWE", KeyMgmt::OWE),
+    ("DPP", KeyMgmt::DPP),
+    ("FILS-SHA256", KeyMgmt::FILS_SHA256),
+    ("FILS-SHA384", KeyMgmt::FILS_SHA384),
+    ("FT-FILS-SHA256", KeyMgmt::FT_FILS_SHA256),
+    ("FT-FILS-SHA384", KeyMgmt::FT_FILS_SHA384),
+    ("FT-EAP-SHA384", KeyMgmt::FT_EAP_SHA384),
+    ("OSEN", KeyMgmt::OSEN),
+];
+
+/// The flags of the names in `reply` that appear in `names`
+fn flags<T: Copy + Default + ::std::ops::BitOr<Output = T>>(reply: &str, names: &[(&str, T)]) -> T {
+    reply.split_whitespace()
+        .filter_map(|word| names.iter().find(|&&(name, _)| name == word).map(|&(_, flag)| flag))
+        .fold(T::default(), |a, b| a | b)
+}
+
+/// Parse the reply to `GET_CAPABILITY`
+fn parse_capabilities(kind: CapabilityKind, reply: &str) -> CapabilitySet {
+    let ciphers = || flags(reply, &[
+        ("CCMP", Ciphers::CCMP),
+        ("TKIP", Ciphers::TKIP),
+        ("GCMP", Ciphers::GCMP),
+        ("CCMP-256", Ciphers::CCMP_256),
+        ("GCMP-256", Ciphers::GCMP_256),
+    ]);
+    match kind {
+        CapabilityKind::KeyMgmt => CapabilitySet::KeyMgmt(flags(reply, KEY_MGMT_NAMES)),
+        CapabilityKind::Pairwise => CapabilitySet::Pairwise(ciphers()),
+        CapabilityKind::Group => CapabilitySet::Group(ciphers()),
+        CapabilityKind::Proto => CapabilitySet::Proto(flags(reply, &[
+            ("WPA", Protocols::WPA),
+            ("RSN", Protocols::RSN),
+            ("OSEN", Protocols::OSEN),
+        ])),
+        CapabilityKind::Modes => CapabilitySet::Modes(flags(reply, &[
+            ("IBSS", Modes::IBSS),
+            ("AP", Modes::AP),
+            ("MESH", Modes::MESH),
+        ])),
+        CapabilityKind::Fils => CapabilitySet::Fils(flags(reply, &[
+            ("FILS", FilsSupport::FILS),
+            ("FILS-SK-PFS", FilsSupport::FILS_SK_PFS),
+        ])),
+    }
+}
+
+impl WpaCtrl {
+    /// What this wpasupplicant build and the driver support
+    ///
+    /// Useful to check for eg SAE before configuring a WPA3 network.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{CapabilityKind, CapabilitySet, KeyMgmt};
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// if let CapabilitySet::KeyMgmt(key_mgmt) = wpa.capabilities(CapabilityKind::KeyMgmt).unwrap() {
+    ///     println!("WPA3-Personal supported: {}", key_mgmt.contains(KeyMgmt::SAE));
+    /// }
+    /// ```
+    pub fn capabilities(&mut self, kind: CapabilityKind) -> Result<CapabilitySet> {
+        let reply = self.request(&format!("GET_CAPABILITY {}", kind.name()))?;
+        match reply_error(&reply) {
+            Some(e) => Err(e),
+            None => Ok(parse_capabilities(kind, &reply)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn capabilities() {
+        let key_mgmt = "NONE IEEE8021X WPA-EAP WPA-PSK WPA-EAP-SUITE-B-192 OWE DPP SAE\n";
+        assert_eq!(parse_capabilities(CapabilityKind::KeyMgmt, key_mgmt),
+                   CapabilitySet::KeyMgmt(KeyMgmt::EAP | KeyMgmt::PSK | KeyMgmt::EAP_SUITE_B_192 | KeyMgmt::OWE |
+                                          KeyMgmt::DPP | KeyMgmt::SAE));
+        assert_eq!(parse_capabilities(CapabilityKind::Pairwise, "CCMP-256 GCMP-256 CCMP GCMP TKIP NONE\n"),
+                   CapabilitySet::Pairwise(Ciphers::all()));
+        assert_eq!(parse_capabilities(CapabilityKind::Group, "CCMP TKIP WEP104 WEP40\n"),
+                   CapabilitySet::Group(Ciphers::CCMP | Ciphers::TKIP));
+        assert_eq!(parse_capabilities(CapabilityKind::Proto, "RSN WPA\n"),
+                   CapabilitySet::Proto(Protocols::RSN | Protocols::WPA));
+        assert_eq!(parse_capabilities(CapabilityKind::Modes, "IBSS AP MESH\n"), CapabilitySet::Modes(Modes::all()));
+        assert_eq!(parse_capabilities(CapabilityKind::Fils, "FILS FILS-SK-PFS\n"),
+                   CapabilitySet::Fils(FilsSupport::all()));
+        assert_eq!(parse_capabilities(CapabilityKind::Fils, "\n"), CapabilitySet::Fils(FilsSupport::empty()));
+    }
+}
@@ -0,0 +1,226 @@
+use error::Error;
+use parse::hex_encode;
+use std::fmt;
+use super::Result;
+use wpactrl::{expect_ok, reply_error, WpaCtrl};
+
+/// A global wpasupplicant variable, as used by `GET` and `SET`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Property {
+    /// `country`: the ISO-3166 country code used for regulatory settings
+    Country,
+    /// `ap_scan`: who selects and scans for access points (0, 1 or 2)
+    ApScan,
+    /// `pmf`: default protected management frames setting (0, 1 or 2)
+    Pmf,
+    /// `p2p_disabled`: whether P2P (Wi-Fi Direct) is disabled
+    P2pDisabled,
+    /// `update_config`: whether `SAVE_CONFIG` may write the configuration
+    UpdateConfig,
+    /// `fast_reauth`: whether EAP fast re-authentication is enabled
+    FastReauth,
+    /// `device_name`: the device name used by WPS and P2P
+    DeviceName,
+    /// `bss_expiration_age`: seconds after which unseen BSS entries expire
+    BssExpirationAge,
+    /// `bss_expiration_scan_count`: scans after which unseen BSS entries expire
+    BssExpirationScanCount,
+    /// `filter_ssids`: whether to only keep scan results for configured SSIDs
+    FilterSsids,
+    /// `autoscan`: the automatic scan parameters, eg `exponential:3:300`
+    Autoscan,
+    /// `mac_addr`: which MAC address to use when connecting, see `MacAddrPolicy`
+    MacAddr,
+    /// `preassoc_mac_addr`: which MAC address to use while not associated
+    PreassocMacAddr,
+    /// Any other variable, by name
+    Other(String),
+}
+
+impl Property {
+    /// The variable name as used by `GET` and `SET`
+    pub fn name(&self) -> &str {
+        match *self {
+            Property::Country => "country",
+            Property::ApScan => "ap_scan",
+            Property::Pmf => "pmf",
+            Property::P2pDisabled => "p2p_disabled",
+            Property::UpdateConfig => "update_config",
+            Property::FastReauth => "fast_reauth",
+            Property::DeviceName => "device_name",
+            Property::BssExpirationAge => "bss_expiration_age",
+            Property::BssExpirationScanCount => "bss_expiration_scan_count",
+            Property::FilterSsids => "filter_ssids",
+            Property::Autoscan => "autoscan",
+            Property::MacAddr => "mac_addr",
+            Property::PreassocMacAddr => "preassoc_mac_addr",
+            Property::Other(ref name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl<'a> From<&'a str> for Property {
+    fn from(name: &'a str) -> Self {
+        Property::Other(name.to_owned())
+    }
+}
+
+/// A value for a global variable, formatted as `SET` expects it
+///
+/// Unlike network variables, global string values are sent unquoted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyValue(String);
+
+impl<'a> From<&'a str> for PropertyValue {
+    fn from(s: &'a str) -> Self {
+        PropertyValue(s.to_owned())
+    }
+}
+
+impl From<String> for PropertyValue {
+    fn from(s: String) -> Self {
+        PropertyValue(s)
+    }
+}
+
+impl From<bool> for PropertyValue {
+    fn from(b: bool) -> Self {
+        PropertyValue(if b { "1" } else { "0" }.to_owned())
+    }
+}
+
+macro_rules! property_value_from_int {
+    ($($t:ty),*) => {$(
+        impl From<$t> for PropertyValue {
+            fn from(n: $t) -> Self {
+                PropertyValue(n.to_string())
+            }
+        }
+    )*}
+}
+property_value_from_int!(i32, u8, u16, u32, i64, u64);
+
+impl WpaCtrl {
+    /// Read a global variable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Property;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// println!("ap_scan={}", wpa.get(Property::ApScan).unwrap());
+    /// println!("config_methods={}", wpa.get("config_methods").unwrap());
+    /// ```
+    pub fn get<P: Into<Property>>(&mut self, property: P) -> Result<String> {
+        let reply = self.request(&format!("GET {}", property.into()))?;
+        match reply_error(&reply) {
+            Some(e) => Err(e),
+            None => Ok(reply.trim_end_matches('\n').to_owned()),
+        }
+    }
+
+    /// Set a global variable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Property;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.set(Property::ApScan, 1).unwrap();
+    /// wpa.set(Property::P2pDisabled, true).unwrap();
+    /// wpa.set(Property::DeviceName, "my device").unwrap();
+    /// ```
+    pub fn set<P: Into<Property>, V: Into<PropertyValue>>(&mut self, property: P, value: V) -> Result<()> {
+        expect_ok(&self.request(&format!("SET {} {}", property.into(), value.into().0))?)
+    }
+
+    /// Store binary data, such as a certificate or key, as a named blob
+    ///
+    /// Network variables such as `ca_cert` can then refer to it as
+    /// `blob://<name>`, so no file has to be written on the device.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{NetworkConfig, NetworkValue};
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let der = std::fs::read("/etc/ssl/ca.der").unwrap();
+    /// wpa.set_blob("ca", &der).unwrap();
+    /// let config = NetworkConfig::open("work").set("ca_cert", NetworkValue::Str("blob://ca".into()));
+    /// ```
+    pub fn set_blob(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        expect_ok(&self.request(&format!("SET blob {} {}", name, hex_encode(data)))?)
+    }
+
+    /// Remove a blob stored by `set_blob`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.remove_blob("ca").unwrap();
+    /// ```
+    pub fn remove_blob(&mut self, name: &str) -> Result<()> {
+        expect_ok(&self.request(&format!("REMOVE_BLOB {}", name))?)
+    }
+
+    /// Write the current configuration, including networks added at runtime, to disk
+    ///
+    /// Fails with an error explaining the cause if wpasupplicant was
+    /// configured with `update_config=0`, which forbids saving.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkConfig;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.create_network(NetworkConfig::open("cafe")).unwrap();
+    /// wpa.save_config().unwrap();
+    /// ```
+    pub fn save_config(&mut self) -> Result<()> {
+        if self.request("SAVE_CONFIG")? == "OK\n" {
+            return Ok(());
+        }
+        match self.get(Property::UpdateConfig)?.as_str() {
+            "0" => Err(Error::UpdateConfigDisabled),
+            _ => Err(Error::Failure),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn property() {
+        assert_eq!(Property::P2pDisabled.to_string(), "p2p_disabled");
+        assert_eq!(Property::from("config_methods").name(), "config_methods");
+    }
+
+    #[test]
+    fn property_value() {
+        assert_eq!(PropertyValue::from(true).0, "1");
+        assert_eq!(PropertyValue::from(2).0, "2");
+        assert_eq!(PropertyValue::from("my device").0, "my device");
+    }
+
+    #[test]
+    fn get() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_config_get_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("GET ap_scan", "1\n");
+        mock.reply("GET config_methods", "FAIL\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert_eq!(wpa.get(Property::ApScan).unwrap(), "1");
+        assert!(matches!(wpa.get("config_methods"), Err(Error::Failure)));
+        assert!(matches!(wpa.get("p2p_disabled"), Err(Error::UnknownCommand)));
+    }
+}
@@ -0,0 +1,187 @@
+use macaddr::MacAddr;
+use network::{create_network, remove_network, select_network, NetworkConfig, NetworkId};
+use parse::{connected, event, event_arg, Connected};
+use std::time::Duration;
+use super::Result;
+use wpactrl::{expect_ok, WpaCtrl, WpaCtrlAttached};
+
+/// The outcome of `WpaCtrlAttached::connect`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ConnectOutcome {
+    /// The connection was established
+    Connected {
+        /// The id of the newly created network block
+        id: NetworkId,
+        /// The BSSID of the access point that was connected to
+        bssid: MacAddr,
+    },
+    /// Authentication failed because the passphrase or key was wrong
+    WrongKey,
+    /// The network was temporarily disabled for another reason, eg `AUTH_FAILED`
+    TempDisabled(String),
+    /// EAP authentication failed
+    EapFailure,
+    /// Association was rejected by the access point with the given status code
+    AssocRejected(u16),
+    /// No matching network was found by the time the timeout expired
+    NotFound,
+    /// No connection was established before the timeout expired
+    Timeout,
+}
+
+impl ConnectOutcome {
+    /// Whether the connection was established
+    pub fn is_connected(&self) -> bool {
+        matches!(*self, ConnectOutcome::Connected { .. })
+    }
+}
+
+/// Progress of a connection attempt, as determined from a single event
+enum Progress {
+    Done(ConnectOutcome),
+    Pending(Option<ConnectOutcome>),
+}
+
+/// Interpret an event in the context of a connection attempt to network `id`
+fn progress(id: NetworkId, msg: &str) -> Progress {
+    let (event, body) = event(msg);
+    let for_network = |body: &str| event_arg(body, "id") == Some(&id.to_string());
+    match event {
+        "CTRL-EVENT-CONNECTED" => match connected(body) {
+            Connected { id: Some(connected_id), bssid: Some(bssid), .. } if connected_id == id => {
+                Progress::Done(ConnectOutcome::Connected { id, bssid })
+            }
+            _ => Progress::Pending(None),
+        },
+        "CTRL-EVENT-SSID-TEMP-DISABLED" if for_network(body) => {
+            Progress::Done(match event_arg(body, "reason") {
+                Some("WRONG_KEY") => ConnectOutcome::WrongKey,
+                reason => ConnectOutcome::TempDisabled(reason.unwrap_or("").to_owned()),
+            })
+        }
+        "CTRL-EVENT-EAP-FAILURE" => Progress::Done(ConnectOutcome::EapFailure),
+        "CTRL-EVENT-ASSOC-REJECT" => {
+            let status = event_arg(body, "status_code").and_then(|s| s.parse().ok()).unwrap_or(0);
+            Progress::Pending(Some(ConnectOutcome::AssocRejected(status)))
+        }
+        "CTRL-EVENT-NETWORK-NOT-FOUND" => Progress::Pending(Some(ConnectOutcome::NotFound)),
+        _ => Progress::Pending(None),
+    }
+}
+
+impl WpaCtrlAttached {
+    /// Add a network, select it, and wait for the connection to complete
+    ///
+    /// Waits up to `timeout` for wpasupplicant to report that the connection
+    /// was established or failed. Association rejections and failures to
+    /// find the network are retried by wpasupplicant, so they are only
+    /// reported if no connection is made before the timeout expires.
+    ///
+    /// Unless the connection succeeds, the network block is removed again.
+    /// Events received while waiting remain available through `recv`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wpactrl::{ConnectOutcome, NetworkConfig};
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// let config = NetworkConfig::wpa_psk("home", "correct horse battery staple");
+    /// match wpa.connect(config, Duration::from_secs(30)).unwrap() {
+    ///     ConnectOutcome::Connected { bssid, .. } => println!("Connected to {}", bssid),
+    ///     failure => println!("Connection failed: {:?}", failure),
+    /// }
+    /// ```
+    pub fn connect<C: Into<NetworkConfig>>(&mut self, config: C, timeout: Duration) -> Result<ConnectOutcome> {
+        let from = self.queued();
+        let id = create_network(self, config.into())?;
+        if let Err(e) = select_network(self, id) {
+            let _ = remove_network(self, id);
+            return Err(e);
+        }
+        let mut last_failure = None;
+        let outcome = self.wait_for(from, timeout, |msg| match progress(id, msg) {
+            Progress::Done(outcome) => Some(outcome),
+            Progress::Pending(failure) => {
+                last_failure = failure.or_else(|| last_failure.take());
+                None
+            }
+        })?.unwrap_or_else(|| last_failure.unwrap_or(ConnectOutcome::Timeout));
+        if !outcome.is_connected() {
+            remove_network(self, id)?;
+        }
+        Ok(outcome)
+    }
+}
+
+impl WpaCtrl {
+    /// Disconnect and stop trying to connect until `reconnect` or `reassociate`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.disconnect().unwrap();
+    /// wpa.reconnect().unwrap();
+    /// ```
+    pub fn disconnect(&mut self) -> Result<()> {
+        expect_ok(&self.request("DISCONNECT")?)
+    }
+
+    /// Connect again after a `disconnect`, if not already connected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.reconnect().unwrap();
+    /// ```
+    pub fn reconnect(&mut self) -> Result<()> {
+        expect_ok(&self.request("RECONNECT")?)
+    }
+
+    /// Force a reassociation, even if already connected
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.reassociate().unwrap();
+    /// ```
+    pub fn reassociate(&mut self) -> Result<()> {
+        expect_ok(&self.request("REASSOCIATE")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn done(msg: &str) -> Option<ConnectOutcome> {
+        match progress(NetworkId(2), msg) {
+            Progress::Done(outcome) => Some(outcome),
+            Progress::Pending(_) => None,
+        }
+    }
+
+    #[test]
+    fn connected() {
+        assert_eq!(done("<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:55 completed [id=2 id_str=]"),
+                   Some(ConnectOutcome::Connected { id: NetworkId(2), bssid: "00:11:22:33:44:55".parse().unwrap() }));
+        assert_eq!(done("<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:55 completed [id=1 id_str=]"), None);
+    }
+
+    #[test]
+    fn failures() {
+        assert_eq!(done("<3>CTRL-EVENT-SSID-TEMP-DISABLED id=2 ssid=\"home\" auth_failures=1 duration=10 reason=WRONG_KEY"),
+                   Some(ConnectOutcome::WrongKey));
+        assert_eq!(done("<3>CTRL-EVENT-SSID-TEMP-DISABLED id=2 ssid=\"home\" auth_failures=1 duration=10 reason=AUTH_FAILED"),
+                   Some(ConnectOutcome::TempDisabled("AUTH_FAILED".into())));
+        assert_eq!(done("<3>CTRL-EVENT-SSID-TEMP-DISABLED id=1 ssid=\"work\" auth_failures=1 duration=10 reason=WRONG_KEY"),
+                   None);
+        assert_eq!(done("<3>CTRL-EVENT-EAP-FAILURE EAP authentication failed"), Some(ConnectOutcome::EapFailure));
+        assert_eq!(done("<3>CTRL-EVENT-ASSOC-REJECT bssid=00:11:22:33:44:55 status_code=17"), None);
+        assert_eq!(done("<3>CTRL-EVENT-NETWORK-NOT-FOUND"), None);
+    }
+}
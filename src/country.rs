@@ -0,0 +1,180 @@
+use error::Error;
+use parse::{event_arg, strip_level};
+use std::fmt;
+use std::str::FromStr;
+use super::Result;
+use wpactrl::{expect_ok, reply_error, WpaCtrl};
+
+/// The ISO-3166-1 alpha-2 codes, concatenated
+const ISO_3166: &str = concat!(
+    "ADAEAFAGAIALAMAOAQARASATAUAWAXAZBABBBDBEBFBGBHBIBJBLBMBNBOBQBRBSBTBVBWBYBZ",
+    "CACCCDCFCGCHCICKCLCMCNCOCRCUCVCWCXCYCZDEDJDKDMDODZECEEEGEHERESETFIFJFKFMFOFR",
+    "GAGBGDGEGFGGGHGIGLGMGNGPGQGRGSGTGUGWGYHKHMHNHRHTHUIDIEILIMINIOIQIRISITJEJMJOJP",
+    "KEKGKHKIKMKNKPKRKWKYKZLALBLCLILKLRLSLTLULVLYMAMCMDMEMFMGMHMKMLMMMNMOMPMQMRMSMT",
+    "MUMVMWMXMYMZNANCNENFNGNINLNONPNRNUNZOMPAPEPFPGPHPKPLPMPNPRPSPTPWPYQARERORSRURW",
+    "SASBSCSDSESGSHSISJSKSLSMSNSOSRSSSTSVSXSYSZTCTDTFTGTHTJTKTLTMTNTOTRTTTVTWTZ",
+    "UAUGUMUSUYUZVAVCVEVGVIVNVUWFWSYEYTZAZMZW",
+);
+
+/// A country code for regulatory settings, as used by `SET country`
+///
+/// Either an ISO-3166-1 alpha-2 code, or `00` for the world regulatory
+/// domain.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::CountryCode;
+/// let code: CountryCode = "de".parse().unwrap();
+/// assert_eq!(code.as_str(), "DE");
+/// assert!("XX".parse::<CountryCode>().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CountryCode([u8; 2]);
+
+impl CountryCode {
+    /// The world regulatory domain, `00`
+    pub const WORLD: CountryCode = CountryCode(*b"00");
+
+    /// The code, in upper case
+    pub fn as_str(&self) -> &str {
+        ::std::str::from_utf8(&self.0).unwrap_or("00")
+    }
+}
+
+impl FromStr for CountryCode {
+    type Err = Error;
+
+    /// Parse a code, ignoring case, failing with `Error::InvalidCountry` if it is not assigned
+    fn from_str(s: &str) -> Result<Self> {
+        let upper = s.to_ascii_uppercase();
+        let known = upper == "00" || (upper.len() == 2 && ISO_3166.as_bytes().chunks(2).any(|c| c == upper.as_bytes()));
+        if !known {
+            return Err(Error::InvalidCountry(s.to_owned()));
+        }
+        let bytes = upper.as_bytes();
+        Ok(CountryCode([bytes[0], bytes[1]]))
+    }
+}
+
+impl fmt::Display for CountryCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Serialized as the code, eg `"DE"`
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for CountryCode {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Deserialized from the code, failing if it is not assigned as for `from_str`
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for CountryCode {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(::serde::de::Error::custom)
+    }
+}
+
+/// A change of the regulatory domain, from a `CTRL-EVENT-REGDOM-CHANGE` event
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::RegdomChange;
+/// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+/// while let Some((_, msg)) = wpa.recv().unwrap() {
+///     if let Some(change) = RegdomChange::parse(&msg) {
+///         println!("Regulatory domain now {:?}, set by {}", change.country, change.initiator);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RegdomChange {
+    /// What caused the change, eg `USER`, `DRIVER`, `CORE` or `COUNTRY_IE`
+    pub initiator: String,
+    /// The kind of domain, eg `COUNTRY`, `WORLD` or `INTERSECTION`
+    pub kind: String,
+    /// The new country, for changes to a country's domain
+    pub country: Option<CountryCode>,
+}
+
+impl RegdomChange {
+    /// Parse a `CTRL-EVENT-REGDOM-CHANGE` event, returning None for other messages
+    pub fn parse(msg: &str) -> Option<Self> {
+        let body = strip_level(msg).trim().strip_prefix("CTRL-EVENT-REGDOM-CHANGE")?;
+        Some(RegdomChange {
+            initiator: event_arg(body, "init").unwrap_or("").to_owned(),
+            kind: event_arg(body, "type").unwrap_or("").to_owned(),
+            country: event_arg(body, "alpha2").and_then(|code| code.parse().ok()),
+        })
+    }
+}
+
+impl WpaCtrl {
+    /// The country used for regulatory settings, or None if none is configured
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// if let Some(country) = wpa.get_country().unwrap() {
+    ///     println!("country={}", country);
+    /// }
+    /// ```
+    pub fn get_country(&mut self) -> Result<Option<CountryCode>> {
+        let reply = self.request("GET country")?;
+        if let Some(e) = reply_error(&reply) {
+            return Err(e);
+        }
+        let reply = reply.trim_end();
+        if reply.is_empty() {
+            return Ok(None);
+        }
+        reply.parse().map(Some).map_err(|_| Error::Parse(reply.to_owned()))
+    }
+
+    /// Set the country used for regulatory settings
+    ///
+    /// `code` is validated before it is sent, failing with
+    /// `Error::InvalidCountry` if it is not an assigned ISO-3166-1 code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.set_country("US").unwrap();
+    /// ```
+    pub fn set_country(&mut self, code: &str) -> Result<()> {
+        let code: CountryCode = code.parse()?;
+        expect_ok(&self.request(&format!("SET country {}", code))?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn country_code() {
+        assert_eq!(ISO_3166.len(), 249 * 2);
+        assert_eq!("us".parse::<CountryCode>().unwrap().as_str(), "US");
+        assert_eq!("00".parse::<CountryCode>().unwrap(), CountryCode::WORLD);
+        assert!(matches!("XX".parse::<CountryCode>(), Err(Error::InvalidCountry(ref s)) if s == "XX"));
+        assert!("USA".parse::<CountryCode>().is_err());
+        assert!("".parse::<CountryCode>().is_err());
+    }
+
+    #[test]
+    fn regdom_change() {
+        assert_eq!(RegdomChange::parse("<3>CTRL-EVENT-REGDOM-CHANGE init=USER type=COUNTRY alpha2=DE"),
+                   Some(RegdomChange { initiator: "USER".into(), kind: "COUNTRY".into(), country: "DE".parse().ok() }));
+        assert_eq!(RegdomChange::parse("<3>CTRL-EVENT-REGDOM-CHANGE init=CORE type=WORLD").unwrap().country, None);
+        assert_eq!(RegdomChange::parse("<3>CTRL-EVENT-SCAN-STARTED "), None);
+    }
+}
@@ -0,0 +1,616 @@
+use error::Error;
+use network::NetworkId;
+use parse::{event, event_arg, hex_encode};
+use secret::REDACTED;
+use std::fmt;
+use std::time::Duration;
+use super::Result;
+use version::Version;
+use wpactrl::{expect_ok, reply_error, Request, WpaCtrl, WpaCtrlAttached};
+
+/// The DPP role a device takes during authentication
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DppRole {
+    /// Provision the peer with a network configuration
+    Configurator,
+    /// Receive a network configuration from the peer
+    Enrollee,
+    /// Take whichever role the peer does not take
+    Either,
+}
+
+impl fmt::Display for DppRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            DppRole::Configurator => "configurator",
+            DppRole::Enrollee => "enrollee",
+            DppRole::Either => "either",
+        })
+    }
+}
+
+/// Optional arguments of `WpaCtrl::dpp_bootstrap_gen`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DppBootstrapParams {
+    /// The channels to listen on, as `<op class>/<channel>` pairs, eg `81/1`
+    pub chan: Option<String>,
+    /// The MAC address to include in the URI
+    pub mac: Option<String>,
+    /// Additional information to include in the URI
+    pub info: Option<String>,
+    /// The elliptic curve of the key, eg `prime256v1`
+    pub curve: Option<String>,
+}
+
+impl DppBootstrapParams {
+    /// Format the arguments as expected by `DPP_BOOTSTRAP_GEN`
+    fn to_args(&self) -> String {
+        let mut args = "type=qrcode".to_owned();
+        let opts = [("chan", &self.chan), ("mac", &self.mac), ("info", &self.info), ("curve", &self.curve)];
+        for (key, value) in opts.iter() {
+            if let Some(ref value) = **value {
+                args.push_str(&format!(" {}={}", key, value));
+            }
+        }
+        args
+    }
+}
+
+/// Parameters of `WpaCtrl::dpp_auth_init`
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{DppAuthParams, DppRole};
+/// // Provision peer 1 with configurator 1 as a station of the signed network
+/// let params = DppAuthParams::new(1).role(DppRole::Configurator).configurator(1).conf("sta-dpp");
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct DppAuthParams {
+    peer: u32,
+    own: Option<u32>,
+    role: Option<DppRole>,
+    configurator: Option<u32>,
+    conf: Option<String>,
+    ssid: Option<String>,
+    pass: Option<String>,
+}
+
+/// Shows the passphrase as `[REDACTED]`
+impl fmt::Debug for DppAuthParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DppAuthParams")
+            .field("peer", &self.peer)
+            .field("own", &self.own)
+            .field("role", &self.role)
+            .field("configurator", &self.configurator)
+            .field("conf", &self.conf)
+            .field("ssid", &self.ssid)
+            .field("pass", &self.pass.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
+impl DppAuthParams {
+    /// Authenticate with the peer bootstrapped as `peer`, eg by `dpp_qr_code`
+    pub fn new(peer: u32) -> Self {
+        DppAuthParams { peer, own: None, role: None, configurator: None, conf: None, ssid: None, pass: None }
+    }
+
+    /// Use this device's own bootstrap key `id` for mutual authentication
+    pub fn own(mut self, id: u32) -> Self {
+        self.own = Some(id);
+        self
+    }
+
+    /// The role to take
+    pub fn role(mut self, role: DppRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    /// The configurator, as returned by `dpp_configurator_add`
+    pub fn configurator(mut self, id: u32) -> Self {
+        self.configurator = Some(id);
+        self
+    }
+
+    /// The configuration to provision, eg `sta-psk`, `sta-sae` or `sta-dpp`
+    pub fn conf(mut self, conf: &str) -> Self {
+        self.conf = Some(conf.to_owned());
+        self
+    }
+
+    /// The SSID of the network to provision
+    pub fn ssid(mut self, ssid: &str) -> Self {
+        self.ssid = Some(ssid.to_owned());
+        self
+    }
+
+    /// The passphrase of the network to provision
+    pub fn pass(mut self, pass: &str) -> Self {
+        self.pass = Some(pass.to_owned());
+        self
+    }
+
+    /// Format the arguments as expected by `DPP_AUTH_INIT`
+    ///
+    /// The SSID and passphrase are sent hex-encoded.
+    fn to_args(&self) -> String {
+        let mut args = format!("peer={}", self.peer);
+        if let Some(own) = self.own {
+            args.push_str(&format!(" own={}", own));
+        }
+        if let Some(role) = self.role {
+            args.push_str(&format!(" role={}", role));
+        }
+        if let Some(configurator) = self.configurator {
+            args.push_str(&format!(" configurator={}", configurator));
+        }
+        if let Some(ref conf) = self.conf {
+            args.push_str(&format!(" conf={}", conf));
+        }
+        if let Some(ref ssid) = self.ssid {
+            args.push_str(&format!(" ssid={}", hex_encode(ssid.as_bytes())));
+        }
+        if let Some(ref pass) = self.pass {
+            args.push_str(&format!(" pass={}", hex_encode(pass.as_bytes())));
+        }
+        args
+    }
+}
+
+/// Parse a reply holding the id of a newly created object
+fn parse_id(reply: &str) -> Result<u32> {
+    if let Some(e) = reply_error(reply) {
+        return Err(e);
+    }
+    reply.trim_end_matches('\n').parse().map_err(|_| Error::Parse(reply.to_owned()))
+}
+
+/// A DPP (Wi-Fi Easy Connect) event
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DppEvent {
+    /// Authentication succeeded (`DPP-AUTH-SUCCESS`)
+    AuthSuccess {
+        /// Whether this device initiated the authentication
+        initiator: bool,
+    },
+    /// Authentication could not be started or timed out (`DPP-AUTH-INIT-FAILED`)
+    AuthInitFailed,
+    /// The peer does not support the requested role (`DPP-NOT-COMPATIBLE`)
+    NotCompatible,
+    /// A configuration was received (`DPP-CONF-RECEIVED`)
+    ConfReceived,
+    /// A configuration was sent to the peer (`DPP-CONF-SENT`)
+    ConfSent,
+    /// The configuration exchange failed (`DPP-CONF-FAILED`)
+    ConfFailed,
+    /// The SSID of the received configuration (`DPP-CONFOBJ-SSID`)
+    ConfObjSsid(String),
+    /// The hex-encoded passphrase of the received configuration (`DPP-CONFOBJ-PASS`)
+    ConfObjPass(String),
+    /// The key management of the received configuration, eg `psk` (`DPP-CONFOBJ-AKM`)
+    ConfObjAkm(String),
+    /// The connector of the received configuration (`DPP-CONNECTOR`)
+    Connector(String),
+    /// The received configuration was added as a network (`DPP-NETWORK-ID`)
+    NetworkId(NetworkId),
+    /// A DPP operation failed, with a description (`DPP-FAIL`)
+    Fail(String),
+}
+
+/// Shows the passphrase of a received configuration as `[REDACTED]`
+impl fmt::Debug for DppEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DppEvent::AuthSuccess { ref initiator } => {
+                f.debug_struct("AuthSuccess").field("initiator", initiator).finish()
+            }
+            DppEvent::AuthInitFailed => f.write_str("AuthInitFailed"),
+            DppEvent::NotCompatible => f.write_str("NotCompatible"),
+            DppEvent::ConfReceived => f.write_str("ConfReceived"),
+            DppEvent::ConfSent => f.write_str("ConfSent"),
+            DppEvent::ConfFailed => f.write_str("ConfFailed"),
+            DppEvent::ConfObjSsid(ref ssid) => f.debug_tuple("ConfObjSsid").field(ssid).finish(),
+            DppEvent::ConfObjPass(_) => f.debug_tuple("ConfObjPass").field(&REDACTED).finish(),
+            DppEvent::ConfObjAkm(ref akm) => f.debug_tuple("ConfObjAkm").field(akm).finish(),
+            DppEvent::Connector(ref connector) => f.debug_tuple("Connector").field(connector).finish(),
+            DppEvent::NetworkId(ref id) => f.debug_tuple("NetworkId").field(id).finish(),
+            DppEvent::Fail(ref reason) => f.debug_tuple("Fail").field(reason).finish(),
+        }
+    }
+}
+
+impl DppEvent {
+    /// Parse a DPP event, returning None for other messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::DppEvent;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((_, msg)) = wpa.recv().unwrap() {
+    ///     if let Some(DppEvent::NetworkId(id)) = DppEvent::parse(&msg) {
+    ///         println!("Provisioned network {}", id);
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        match event {
+            "DPP-AUTH-SUCCESS" => Some(DppEvent::AuthSuccess { initiator: event_arg(body, "init") == Some("1") }),
+            "DPP-AUTH-INIT-FAILED" => Some(DppEvent::AuthInitFailed),
+            "DPP-NOT-COMPATIBLE" => Some(DppEvent::NotCompatible),
+            "DPP-CONF-RECEIVED" => Some(DppEvent::ConfReceived),
+            "DPP-CONF-SENT" => Some(DppEvent::ConfSent),
+            "DPP-CONF-FAILED" => Some(DppEvent::ConfFailed),
+            "DPP-CONFOBJ-SSID" => Some(DppEvent::ConfObjSsid(body.to_owned())),
+            "DPP-CONFOBJ-PASS" => Some(DppEvent::ConfObjPass(body.to_owned())),
+            "DPP-CONFOBJ-AKM" => Some(DppEvent::ConfObjAkm(body.to_owned())),
+            "DPP-CONNECTOR" => Some(DppEvent::Connector(body.to_owned())),
+            "DPP-NETWORK-ID" => body.parse().ok().map(|id| DppEvent::NetworkId(NetworkId(id))),
+            "DPP-FAIL" => Some(DppEvent::Fail(body.to_owned())),
+            _ => None,
+        }
+    }
+}
+
+/// The outcome of provisioning an enrollee with `WpaCtrlAttached::dpp_configure`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DppOutcome {
+    /// The configuration was sent to the enrollee
+    Sent,
+    /// Authentication could not be started or timed out
+    AuthFailed,
+    /// The enrollee does not support being configured
+    NotCompatible,
+    /// The configuration exchange failed
+    ConfFailed,
+    /// A DPP operation failed, with a description
+    Fail(String),
+    /// The enrollee did not answer before the timeout expired
+    Timeout,
+}
+
+impl DppOutcome {
+    /// Whether the configuration was sent
+    pub fn is_success(&self) -> bool {
+        *self == DppOutcome::Sent
+    }
+}
+
+/// The outcome of provisioning reported by an event, if any
+fn outcome(msg: &str) -> Option<DppOutcome> {
+    match DppEvent::parse(msg)? {
+        DppEvent::ConfSent => Some(DppOutcome::Sent),
+        DppEvent::AuthInitFailed => Some(DppOutcome::AuthFailed),
+        DppEvent::NotCompatible => Some(DppOutcome::NotCompatible),
+        DppEvent::ConfFailed => Some(DppOutcome::ConfFailed),
+        DppEvent::Fail(reason) => Some(DppOutcome::Fail(reason)),
+        _ => None,
+    }
+}
+
+/// A DPP configurator together with the configuration it provisions
+///
+/// The configurator is added to wpasupplicant the first time it is used
+/// with `WpaCtrlAttached::dpp_configure`, and reused afterwards.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::DppConfigurator;
+/// let configurator = DppConfigurator::new().conf("sta-psk").ssid("home").pass("correct horse battery staple");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DppConfigurator {
+    id: Option<u32>,
+    curve: Option<String>,
+    params: DppAuthParams,
+}
+
+impl DppConfigurator {
+    /// A configurator provisioning a DPP network (`sta-dpp`) until configured otherwise
+    pub fn new() -> Self {
+        DppConfigurator { id: None, curve: None, params: DppAuthParams::new(0).role(DppRole::Configurator) }
+    }
+
+    /// Use a configurator already added with `dpp_configurator_add`
+    pub fn existing(mut self, id: u32) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// The elliptic curve of the signing key, eg `prime256v1`
+    pub fn curve(mut self, curve: &str) -> Self {
+        self.curve = Some(curve.to_owned());
+        self
+    }
+
+    /// The configuration to provision, eg `sta-psk`, `sta-sae` or `sta-dpp`
+    pub fn conf(mut self, conf: &str) -> Self {
+        self.params = self.params.conf(conf);
+        self
+    }
+
+    /// The SSID of the network to provision
+    pub fn ssid(mut self, ssid: &str) -> Self {
+        self.params = self.params.ssid(ssid);
+        self
+    }
+
+    /// The passphrase of the network to provision
+    pub fn pass(mut self, pass: &str) -> Self {
+        self.params = self.params.pass(pass);
+        self
+    }
+
+    /// The id of the configurator in wpasupplicant, once it was added
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+}
+
+impl Default for DppConfigurator {
+    fn default() -> Self {
+        DppConfigurator::new()
+    }
+}
+
+/// Add a configurator, returning its id
+fn configurator_add<C: Request>(ctrl: &mut C, curve: Option<&str>) -> Result<u32> {
+    let cmd = match curve {
+        Some(curve) => format!("DPP_CONFIGURATOR_ADD curve={}", curve),
+        None => "DPP_CONFIGURATOR_ADD".to_owned(),
+    };
+    parse_id(&ctrl.request(&cmd)?)
+}
+
+impl WpaCtrlAttached {
+    /// Provision the enrollee of a scanned `DPP:` URI and wait for the outcome
+    ///
+    /// Adds the configurator if needed and the bootstrap key of the
+    /// enrollee, then authenticates with the enrollee and sends it the
+    /// configuration. If `timeout` expires first, the exchange is stopped
+    /// and `DppOutcome::Timeout` is returned. Events received while waiting
+    /// remain available through `recv`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wpactrl::DppConfigurator;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// let mut configurator = DppConfigurator::new().conf("sta-psk").ssid("home").pass("secret");
+    /// let uri = "DPP:K:MDkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDIgADURzxmttZoIRIPWGoQMV00XHWCAQIhXruVWOz0NjlkIA=;;";
+    /// println!("{:?}", wpa.dpp_configure(&mut configurator, uri, Duration::from_secs(30)).unwrap());
+    /// ```
+    pub fn dpp_configure(&mut self, configurator: &mut DppConfigurator, uri: &str, timeout: Duration)
+                         -> Result<DppOutcome> {
+        let id = match configurator.id {
+            Some(id) => id,
+            None => {
+                let id = configurator_add(self, configurator.curve.as_deref())?;
+                configurator.id = Some(id);
+                id
+            }
+        };
+        let peer = parse_id(&self.request(&format!("DPP_QR_CODE {}", uri))?)?;
+        let mut params = configurator.params.clone().configurator(id);
+        params.peer = peer;
+        let from = self.queued();
+        let result = expect_ok(&self.request(&format!("DPP_AUTH_INIT {}", params.to_args()))?)
+            .and_then(|_| self.wait_for(from, timeout, outcome));
+        let outcome = match result {
+            Ok(Some(outcome)) => Ok(outcome),
+            Ok(None) => expect_ok(&self.request("DPP_STOP_LISTEN")?).map(|_| DppOutcome::Timeout),
+            Err(e) => Err(e),
+        };
+        let _ = self.request(&format!("DPP_BOOTSTRAP_REMOVE {}", peer));
+        outcome
+    }
+}
+
+impl WpaCtrl {
+    /// Generate a bootstrap key to be shown as a QR code, returning its id
+    ///
+    /// Fails with `Error::NotSupported` on releases before 2.7, which lack DPP.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::DppBootstrapParams;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let params = DppBootstrapParams { chan: Some("81/1".into()), ..Default::default() };
+    /// let id = wpa.dpp_bootstrap_gen(&params).unwrap();
+    /// println!("{}", wpa.dpp_bootstrap_get_uri(id).unwrap());
+    /// ```
+    pub fn dpp_bootstrap_gen(&mut self, params: &DppBootstrapParams) -> Result<u32> {
+        let id = parse_id(&self.request(&format!("DPP_BOOTSTRAP_GEN {}", params.to_args()))?);
+        self.since(Version::new(2, 7), id)
+    }
+
+    /// The `DPP:` URI of a bootstrap key, to be encoded as a QR code
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::DppBootstrapParams;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.dpp_bootstrap_gen(&DppBootstrapParams::default()).unwrap();
+    /// println!("{}", wpa.dpp_bootstrap_get_uri(id).unwrap());
+    /// ```
+    pub fn dpp_bootstrap_get_uri(&mut self, id: u32) -> Result<String> {
+        let reply = self.request(&format!("DPP_BOOTSTRAP_GET_URI {}", id))?;
+        match reply_error(&reply) {
+            Some(e) => Err(e),
+            None => Ok(reply.trim_end_matches('\n').to_owned()),
+        }
+    }
+
+    /// Add the peer bootstrap key from a scanned `DPP:` URI, returning its id
+    ///
+    /// A `DppUri` can be passed with `to_string`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let peer = wpa.dpp_qr_code("DPP:K:MDkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDIgADURzxmttZoIRIPWGoQMV00XHWCAQIhXruVWOz0NjlkIA=;;")
+    ///     .unwrap();
+    /// ```
+    pub fn dpp_qr_code(&mut self, uri: &str) -> Result<u32> {
+        parse_id(&self.request(&format!("DPP_QR_CODE {}", uri))?)
+    }
+
+    /// Start authenticating with a peer
+    ///
+    /// Progress is reported with `DppEvent`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::DppAuthParams;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.dpp_auth_init(&DppAuthParams::new(1)).unwrap();
+    /// ```
+    pub fn dpp_auth_init(&mut self, params: &DppAuthParams) -> Result<()> {
+        expect_ok(&self.request(&format!("DPP_AUTH_INIT {}", params.to_args()))?)
+    }
+
+    /// Listen on `freq` MHz for authentication requests from a peer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::DppRole;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.dpp_listen(2412, Some(DppRole::Enrollee)).unwrap();
+    /// ```
+    pub fn dpp_listen(&mut self, freq: u32, role: Option<DppRole>) -> Result<()> {
+        let cmd = match role {
+            Some(role) => format!("DPP_LISTEN {} role={}", freq, role),
+            None => format!("DPP_LISTEN {}", freq),
+        };
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Stop listening for authentication requests
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.dpp_stop_listen().unwrap();
+    /// ```
+    pub fn dpp_stop_listen(&mut self) -> Result<()> {
+        expect_ok(&self.request("DPP_STOP_LISTEN")?)
+    }
+
+    /// Add a configurator, returning its id
+    ///
+    /// `curve` selects the elliptic curve of the signing key, eg
+    /// `prime256v1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let configurator = wpa.dpp_configurator_add(None).unwrap();
+    /// ```
+    pub fn dpp_configurator_add(&mut self, curve: Option<&str>) -> Result<u32> {
+        configurator_add(self, curve)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn params() {
+        assert_eq!(DppBootstrapParams::default().to_args(), "type=qrcode");
+        let params = DppBootstrapParams { chan: Some("81/1".into()), mac: Some("02:00:00:00:00:01".into()), ..Default::default() };
+        assert_eq!(params.to_args(), "type=qrcode chan=81/1 mac=02:00:00:00:00:01");
+        assert_eq!(DppAuthParams::new(1).to_args(), "peer=1");
+        assert_eq!(DppAuthParams::new(2).own(1).role(DppRole::Configurator).configurator(1).conf("sta-psk")
+                       .ssid("home").pass("secret").to_args(),
+                   "peer=2 own=1 role=configurator configurator=1 conf=sta-psk ssid=686f6d65 pass=736563726574");
+    }
+
+    #[test]
+    fn ids() {
+        assert_eq!(parse_id("1\n").unwrap(), 1);
+        assert!(parse_id("FAIL\n").is_err());
+        assert!(parse_id("OK\n").is_err());
+    }
+
+    #[test]
+    fn events() {
+        assert_eq!(DppEvent::parse("<3>DPP-AUTH-SUCCESS init=1"), Some(DppEvent::AuthSuccess { initiator: true }));
+        assert_eq!(DppEvent::parse("<3>DPP-AUTH-SUCCESS init=0"), Some(DppEvent::AuthSuccess { initiator: false }));
+        assert_eq!(DppEvent::parse("<3>DPP-CONF-RECEIVED "), Some(DppEvent::ConfReceived));
+        assert_eq!(DppEvent::parse("<3>DPP-CONFOBJ-SSID my network"), Some(DppEvent::ConfObjSsid("my network".into())));
+        assert_eq!(DppEvent::parse("<3>DPP-CONFOBJ-PASS 736563726574"),
+                   Some(DppEvent::ConfObjPass("736563726574".into())));
+        assert_eq!(format!("{:?}", DppEvent::ConfObjPass("736563726574".into())), "ConfObjPass(\"[REDACTED]\")");
+        assert_eq!(DppEvent::parse("<3>DPP-NETWORK-ID 3"), Some(DppEvent::NetworkId(NetworkId(3))));
+        assert_eq!(DppEvent::parse("<3>DPP-FAIL Configurator rejected configuration"),
+                   Some(DppEvent::Fail("Configurator rejected configuration".into())));
+        assert_eq!(DppEvent::parse("<3>DPP-NETWORK-ID"), None);
+        assert_eq!(DppEvent::parse("<3>WPS-SUCCESS"), None);
+    }
+
+    #[test]
+    fn outcomes() {
+        assert_eq!(outcome("<3>DPP-CONF-SENT "), Some(DppOutcome::Sent));
+        assert_eq!(outcome("<3>DPP-AUTH-INIT-FAILED"), Some(DppOutcome::AuthFailed));
+        assert_eq!(outcome("<3>DPP-FAIL No matching own bootstrapping key found"),
+                   Some(DppOutcome::Fail("No matching own bootstrapping key found".into())));
+        assert_eq!(outcome("<3>DPP-AUTH-SUCCESS init=1"), None);
+    }
+
+    #[test]
+    fn configure() {
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_dpp_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("DPP_CONFIGURATOR_ADD", "1\n");
+        mock.reply("DPP_QR_CODE DPP:K:MDkw;;", "2\n");
+        mock.reply("DPP_AUTH_INIT peer=2 role=configurator configurator=1 conf=sta-psk ssid=686f6d65 pass=736563726574",
+                   "OK\n");
+        mock.reply("DPP_STOP_LISTEN", "OK\n");
+        mock.reply("DPP_BOOTSTRAP_REMOVE 2", "OK\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        let mut configurator = DppConfigurator::new().conf("sta-psk").ssid("home").pass("secret");
+        let timeout = Duration::from_millis(10);
+        assert_eq!(wpa.dpp_configure(&mut configurator, "DPP:K:MDkw;;", timeout).unwrap(), DppOutcome::Timeout);
+        assert_eq!(configurator.id(), Some(1));
+        wpa.dpp_configure(&mut configurator, "DPP:K:MDkw;;", timeout).unwrap();
+        let commands = mock.commands();
+        assert_eq!(commands[1..6], [
+            "DPP_CONFIGURATOR_ADD", "DPP_QR_CODE DPP:K:MDkw;;",
+            "DPP_AUTH_INIT peer=2 role=configurator configurator=1 conf=sta-psk ssid=686f6d65 pass=736563726574",
+            "DPP_STOP_LISTEN", "DPP_BOOTSTRAP_REMOVE 2",
+        ]);
+        assert_eq!(commands[6], "DPP_QR_CODE DPP:K:MDkw;;");
+    }
+
+    #[test]
+    fn bootstrap_get_uri() {
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_dpp_uri_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("DPP_BOOTSTRAP_GET_URI 1", "DPP:K:MDkw;;\n");
+        mock.reply("DPP_BOOTSTRAP_GET_URI 2", "FAIL\n");
+        mock.reply("DPP_BOOTSTRAP_GET_URI 3", "UNKNOWN COMMAND\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert_eq!(wpa.dpp_bootstrap_get_uri(1).unwrap(), "DPP:K:MDkw;;");
+        assert!(matches!(wpa.dpp_bootstrap_get_uri(2), Err(Error::Failure)));
+        assert!(matches!(wpa.dpp_bootstrap_get_uri(3), Err(Error::UnknownCommand)));
+    }
+}
@@ -0,0 +1,131 @@
+use error::Error;
+use macaddr::MacAddr;
+use parse::{hex_decode, hex_encode};
+use std::fmt;
+use std::str::FromStr;
+use super::Result;
+
+/// A DPP bootstrapping URI, as encoded in QR codes
+///
+/// Parsed from a scanned `DPP:` string or from `WpaCtrl::dpp_bootstrap_get_uri`,
+/// and turned back into one with `to_string`, eg for `WpaCtrl::dpp_qr_code`.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::DppUri;
+/// let uri: DppUri = "DPP:C:81/1,115/36;M:020000000001;K:MDkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDIgADURzxmttZ;;"
+///     .parse().unwrap();
+/// assert_eq!(uri.channels, vec![(81, 1), (115, 36)]);
+/// assert_eq!(uri.mac, "02:00:00:00:00:01".parse().ok());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DppUri {
+    /// The channels the device listens on, as `(operating class, channel)` pairs (`C:`)
+    pub channels: Vec<(u8, u8)>,
+    /// The MAC address of the device (`M:`)
+    pub mac: Option<MacAddr>,
+    /// Free-form information about the device (`I:`)
+    pub info: Option<String>,
+    /// The highest DPP version the device supports (`V:`)
+    pub version: Option<u8>,
+    /// The base64-encoded public bootstrapping key (`K:`)
+    pub key: String,
+    /// Fields not known to this library, as `(tag, value)` pairs in their original order
+    pub other: Vec<(String, String)>,
+}
+
+impl DppUri {
+    /// A URI holding only the public key
+    pub fn new(key: &str) -> Self {
+        DppUri { channels: Vec::new(), mac: None, info: None, version: None, key: key.to_owned(), other: Vec::new() }
+    }
+}
+
+/// Parse an `<op class>/<channel>` pair
+fn parse_channel(s: &str) -> Option<(u8, u8)> {
+    let mut parts = s.splitn(2, '/');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// Parse a MAC address written as 12 hex digits without separators
+fn parse_mac(s: &str) -> Option<MacAddr> {
+    let bytes = hex_decode(s).filter(|bytes| bytes.len() == 6)?;
+    let mut octets = [0; 6];
+    octets.copy_from_slice(&bytes);
+    Some(MacAddr::new(octets))
+}
+
+impl FromStr for DppUri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || Error::Parse(s.to_owned());
+        let body = s.trim().strip_prefix("DPP:").ok_or_else(err)?;
+        let mut uri = DppUri::new("");
+        for field in body.split(';').filter(|field| !field.is_empty()) {
+            let mut parts = field.splitn(2, ':');
+            let (tag, value) = (parts.next().unwrap_or(""), parts.next().ok_or_else(err)?);
+            match tag {
+                "C" => uri.channels = value.split(',').map(parse_channel).collect::<Option<_>>().ok_or_else(err)?,
+                "M" => uri.mac = Some(parse_mac(value).ok_or_else(err)?),
+                "I" => uri.info = Some(value.to_owned()),
+                "V" => uri.version = Some(value.parse().map_err(|_| err())?),
+                "K" => uri.key = value.to_owned(),
+                _ => uri.other.push((tag.to_owned(), value.to_owned())),
+            }
+        }
+        if uri.key.is_empty() {
+            return Err(err());
+        }
+        Ok(uri)
+    }
+}
+
+impl fmt::Display for DppUri {
+    /// Format the URI with the fields in the order wpasupplicant uses
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DPP:")?;
+        if !self.channels.is_empty() {
+            let channels: Vec<_> = self.channels.iter().map(|&(class, chan)| format!("{}/{}", class, chan)).collect();
+            write!(f, "C:{};", channels.join(","))?;
+        }
+        if let Some(mac) = self.mac {
+            write!(f, "M:{};", hex_encode(&mac.octets()))?;
+        }
+        if let Some(ref info) = self.info {
+            write!(f, "I:{};", info)?;
+        }
+        if let Some(version) = self.version {
+            write!(f, "V:{};", version)?;
+        }
+        for (tag, value) in &self.other {
+            write!(f, "{}:{};", tag, value)?;
+        }
+        write!(f, "K:{};;", self.key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn uri() {
+        let s = "DPP:C:81/1,115/36;M:5254005828e5;I:SN=4774LH2b4044;V:2;H:10.0.0.1;K:MDkwEwYHKoZIzj0CAQYIKoZIzj0D;;";
+        let uri: DppUri = s.parse().unwrap();
+        assert_eq!(uri.channels, vec![(81, 1), (115, 36)]);
+        assert_eq!(uri.mac, "52:54:00:58:28:e5".parse().ok());
+        assert_eq!(uri.info, Some("SN=4774LH2b4044".into()));
+        assert_eq!(uri.version, Some(2));
+        assert_eq!(uri.other, vec![("H".to_owned(), "10.0.0.1".to_owned())]);
+        assert_eq!(uri.key, "MDkwEwYHKoZIzj0CAQYIKoZIzj0D");
+        assert_eq!(uri.to_string(), s);
+        assert_eq!(DppUri::new("MDkw").to_string(), "DPP:K:MDkw;;");
+        assert_eq!("DPP:K:MDkw;;\n".parse::<DppUri>().unwrap(), DppUri::new("MDkw"));
+        assert!("DPP:C:81/1;;".parse::<DppUri>().is_err());
+        assert!("DPP:M:5254;K:MDkw;;".parse::<DppUri>().is_err());
+        assert!("WIFI:S:home;;".parse::<DppUri>().is_err());
+    }
+}
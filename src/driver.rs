@@ -0,0 +1,83 @@
+use error::Error;
+use parse::{hex_decode, hex_encode};
+use super::Result;
+use wpactrl::{reply_error, WpaCtrl};
+
+impl WpaCtrl {
+    /// Send a vendor-specific command to the driver and return its reply data
+    ///
+    /// `oui` is the vendor's OUI, eg `0x001374` for Qualcomm Atheros, and
+    /// `subcmd` the vendor command number. The payload and reply are hex
+    /// encoded on the control interface; this handles that in both
+    /// directions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let reply = wpa.vendor(0x001374, 1, &[0x01, 0x02]).unwrap();
+    /// println!("{} bytes", reply.len());
+    /// ```
+    pub fn vendor(&mut self, oui: u32, subcmd: u32, payload: &[u8]) -> Result<Vec<u8>> {
+        let cmd = if payload.is_empty() {
+            format!("VENDOR {:x} {}", oui, subcmd)
+        } else {
+            format!("VENDOR {:x} {} {}", oui, subcmd, hex_encode(payload))
+        };
+        parse_vendor_reply(&self.request(&cmd)?)
+    }
+
+    /// Send a private command to the driver and return its reply verbatim
+    ///
+    /// Such commands, eg `MACADDR` or `COUNTRY DE` on Android, are specific
+    /// to the platform and driver, so their replies are not interpreted.
+    /// A reply rejecting the command, such as `FAIL`, is returned as an
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// println!("{}", wpa.driver_cmd("MACADDR").unwrap());
+    /// ```
+    pub fn driver_cmd(&mut self, cmd: &str) -> Result<String> {
+        let reply = self.request(&format!("DRIVER {}", cmd))?;
+        match reply_error(&reply) {
+            Some(e) => Err(e),
+            None => Ok(reply),
+        }
+    }
+}
+
+/// Parse the reply to `VENDOR`, a possibly empty hex dump
+fn parse_vendor_reply(reply: &str) -> Result<Vec<u8>> {
+    if let Some(e) = reply_error(reply) {
+        return Err(e);
+    }
+    hex_decode(reply).ok_or_else(|| Error::Parse(reply.to_owned()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vendor_reply() {
+        assert_eq!(parse_vendor_reply("0a0b\n").unwrap(), vec![0x0a, 0x0b]);
+        assert_eq!(parse_vendor_reply("").unwrap(), Vec::<u8>::new());
+        assert!(matches!(parse_vendor_reply("FAIL\n"), Err(Error::Failure)));
+        assert!(matches!(parse_vendor_reply("NOT-SUPPORTED\n"), Err(Error::NotSupported)));
+    }
+
+    #[test]
+    fn driver_cmd() {
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_driver_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("DRIVER MACADDR", "Macaddr = 02:00:00:00:01:00\n");
+        mock.reply("DRIVER COUNTRY XX", "FAIL\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert_eq!(wpa.driver_cmd("MACADDR").unwrap(), "Macaddr = 02:00:00:00:01:00\n");
+        assert!(matches!(wpa.driver_cmd("COUNTRY XX"), Err(Error::Failure)));
+    }
+}
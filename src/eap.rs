@@ -0,0 +1,166 @@
+use network::{NetworkConfig, NetworkValue};
+use std::fmt;
+
+/// An EAP method, as set in the `eap` network variable
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EapMethod {
+    /// Protected EAP, usually with MSCHAPv2 inside
+    Peap,
+    /// EAP-TTLS, with a PAP, CHAP, MSCHAPv2 or EAP inner method
+    Ttls,
+    /// EAP-TLS, authenticating with a client certificate
+    Tls,
+    /// EAP-PWD, authenticating with a password only
+    Pwd,
+    /// EAP-FAST
+    Fast,
+    /// Any other method, by name
+    Other(String),
+}
+
+impl fmt::Display for EapMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            EapMethod::Peap => "PEAP",
+            EapMethod::Ttls => "TTLS",
+            EapMethod::Tls => "TLS",
+            EapMethod::Pwd => "PWD",
+            EapMethod::Fast => "FAST",
+            EapMethod::Other(ref name) => name,
+        })
+    }
+}
+
+/// Where a certificate or private key is read from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CertSource {
+    /// A file on the device running wpasupplicant
+    Path(String),
+    /// A blob stored with `WpaCtrl::set_blob`, by name
+    Blob(String),
+}
+
+impl CertSource {
+    fn to_value(&self) -> NetworkValue {
+        match *self {
+            CertSource::Path(ref path) => NetworkValue::Str(path.clone()),
+            CertSource::Blob(ref name) => NetworkValue::Str(format!("blob://{}", name)),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for CertSource {
+    fn from(path: &'a str) -> Self {
+        CertSource::Path(path.to_owned())
+    }
+}
+
+/// Configuration for a WPA-Enterprise (802.1X / EAP) network block
+///
+/// Converts into a `NetworkConfig`, so it can be passed to
+/// `create_network` or `connect` directly.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{CertSource, EapMethod, EapNetworkConfig};
+/// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+/// let config = EapNetworkConfig::new("eduroam", EapMethod::Peap)
+///     .identity("user@example.org")
+///     .anonymous_identity("anonymous@example.org")
+///     .password("hunter2")
+///     .ca_cert(CertSource::Blob("ca".into()))
+///     .phase2("auth=MSCHAPV2");
+/// wpa.create_network(config).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct EapNetworkConfig(pub(crate) NetworkConfig);
+
+impl EapNetworkConfig {
+    /// A network using `key_mgmt=WPA-EAP` and the given EAP method
+    pub fn new<S: Into<String>>(ssid: S, method: EapMethod) -> Self {
+        EapNetworkConfig(NetworkConfig::open(ssid)
+            .set("key_mgmt", NetworkValue::Raw("WPA-EAP".into()))
+            .set("eap", NetworkValue::Raw(method.to_string())))
+    }
+
+    /// The identity to authenticate as
+    pub fn identity<S: Into<String>>(self, identity: S) -> Self {
+        self.set("identity", identity.into())
+    }
+
+    /// The identity sent in the clear by tunneled methods such as PEAP and TTLS
+    pub fn anonymous_identity<S: Into<String>>(self, identity: S) -> Self {
+        self.set("anonymous_identity", identity.into())
+    }
+
+    /// The password for password-based methods
+    pub fn password<S: Into<String>>(self, password: S) -> Self {
+        self.set("password", password.into())
+    }
+
+    /// The CA certificate used to verify the authentication server
+    pub fn ca_cert<C: Into<CertSource>>(self, cert: C) -> Self {
+        let value = cert.into().to_value();
+        self.set("ca_cert", value)
+    }
+
+    /// The client certificate, for EAP-TLS
+    pub fn client_cert<C: Into<CertSource>>(self, cert: C) -> Self {
+        let value = cert.into().to_value();
+        self.set("client_cert", value)
+    }
+
+    /// The private key of the client certificate, for EAP-TLS
+    pub fn private_key<C: Into<CertSource>>(self, key: C) -> Self {
+        let value = key.into().to_value();
+        self.set("private_key", value)
+    }
+
+    /// The passphrase protecting the private key
+    pub fn private_key_passwd<S: Into<String>>(self, passwd: S) -> Self {
+        self.set("private_key_passwd", passwd.into())
+    }
+
+    /// Outer authentication options, eg `peapver=0`
+    pub fn phase1<S: Into<String>>(self, phase1: S) -> Self {
+        self.set("phase1", phase1.into())
+    }
+
+    /// Inner authentication options, eg `auth=MSCHAPV2`
+    pub fn phase2<S: Into<String>>(self, phase2: S) -> Self {
+        self.set("phase2", phase2.into())
+    }
+
+    /// Set any other network variable, replacing any previous value
+    pub fn set<V: Into<NetworkValue>>(self, key: &str, value: V) -> Self {
+        EapNetworkConfig(self.0.set(key, value))
+    }
+}
+
+impl From<EapNetworkConfig> for NetworkConfig {
+    fn from(config: EapNetworkConfig) -> Self {
+        config.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn eap_network_config() {
+        let config: NetworkConfig = EapNetworkConfig::new("eduroam", EapMethod::Ttls)
+            .identity("user")
+            .ca_cert("/etc/ssl/ca.pem")
+            .client_cert(CertSource::Blob("client".into()))
+            .phase2("auth=PAP")
+            .into();
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("WPA-EAP".into())));
+        assert_eq!(config.get("eap"), Some(&NetworkValue::Raw("TTLS".into())));
+        assert_eq!(config.get("identity"), Some(&NetworkValue::Str("user".into())));
+        assert_eq!(config.get("ca_cert"), Some(&NetworkValue::Str("/etc/ssl/ca.pem".into())));
+        assert_eq!(config.get("client_cert"), Some(&NetworkValue::Str("blob://client".into())));
+        assert_eq!(config.get("phase2"), Some(&NetworkValue::Str("auth=PAP".into())));
+    }
+}
@@ -0,0 +1,145 @@
+use macaddr::MacAddr;
+use nix;
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::Utf8Error;
+
+/// Error type used for the library
+///
+/// Callers can match on the kind of error directly. Underlying I/O and
+/// UTF-8 errors are available through `std::error::Error::source`.
+#[derive(Debug)]
+pub enum Error {
+    /// wpasupplicant / hostapd rejected the command
+    Failure,
+    /// wpasupplicant / hostapd does not know the command, eg because it was built without it
+    UnknownCommand,
+    /// The command is known but not supported, eg by the driver or by an older release
+    NotSupported,
+    /// The reply could not be parsed
+    Parse(String),
+    /// `SAVE_CONFIG` failed because wpasupplicant runs with `update_config=0`
+    UpdateConfigDisabled,
+    /// The socket of wpasupplicant / hostapd went away, eg because it exited
+    ConnectionLost,
+    /// No live interface was found in the control directory
+    NoInterface(PathBuf),
+    /// The access point is not in the scan results
+    UnknownBss(MacAddr),
+    /// The hostapd interface has no BSS of the given name, see `MultiBss`
+    NoSuchBss(String),
+    /// A message was longer than the receive buffer, of the given size
+    ///
+    /// The buffer size can be raised with `WpaCtrlBuilder::buffer_size`.
+    Truncated(usize),
+    /// wpasupplicant / hostapd did not reply in time
+    Timeout,
+    /// There is no control socket at the given path, or nothing listens on it
+    ///
+    /// This usually means wpasupplicant is not running on the interface.
+    NoSuchInterface(PathBuf),
+    /// This process is not allowed to use the control socket at the given path
+    ///
+    /// The socket's group is set by `GROUP=` in `ctrl_interface`, or by
+    /// `ctrl_interface_group`, in the wpasupplicant configuration; this
+    /// process needs to run as root or in that group.
+    PermissionDenied(PathBuf),
+    /// The path given as control socket is not a socket
+    NotASocket(PathBuf),
+    /// The control interface at the given path could not be opened for another reason
+    Open(PathBuf, io::Error),
+    /// The command at the given index of a batch failed, with the given error
+    ///
+    /// The commands before it were sent successfully.
+    Batch(usize, Box<Error>),
+    /// The given country code is not an ISO-3166-1 alpha-2 code
+    InvalidCountry(String),
+    /// A reply or message was not valid UTF-8
+    Utf8(Utf8Error),
+    /// Any other I/O error
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Failure => write!(f, "Failed to execute the specified command"),
+            Error::UnknownCommand => write!(f, "Unknown command"),
+            Error::NotSupported => write!(f, "Command not supported"),
+            Error::Parse(ref reply) => write!(f, "Unable to parse reply: {:?}", reply),
+            Error::UpdateConfigDisabled => write!(f, "Configuration updates are disabled (update_config=0)"),
+            Error::ConnectionLost => write!(f, "Lost the connection to the control interface"),
+            Error::NoInterface(ref dir) => write!(f, "No control interface found in {:?}", dir),
+            Error::UnknownBss(ref bssid) => write!(f, "BSS {} not found in the scan results", bssid),
+            Error::NoSuchBss(ref name) => write!(f, "No BSS named {:?} on the interface", name),
+            Error::Truncated(size) => write!(f, "Message longer than the {} byte receive buffer", size),
+            Error::Timeout => write!(f, "Timed out waiting for a reply"),
+            Error::NoSuchInterface(ref path) => write!(f, "No control interface at {:?}", path),
+            Error::PermissionDenied(ref path) => write!(f, "Permission denied for control interface {:?}; run as root or \
+                                                            in the group set by GROUP= in ctrl_interface", path),
+            Error::NotASocket(ref path) => write!(f, "{:?} is not a socket", path),
+            Error::Open(ref path, ref e) => write!(f, "Unable to open control interface {:?}: {}", path, e),
+            Error::Batch(i, ref e) => write!(f, "Command {} of the batch failed: {}", i, e),
+            Error::InvalidCountry(ref code) => write!(f, "Invalid country code {:?}", code),
+            Error::Utf8(ref e) => write!(f, "Invalid UTF-8: {}", e),
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Open(_, ref e) | Error::Io(ref e) => Some(e),
+            Error::Utf8(ref e) => Some(e),
+            Error::Batch(_, ref e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(e: Utf8Error) -> Self {
+        Error::Utf8(e)
+    }
+}
+
+impl From<nix::Error> for Error {
+    fn from(e: nix::Error) -> Self {
+        match e {
+            nix::Error::Sys(errno) => Error::Io(io::Error::from_raw_os_error(errno as i32)),
+            e => Error::Io(io::Error::other(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::error::Error as StdError;
+
+    #[test]
+    fn source() {
+        let e = Error::Open("/var/run/wpa_supplicant/wlan0".into(), io::ErrorKind::NotFound.into());
+        assert_eq!(e.to_string(), "Unable to open control interface \"/var/run/wpa_supplicant/wlan0\": entity not found");
+        assert!(e.source().is_some());
+        assert!(Error::Failure.source().is_none());
+    }
+
+    #[test]
+    fn from_nix() {
+        let e = Error::from(nix::Error::Sys(nix::errno::Errno::EACCES));
+        match e {
+            Error::Io(ref e) => assert_eq!(e.kind(), io::ErrorKind::PermissionDenied),
+            _ => panic!("unexpected error {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use eap::EapNetworkConfig;
+use macaddr::MacAddr;
+use network::NetworkValue;
+use parse::{event, event_arg, hex_decode};
+
+impl EapNetworkConfig {
+    /// Allow FILS alongside WPA-EAP, with ERP for fast re-authentication
+    ///
+    /// FILS (802.11ai) connects in a single round trip once EAP keys were
+    /// established, which speeds up roaming between access points of the
+    /// same network. This replaces `key_mgmt` and enables `erp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{EapMethod, EapNetworkConfig};
+    /// let config = EapNetworkConfig::new("corp", EapMethod::Peap).identity("user").password("hunter2").fils();
+    /// ```
+    pub fn fils(self) -> Self {
+        self.set("key_mgmt", NetworkValue::Raw("WPA-EAP FILS-SHA256 FILS-SHA384".into()))
+            .set("erp", true)
+    }
+
+    /// Use FILS shared key authentication with perfect forward secrecy, using a Diffie-Hellman group, eg 19
+    pub fn fils_dh_group(self, group: u16) -> Self {
+        self.set("fils_dh_group", group)
+    }
+}
+
+/// Authentication algorithm numbers of FILS shared key, FILS shared key with PFS and FILS public key
+const AUTH_TYPES_FILS: &[&str] = &["4", "5", "6"];
+
+/// A FILS event
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FilsEvent {
+    /// A higher layer protocol packet, eg a DHCP reply, was received during association (`FILS-HLP-RX`)
+    HlpRx {
+        /// The destination address of the packet
+        dst: MacAddr,
+        /// The source address of the packet
+        src: MacAddr,
+        /// The packet, starting with its LLC header
+        frame: Vec<u8>,
+    },
+    /// The access point rejected FILS authentication (`CTRL-EVENT-AUTH-REJECT`)
+    AuthRejected {
+        /// The BSSID of the access point
+        bssid: MacAddr,
+        /// The IEEE 802.11 status code
+        status_code: u16,
+    },
+}
+
+impl FilsEvent {
+    /// Parse a FILS event, returning None for other messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::FilsEvent;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((_, msg)) = wpa.recv().unwrap() {
+    ///     if let Some(FilsEvent::HlpRx { src, frame, .. }) = FilsEvent::parse(&msg) {
+    ///         println!("{} bytes from {} during association", frame.len(), src);
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        let addr = |key| event_arg(body, key)?.parse().ok();
+        match event {
+            "FILS-HLP-RX" => Some(FilsEvent::HlpRx {
+                dst: addr("dst")?,
+                src: addr("src")?,
+                frame: hex_decode(event_arg(body, "frame")?)?,
+            }),
+            "CTRL-EVENT-AUTH-REJECT" if AUTH_TYPES_FILS.contains(&event_arg(body, "auth_type")?) => {
+                Some(FilsEvent::AuthRejected {
+                    bssid: body.split(' ').next()?.parse().ok()?,
+                    status_code: event_arg(body, "status_code")?.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use eap::EapMethod;
+    use network::NetworkConfig;
+
+    #[test]
+    fn fils() {
+        let config: NetworkConfig = EapNetworkConfig::new("corp", EapMethod::Peap).fils().fils_dh_group(19).into();
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("WPA-EAP FILS-SHA256 FILS-SHA384".into())));
+        assert_eq!(config.get("erp"), Some(&NetworkValue::Raw("1".into())));
+        assert_eq!(config.get("fils_dh_group"), Some(&NetworkValue::Raw("19".into())));
+    }
+
+    #[test]
+    fn events() {
+        assert_eq!(FilsEvent::parse("<3>FILS-HLP-RX dst=02:00:00:00:00:01 src=02:00:00:00:01:00 frame=aaaa0300"),
+                   Some(FilsEvent::HlpRx {
+                       dst: "02:00:00:00:00:01".parse().unwrap(),
+                       src: "02:00:00:00:01:00".parse().unwrap(),
+                       frame: vec![0xaa, 0xaa, 0x03, 0x00],
+                   }));
+        assert_eq!(FilsEvent::parse("<3>CTRL-EVENT-AUTH-REJECT 02:00:00:00:01:00 auth_type=4 auth_transaction=2 \
+                                     status_code=112"),
+                   Some(FilsEvent::AuthRejected { bssid: "02:00:00:00:01:00".parse().unwrap(), status_code: 112 }));
+        assert_eq!(FilsEvent::parse("<3>CTRL-EVENT-AUTH-REJECT 02:00:00:00:01:00 auth_type=3 auth_transaction=1 \
+                                     status_code=123"),
+                   None);
+        assert_eq!(FilsEvent::parse("<3>FILS-HLP-RX dst=02:00:00:00:00:01"), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+
+/// A Wi-Fi frequency band
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Band {
+    /// The 2.4 GHz band, channels 1 to 14
+    Ghz2_4,
+    /// The 5 GHz band, including the 4.9 GHz channels used in Japan
+    Ghz5,
+    /// The 6 GHz band
+    Ghz6,
+}
+
+impl Band {
+    /// The band of a frequency in MHz, or None if it is not in a known band
+    pub fn from_freq(freq: u32) -> Option<Self> {
+        freq_to_channel(freq).map(|(band, _)| band)
+    }
+}
+
+impl fmt::Display for Band {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Band::Ghz2_4 => "2.4 GHz",
+            Band::Ghz5 => "5 GHz",
+            Band::Ghz6 => "6 GHz",
+        })
+    }
+}
+
+/// The band and channel number of a frequency in MHz
+///
+/// Returns None if the frequency is not the center of a channel in a known
+/// band. Follows the mapping used by Linux and wpasupplicant.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{freq_to_channel, Band};
+/// assert_eq!(freq_to_channel(2437), Some((Band::Ghz2_4, 6)));
+/// assert_eq!(freq_to_channel(5180), Some((Band::Ghz5, 36)));
+/// assert_eq!(freq_to_channel(5955), Some((Band::Ghz6, 1)));
+/// ```
+pub fn freq_to_channel(freq: u32) -> Option<(Band, u32)> {
+    let (band, base) = match freq {
+        2484 => return Some((Band::Ghz2_4, 14)),
+        5935 => return Some((Band::Ghz6, 2)),
+        2412..=2472 => (Band::Ghz2_4, 2407),
+        4910..=4980 => (Band::Ghz5, 4000),
+        5005..=5895 => (Band::Ghz5, 5000),
+        5955..=7115 => (Band::Ghz6, 5950),
+        _ => return None,
+    };
+    let channel = (freq - base) / 5;
+    // Apart from channel 2, 6 GHz channel numbers are odd
+    if !(freq - base).is_multiple_of(5) || (band == Band::Ghz6 && channel.is_multiple_of(2)) {
+        return None;
+    }
+    Some((band, channel))
+}
+
+/// The center frequency in MHz of a channel, or None if there is no such channel
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{channel_to_freq, Band};
+/// assert_eq!(channel_to_freq(Band::Ghz2_4, 11), Some(2462));
+/// assert_eq!(channel_to_freq(Band::Ghz5, 149), Some(5745));
+/// assert_eq!(channel_to_freq(Band::Ghz6, 37), Some(6135));
+/// ```
+pub fn channel_to_freq(band: Band, channel: u32) -> Option<u32> {
+    let freq = match (band, channel) {
+        (Band::Ghz2_4, 14) => 2484,
+        (Band::Ghz2_4, 1..=13) => 2407 + channel * 5,
+        (Band::Ghz5, 182..=196) => 4000 + channel * 5,
+        (Band::Ghz5, 1..=179) => 5000 + channel * 5,
+        (Band::Ghz6, 2) => 5935,
+        (Band::Ghz6, 1..=233) if !channel.is_multiple_of(2) => 5950 + channel * 5,
+        _ => return None,
+    };
+    Some(freq)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for freq in 2400..7200 {
+            if let Some((band, channel)) = freq_to_channel(freq) {
+                assert_eq!(channel_to_freq(band, channel), Some(freq), "{} MHz", freq);
+            }
+        }
+        for &(band, max) in &[(Band::Ghz2_4, 14), (Band::Ghz5, 196), (Band::Ghz6, 233)] {
+            for channel in 1..=max {
+                if let Some(freq) = channel_to_freq(band, channel) {
+                    assert_eq!(freq_to_channel(freq), Some((band, channel)), "{} channel {}", band, channel);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn channels() {
+        assert_eq!(freq_to_channel(2412), Some((Band::Ghz2_4, 1)));
+        assert_eq!(freq_to_channel(2484), Some((Band::Ghz2_4, 14)));
+        assert_eq!(freq_to_channel(4920), Some((Band::Ghz5, 184)));
+        assert_eq!(freq_to_channel(5825), Some((Band::Ghz5, 165)));
+        assert_eq!(freq_to_channel(5935), Some((Band::Ghz6, 2)));
+        assert_eq!(freq_to_channel(7115), Some((Band::Ghz6, 233)));
+        assert_eq!(freq_to_channel(2413), None);
+        assert_eq!(freq_to_channel(5960), None);
+        assert_eq!(freq_to_channel(60480), None);
+        assert_eq!(channel_to_freq(Band::Ghz2_4, 15), None);
+        assert_eq!(Band::from_freq(5500), Some(Band::Ghz5));
+        assert_eq!(Band::Ghz2_4.to_string(), "2.4 GHz");
+    }
+}
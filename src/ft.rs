@@ -0,0 +1,125 @@
+use eap::EapNetworkConfig;
+use hostapd::HostapdCtrl;
+use network::{NetworkConfig, NetworkValue};
+use super::Result;
+
+/// Key management suites and their fast transition counterparts, by `key_mgmt` name
+const FT_KEY_MGMT: &[(&str, &str)] = &[
+    ("WPA-PSK", "FT-PSK"),
+    ("WPA-EAP", "FT-EAP"),
+    ("WPA-EAP-SHA384", "FT-EAP-SHA384"),
+    ("SAE", "FT-SAE"),
+    ("SAE-EXT-KEY", "FT-SAE-EXT-KEY"),
+    ("FILS-SHA256", "FT-FILS-SHA256"),
+    ("FILS-SHA384", "FT-FILS-SHA384"),
+];
+
+/// Add the fast transition counterpart of each suite in a `key_mgmt` value
+fn with_ft(key_mgmt: &str) -> String {
+    let mut suites: Vec<&str> = key_mgmt.split_whitespace().collect();
+    for &(suite, ft) in FT_KEY_MGMT {
+        if suites.contains(&suite) && !suites.contains(&ft) {
+            suites.push(ft);
+        }
+    }
+    suites.join(" ")
+}
+
+impl NetworkConfig {
+    /// Also allow fast transition (802.11r) with the key management suites already configured
+    ///
+    /// eg `WPA-PSK` becomes `WPA-PSK FT-PSK`, and `SAE` becomes `SAE FT-SAE`.
+    /// Roaming between access points of the same mobility domain then skips
+    /// most of the handshake.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{NetworkConfig, NetworkValue};
+    /// let config = NetworkConfig::sae("office", "correct horse").fast_transition();
+    /// assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("SAE FT-SAE".into())));
+    /// ```
+    pub fn fast_transition(self) -> Self {
+        let key_mgmt = match self.get("key_mgmt") {
+            Some(NetworkValue::Raw(key_mgmt)) => with_ft(key_mgmt),
+            _ => return self,
+        };
+        self.set("key_mgmt", NetworkValue::Raw(key_mgmt))
+    }
+}
+
+impl EapNetworkConfig {
+    /// Also allow fast transition (802.11r), eg `FT-EAP` alongside `WPA-EAP`
+    pub fn fast_transition(self) -> Self {
+        EapNetworkConfig(self.0.fast_transition())
+    }
+}
+
+/// Fast transition (802.11r) settings of an access point, for `HostapdCtrl::set_fast_transition`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FtParams {
+    /// The mobility domain, shared by the access points stations roam between (`mobility_domain`)
+    pub mobility_domain: u16,
+    /// Allow fast transition through the current access point, not only over the air (`ft_over_ds`)
+    pub over_ds: bool,
+    /// Derive the keys of FT-PSK stations on each access point, instead of exchanging them (`ft_psk_generate_local`)
+    pub psk_generate_local: bool,
+}
+
+impl HostapdCtrl {
+    /// Change the fast transition settings of the access point
+    ///
+    /// The FT key management suites, eg `FT-PSK`, must also be enabled in
+    /// `wpa_key_mgmt`. Like other `set_config` changes, the settings take
+    /// effect with `apply`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::FtParams;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let params = FtParams { mobility_domain: 0xa1b2, psk_generate_local: true, ..Default::default() };
+    /// hostapd.set_fast_transition(&params).unwrap();
+    /// hostapd.set_config("wpa_key_mgmt", "WPA-PSK FT-PSK").unwrap();
+    /// hostapd.apply().unwrap();
+    /// ```
+    pub fn set_fast_transition(&mut self, params: &FtParams) -> Result<()> {
+        self.set_config("mobility_domain", format!("{:04x}", params.mobility_domain))?;
+        self.set_config("ft_over_ds", params.over_ds as u8)?;
+        self.set_config("ft_psk_generate_local", params.psk_generate_local as u8)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use eap::EapMethod;
+    use testing::MockSupplicant;
+    use wpactrl::WpaCtrl;
+
+    #[test]
+    fn key_mgmt() {
+        assert_eq!(with_ft("WPA-PSK SAE"), "WPA-PSK SAE FT-PSK FT-SAE");
+        assert_eq!(with_ft("WPA-PSK FT-PSK"), "WPA-PSK FT-PSK");
+        assert_eq!(with_ft("NONE"), "NONE");
+        let config: NetworkConfig = EapNetworkConfig::new("corp", EapMethod::Tls).fast_transition().into();
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("WPA-EAP FT-EAP".into())));
+    }
+
+    #[test]
+    fn set_fast_transition() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_ft_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("SET mobility_domain 00ab", "OK\n");
+        mock.reply("SET ft_over_ds 1", "OK\n");
+        mock.reply("SET ft_psk_generate_local 0", "OK\n");
+        mock.reply("RELOAD", "OK\n");
+        let mut hostapd = WpaCtrl::new().ctrl_path(mock.path()).open_hostapd().unwrap();
+        hostapd.set_fast_transition(&FtParams { mobility_domain: 0xab, over_ds: true, psk_generate_local: false })
+            .unwrap();
+        hostapd.apply().unwrap();
+        assert_eq!(mock.commands(), vec![
+            "SET mobility_domain 00ab", "SET ft_over_ds 1", "SET ft_psk_generate_local 0", "RELOAD",
+        ]);
+    }
+}
@@ -0,0 +1,150 @@
+use super::Result;
+use wpactrl::{expect_ok, WpaCtrl, WpaCtrlBuilder};
+
+const PATH_DEFAULT_GLOBAL: &str = "/var/run/wpa_supplicant-global";
+
+/// Parameters for adding an interface with `GlobalCtrl::interface_add`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterfaceConfig {
+    /// The network interface name, eg `wlan0`
+    pub ifname: String,
+    /// Path of the configuration file for the interface
+    pub confname: Option<String>,
+    /// The driver to use, eg `nl80211`
+    pub driver: Option<String>,
+    /// The control interface for the new interface, eg `/var/run/wpa_supplicant`
+    pub ctrl_interface: Option<String>,
+    /// Driver-specific parameters
+    pub driver_param: Option<String>,
+    /// The bridge interface to use for EAPOL frames
+    pub bridge_name: Option<String>,
+}
+
+impl InterfaceConfig {
+    /// Parameters for the given interface, with everything else left at its default
+    pub fn new<S: Into<String>>(ifname: S) -> Self {
+        InterfaceConfig { ifname: ifname.into(), ..Default::default() }
+    }
+
+    /// Format the parameters as expected by `INTERFACE_ADD`
+    fn to_args(&self) -> String {
+        let mut args = vec![self.ifname.as_str()];
+        args.extend([&self.confname, &self.driver, &self.ctrl_interface, &self.driver_param, &self.bridge_name]
+                    .iter().map(|f| f.as_ref().map_or("", String::as_str)));
+        while args.len() > 1 && args.last() == Some(&"") {
+            args.pop();
+        }
+        args.join("\t")
+    }
+}
+
+/// A connection to the global control interface of wpasupplicant
+///
+/// The global control interface (enabled with `wpa_supplicant -g`) manages
+/// the interfaces wpasupplicant controls, rather than a single interface.
+pub struct GlobalCtrl(WpaCtrl);
+
+impl WpaCtrlBuilder {
+    /// Open the global control interface of wpasupplicant
+    ///
+    /// `ctrl_path` defaults to `/var/run/wpa_supplicant-global` here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut global = wpactrl::WpaCtrl::new().open_global().unwrap();
+    /// println!("{:?}", global.interfaces().unwrap());
+    /// ```
+    pub fn open_global(self) -> Result<GlobalCtrl> {
+        let builder = if self.has_ctrl_path() { self } else { self.ctrl_path(PATH_DEFAULT_GLOBAL) };
+        builder.open().map(GlobalCtrl)
+    }
+}
+
+impl GlobalCtrl {
+    /// Send a command to the global control interface
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut global = wpactrl::WpaCtrl::new().open_global().unwrap();
+    /// assert_eq!(global.request("PING").unwrap(), "PONG\n");
+    /// ```
+    pub fn request(&mut self, cmd: &str) -> Result<String> {
+        self.0.request(cmd)
+    }
+
+    /// Send a command to the global control interface and return the reply verbatim
+    ///
+    /// See `WpaCtrl::raw_request` for details.
+    pub fn raw_request(&mut self, cmd: &str) -> Result<String> {
+        self.0.raw_request(cmd)
+    }
+
+    /// Start controlling a network interface
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::InterfaceConfig;
+    /// let mut global = wpactrl::WpaCtrl::new().open_global().unwrap();
+    /// let mut config = InterfaceConfig::new("wlan1");
+    /// config.driver = Some("nl80211".into());
+    /// config.ctrl_interface = Some("/var/run/wpa_supplicant".into());
+    /// global.interface_add(&config).unwrap();
+    /// ```
+    pub fn interface_add(&mut self, config: &InterfaceConfig) -> Result<()> {
+        expect_ok(&self.request(&format!("INTERFACE_ADD {}", config.to_args()))?)
+    }
+
+    /// Stop controlling a network interface
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut global = wpactrl::WpaCtrl::new().open_global().unwrap();
+    /// global.interface_remove("wlan1").unwrap();
+    /// ```
+    pub fn interface_remove(&mut self, ifname: &str) -> Result<()> {
+        expect_ok(&self.request(&format!("INTERFACE_REMOVE {}", ifname))?)
+    }
+
+    /// List the network interfaces being controlled
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut global = wpactrl::WpaCtrl::new().open_global().unwrap();
+    /// for ifname in global.interfaces().unwrap() {
+    ///     println!("{}", ifname);
+    /// }
+    /// ```
+    pub fn interfaces(&mut self) -> Result<Vec<String>> {
+        Ok(parse_interfaces(&self.request("INTERFACES")?))
+    }
+}
+
+/// Parse the reply to `INTERFACES`
+fn parse_interfaces(reply: &str) -> Vec<String> {
+    reply.lines().filter(|l| !l.is_empty()).map(str::to_owned).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn interface_config() {
+        assert_eq!(InterfaceConfig::new("wlan0").to_args(), "wlan0");
+        let mut config = InterfaceConfig::new("wlan0");
+        config.driver = Some("nl80211".into());
+        config.ctrl_interface = Some("/var/run/wpa_supplicant".into());
+        assert_eq!(config.to_args(), "wlan0\t\tnl80211\t/var/run/wpa_supplicant");
+    }
+
+    #[test]
+    fn interfaces() {
+        assert_eq!(parse_interfaces("wlan0\nwlan1\n"), vec!["wlan0", "wlan1"]);
+        assert!(parse_interfaces("").is_empty());
+    }
+}
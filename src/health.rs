@@ -0,0 +1,95 @@
+use error::Error;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use super::Result;
+use wpactrl::WpaCtrl;
+
+/// A change in the responsiveness of wpasupplicant, reported by a `Keepalive`
+#[derive(Debug)]
+pub enum Health {
+    /// wpasupplicant answers `PING` again after not responding
+    Responding,
+    /// wpasupplicant failed to answer `PING`
+    NotResponding(Error),
+}
+
+/// A background thread that periodically checks that wpasupplicant responds
+///
+/// Created by `WpaCtrl::keepalive`.
+pub struct Keepalive {
+    stop: Sender<()>,
+    thread: JoinHandle<WpaCtrl>,
+}
+
+impl Keepalive {
+    /// Stop checking and return the connection used for the checks
+    pub fn stop(self) -> WpaCtrl {
+        let _ = self.stop.send(());
+        match self.thread.join() {
+            Ok(wpa) => wpa,
+            Err(e) => ::std::panic::resume_unwind(e),
+        }
+    }
+}
+
+impl WpaCtrl {
+    /// Check that wpasupplicant responds to `PING`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.health_check().unwrap();
+    /// ```
+    pub fn health_check(&mut self) -> Result<()> {
+        match self.request("PING")?.as_str() {
+            "PONG\n" => Ok(()),
+            reply => Err(Error::Parse(reply.to_owned())),
+        }
+    }
+
+    /// Check that wpasupplicant responds every `interval` on a background thread
+    ///
+    /// `f` is called whenever wpasupplicant stops responding, and again once
+    /// it responds after having stopped. Since the connection is moved to
+    /// the background thread, use a separate connection for other commands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wpactrl::Health;
+    /// let wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let keepalive = wpa.keepalive(Duration::from_secs(5), |health| match health {
+    ///     Health::NotResponding(e) => eprintln!("wpa_supplicant is not responding: {}", e),
+    ///     Health::Responding => eprintln!("wpa_supplicant is back"),
+    /// });
+    /// let wpa = keepalive.stop();
+    /// ```
+    pub fn keepalive<F>(mut self, interval: Duration, mut f: F) -> Keepalive
+        where F: FnMut(Health) + Send + 'static {
+        let (stop, stopped) = channel();
+        let thread = thread::spawn(move || {
+            let mut responding = true;
+            loop {
+                match self.health_check() {
+                    Ok(()) if !responding => {
+                        responding = true;
+                        f(Health::Responding);
+                    }
+                    Err(e) if responding => {
+                        responding = false;
+                        f(Health::NotResponding(e));
+                    }
+                    _ => (),
+                }
+                match stopped.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => return self,
+                }
+            }
+        });
+        Keepalive { stop, thread }
+    }
+}
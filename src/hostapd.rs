@@ -0,0 +1,892 @@
+use error::Error;
+use macaddr::MacAddr;
+use parse;
+use std::fmt::Display;
+use std::path::Path;
+use super::Result;
+use wpactrl::{expect_ok, reply_error, WpaCtrl, WpaCtrlBuilder};
+
+const PATH_DEFAULT_HOSTAPD: &str = "/var/run/hostapd/wlan0";
+
+/// A connection to hostapd
+///
+/// This offers the typed commands specific to access points, next to
+/// `request` for anything else.
+pub struct HostapdCtrl(WpaCtrl, ApplyAction);
+
+impl WpaCtrlBuilder {
+    /// Open a control interface to hostapd
+    ///
+    /// `ctrl_path` defaults to `/var/run/hostapd/wlan0` here.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// assert_eq!(hostapd.request("PING").unwrap(), "PONG\n");
+    /// ```
+    pub fn open_hostapd(self) -> Result<HostapdCtrl> {
+        let builder = if self.has_ctrl_path() { self } else { self.ctrl_path(PATH_DEFAULT_HOSTAPD) };
+        builder.open().map(|wpa| HostapdCtrl(wpa, ApplyAction::Immediate))
+    }
+}
+
+/// A station associated with the access point, as shown by `STA`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Station {
+    /// The MAC address of the station
+    pub addr: MacAddr,
+    /// The station's flags, eg `AUTH`, `ASSOC` and `AUTHORIZED`
+    pub flags: Vec<String>,
+    /// Bytes received from the station
+    pub rx_bytes: Option<u64>,
+    /// Bytes sent to the station
+    pub tx_bytes: Option<u64>,
+    /// Signal strength of the station in dBm
+    pub signal: Option<i32>,
+    /// The rate of the last frame sent to the station, in kbit/s
+    pub tx_rate: Option<u32>,
+    /// The rate of the last frame received from the station, in kbit/s
+    pub rx_rate: Option<u32>,
+    /// Airtime used sending to the station, in microseconds
+    pub tx_airtime: Option<u64>,
+    /// Airtime used receiving from the station, in microseconds
+    pub rx_airtime: Option<u64>,
+    /// Seconds since the station connected
+    pub connected_time: Option<u64>,
+    /// The unparsed reply, for variables this type does not cover
+    pub raw: String,
+}
+
+impl Station {
+    /// Parse the reply to `STA`, `STA-FIRST` or `STA-NEXT`
+    fn parse(reply: &str) -> Result<Self> {
+        let addr = reply.lines().next().and_then(|l| l.parse().ok())
+            .ok_or_else(|| Error::Parse(reply.to_owned()))?;
+        let num = |key| parse::key_value(reply, key).and_then(|v| v.parse().ok());
+        // Rates are given in units of 100 kbit/s, followed by eg the MCS
+        let rate = |key| parse::key_value(reply, key)
+            .and_then(|v| v.split(' ').next())
+            .and_then(|v| v.parse::<u32>().ok())
+            .map(|rate| rate * 100);
+        Ok(Station {
+            addr,
+            flags: parse::key_value(reply, "flags").map(|f| parse::flags(f).map(str::to_owned).collect()).unwrap_or_default(),
+            rx_bytes: num("rx_bytes"),
+            tx_bytes: num("tx_bytes"),
+            signal: parse::key_value(reply, "signal").and_then(|v| v.parse().ok()),
+            tx_rate: rate("tx_rate_info"),
+            rx_rate: rate("rx_rate_info"),
+            tx_airtime: num("tx_airtime"),
+            rx_airtime: num("rx_airtime"),
+            connected_time: num("connected_time"),
+            raw: reply.to_owned(),
+        })
+    }
+}
+
+/// The live configuration of the access point, as shown by `GET_CONFIG`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ApConfig {
+    /// The BSSID of the access point
+    pub bssid: MacAddr,
+    /// The SSID of the access point
+    pub ssid: String,
+    /// The WPS state, eg `disabled`, `not configured` or `configured`
+    pub wps_state: Option<String>,
+    /// The enabled key management suites, eg `WPA-PSK` and `SAE`
+    pub key_mgmt: Vec<String>,
+    /// The group cipher, eg `CCMP`
+    pub group_cipher: Option<String>,
+    /// The pairwise ciphers enabled for RSN (WPA2 and later)
+    pub rsn_pairwise_ciphers: Vec<String>,
+    /// The pairwise ciphers enabled for WPA
+    pub wpa_pairwise_ciphers: Vec<String>,
+    /// The unparsed reply, for variables this type does not cover
+    pub raw: String,
+}
+
+impl ApConfig {
+    /// Parse the reply to `GET_CONFIG`
+    fn parse(reply: &str) -> Result<Self> {
+        let required = |key| parse::key_value(reply, key).map(str::to_owned)
+            .ok_or_else(|| Error::Parse(reply.to_owned()));
+        let list = |key| parse::key_value(reply, key)
+            .map(|v| v.split_whitespace().map(str::to_owned).collect())
+            .unwrap_or_default();
+        Ok(ApConfig {
+            bssid: required("bssid")?.parse().map_err(|_| Error::Parse(reply.to_owned()))?,
+            ssid: required("ssid")?,
+            wps_state: parse::key_value(reply, "wps_state").map(str::to_owned),
+            key_mgmt: list("key_mgmt"),
+            group_cipher: parse::key_value(reply, "group_cipher").map(str::to_owned),
+            rsn_pairwise_ciphers: list("rsn_pairwise_cipher"),
+            wpa_pairwise_ciphers: list("wpa_pairwise_cipher"),
+            raw: reply.to_owned(),
+        })
+    }
+}
+
+/// The state of the access point, as shown by hostapd's `STATUS`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ApStatus {
+    /// The state of the interface, eg `ENABLED`, `DISABLED`, `ACS` or `DFS`
+    pub state: String,
+    /// The wireless device, eg `phy0`
+    pub phy: Option<String>,
+    /// The operating frequency in MHz
+    pub freq: Option<u32>,
+    /// The primary channel
+    pub channel: Option<u32>,
+    /// The operating class of the channel
+    pub op_class: Option<u8>,
+    /// The position of the secondary 40MHz channel: -1 below, 1 above, 0 none
+    pub secondary_channel: Option<i8>,
+    /// The BSSes of the interface, the first being the interface itself
+    pub bss: Vec<BssStatus>,
+    /// The unparsed reply, for variables this type does not cover
+    pub raw: String,
+}
+
+/// One BSS of an access point, from the `bss[N]` entries of `STATUS`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BssStatus {
+    /// The network interface of the BSS, eg `wlan0`
+    pub ifname: String,
+    /// The BSSID
+    pub bssid: Option<MacAddr>,
+    /// The SSID
+    pub ssid: Option<String>,
+    /// The number of associated stations
+    pub num_sta: Option<u32>,
+}
+
+impl ApStatus {
+    /// Parse the reply to hostapd's `STATUS`
+    fn parse(reply: &str) -> Result<Self> {
+        let state = parse::key_value(reply, "state").ok_or_else(|| Error::Parse(reply.to_owned()))?;
+        let num = |key| parse::key_value(reply, key).and_then(|v| v.parse().ok());
+        let bss = (0..)
+            .map_while(|i| {
+                let entry = |key| parse::key_value(reply, &format!("{}[{}]", key, i));
+                entry("bss").map(|ifname| BssStatus {
+                    ifname: ifname.to_owned(),
+                    bssid: entry("bssid").and_then(|v| v.parse().ok()),
+                    ssid: entry("ssid").map(str::to_owned),
+                    num_sta: entry("num_sta").and_then(|v| v.parse().ok()),
+                })
+            })
+            .collect();
+        Ok(ApStatus {
+            state: state.to_owned(),
+            phy: parse::key_value(reply, "phy").map(str::to_owned),
+            freq: num("freq"),
+            channel: num("channel"),
+            op_class: parse::key_value(reply, "op_class").and_then(|v| v.parse().ok()),
+            secondary_channel: parse::key_value(reply, "secondary_channel").and_then(|v| v.parse().ok()),
+            bss,
+            raw: reply.to_owned(),
+        })
+    }
+
+    /// The number of stations associated with any BSS of the interface
+    pub fn num_sta(&self) -> u32 {
+        self.bss.iter().filter_map(|bss| bss.num_sta).sum()
+    }
+}
+
+/// What it takes for a parameter changed with `HostapdCtrl::set_config` to take effect
+///
+/// Ordered from the least to the most disruptive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ApplyAction {
+    /// The change takes effect immediately
+    Immediate,
+    /// The beacon needs to be updated with `UPDATE_BEACON`; stations stay connected
+    UpdateBeacon,
+    /// The interface needs to be reloaded with `RELOAD`, disconnecting stations
+    Reload,
+}
+
+impl ApplyAction {
+    /// What it takes for a change of the parameter `key` to take effect
+    ///
+    /// Parameters this crate does not know about are taken to need a
+    /// reload, which applies any change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::ApplyAction;
+    /// assert_eq!(ApplyAction::of("ignore_broadcast_ssid"), ApplyAction::UpdateBeacon);
+    /// assert_eq!(ApplyAction::of("wpa_passphrase"), ApplyAction::Reload);
+    /// ```
+    pub fn of(key: &str) -> Self {
+        match key {
+            "max_num_sta" | "ap_max_inactivity" | "skip_inactivity_poll" | "disassoc_low_ack" |
+            "ap_isolate" | "bss_load_update_period" | "wps_state" | "deny_mac_file" | "accept_mac_file" =>
+                ApplyAction::Immediate,
+            "ignore_broadcast_ssid" | "dtim_period" | "utf8_ssid" | "ap_table_max_size" |
+            "interworking" | "hs20" => ApplyAction::UpdateBeacon,
+            _ => ApplyAction::Reload,
+        }
+    }
+}
+
+/// Optional arguments of `HostapdCtrl::deauthenticate` and `disassociate`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct KickParams {
+    /// The IEEE 802.11 reason code to send (`reason=`)
+    pub reason: Option<u16>,
+    /// Send a protected test frame instead (`test=1`)
+    ///
+    /// hostapd only transmits the frame and keeps the station associated,
+    /// which is useful for testing how a client handles it. hostapd treats
+    /// any `test=` argument this way, so it is left out when false.
+    pub test: bool,
+}
+
+impl KickParams {
+    /// Format the arguments as expected by `DEAUTHENTICATE` and `DISASSOCIATE`
+    fn to_args(self) -> String {
+        let mut args = String::new();
+        if let Some(reason) = self.reason {
+            args.push_str(&format!(" reason={}", reason));
+        }
+        if self.test {
+            args.push_str(" test=1");
+        }
+        args
+    }
+}
+
+impl From<u16> for KickParams {
+    fn from(reason: u16) -> Self {
+        KickParams { reason: Some(reason), test: false }
+    }
+}
+
+/// One of hostapd's MAC address access control lists
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Acl {
+    /// The list of stations that are allowed to connect (`ACCEPT_ACL`)
+    Accept,
+    /// The list of stations that are refused (`DENY_ACL`)
+    Deny,
+}
+
+impl Acl {
+    /// The command managing this list
+    fn command(self) -> &'static str {
+        match self {
+            Acl::Accept => "ACCEPT_ACL",
+            Acl::Deny => "DENY_ACL",
+        }
+    }
+}
+
+/// Parse the reply to `ACCEPT_ACL SHOW` or `DENY_ACL SHOW`
+///
+/// Each line holds a MAC address, optionally followed by `VLAN_ID=<id>`.
+fn parse_acl(reply: &str) -> Vec<MacAddr> {
+    reply.lines().filter_map(|line| line.split_whitespace().next()?.parse().ok()).collect()
+}
+
+/// Parameters of `HostapdCtrl::chan_switch`
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::ChanSwitchParams;
+/// // Move to channel 36 with an 80 MHz VHT channel, announced 5 beacons ahead
+/// let params = ChanSwitchParams::new(5, 5180).center_freq1(5210).bandwidth(80).vht();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChanSwitchParams {
+    cs_count: u8,
+    freq: u32,
+    sec_channel_offset: Option<i8>,
+    center_freq1: Option<u32>,
+    center_freq2: Option<u32>,
+    bandwidth: Option<u32>,
+    block_tx: bool,
+    ht: bool,
+    vht: bool,
+}
+
+impl ChanSwitchParams {
+    /// Switch to the channel at `freq` MHz after `cs_count` beacons
+    pub fn new(cs_count: u8, freq: u32) -> Self {
+        ChanSwitchParams { cs_count, freq, ..Default::default() }
+    }
+
+    /// The offset of the secondary channel: -1 for below, 1 for above
+    pub fn sec_channel_offset(mut self, offset: i8) -> Self {
+        self.sec_channel_offset = Some(offset);
+        self
+    }
+
+    /// The center frequency of the (first segment of the) channel in MHz
+    pub fn center_freq1(mut self, freq: u32) -> Self {
+        self.center_freq1 = Some(freq);
+        self
+    }
+
+    /// The center frequency of the second segment of an 80+80 MHz channel
+    pub fn center_freq2(mut self, freq: u32) -> Self {
+        self.center_freq2 = Some(freq);
+        self
+    }
+
+    /// The channel width in MHz, eg 20, 40, 80 or 160
+    pub fn bandwidth(mut self, mhz: u32) -> Self {
+        self.bandwidth = Some(mhz);
+        self
+    }
+
+    /// Ask stations to stop transmitting until the switch is done
+    pub fn block_tx(mut self) -> Self {
+        self.block_tx = true;
+        self
+    }
+
+    /// Use an HT (802.11n) channel
+    pub fn ht(mut self) -> Self {
+        self.ht = true;
+        self
+    }
+
+    /// Use a VHT (802.11ac) channel
+    pub fn vht(mut self) -> Self {
+        self.vht = true;
+        self
+    }
+
+    /// Format the arguments as expected by `CHAN_SWITCH`
+    fn to_args(&self) -> String {
+        let mut args = format!("{} {}", self.cs_count, self.freq);
+        let opts = [
+            ("sec_channel_offset", self.sec_channel_offset.map(|o| o.to_string())),
+            ("center_freq1", self.center_freq1.map(|f| f.to_string())),
+            ("center_freq2", self.center_freq2.map(|f| f.to_string())),
+            ("bandwidth", self.bandwidth.map(|b| b.to_string())),
+        ];
+        for (key, value) in opts.iter() {
+            if let Some(ref value) = *value {
+                args.push_str(&format!(" {}={}", key, value));
+            }
+        }
+        for (flag, set) in [("blocktx", self.block_tx), ("ht", self.ht), ("vht", self.vht)].iter() {
+            if *set {
+                args.push(' ');
+                args.push_str(flag);
+            }
+        }
+        args
+    }
+}
+
+/// Iterator over the stations associated with an access point
+///
+/// Created by `HostapdCtrl::stations`.
+pub struct Stations<'a> {
+    ctrl: &'a mut HostapdCtrl,
+    last: Option<MacAddr>,
+    done: bool,
+}
+
+impl<'a> Iterator for Stations<'a> {
+    type Item = Result<Station>;
+
+    fn next(&mut self) -> Option<Result<Station>> {
+        if self.done {
+            return None;
+        }
+        let cmd = match self.last {
+            Some(ref addr) => format!("STA-NEXT {}", addr),
+            None => "STA-FIRST".to_owned(),
+        };
+        let reply = match self.ctrl.request(&cmd) {
+            Ok(reply) => reply,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        if reply.is_empty() {
+            self.done = true;
+            return None;
+        }
+        if let Some(e) = reply_error(&reply) {
+            self.done = true;
+            return Some(Err(e));
+        }
+        let station = Station::parse(&reply);
+        match station {
+            Ok(ref station) => self.last = Some(station.addr),
+            Err(_) => self.done = true,
+        }
+        Some(station)
+    }
+}
+
+impl HostapdCtrl {
+    /// Send a command to hostapd
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// assert_eq!(hostapd.request("PING").unwrap(), "PONG\n");
+    /// ```
+    pub fn request(&mut self, cmd: &str) -> Result<String> {
+        self.0.request(cmd)
+    }
+
+    /// Send a command to hostapd and return the reply verbatim
+    ///
+    /// See `WpaCtrl::raw_request` for details.
+    pub fn raw_request(&mut self, cmd: &str) -> Result<String> {
+        self.0.raw_request(cmd)
+    }
+
+    /// The path of the control socket connected to
+    pub(crate) fn ctrl_path(&self) -> &Path {
+        self.0.ctrl_path()
+    }
+
+    /// Iterate over the associated stations
+    ///
+    /// Stations are fetched one at a time with `STA-FIRST` / `STA-NEXT`, so
+    /// the number of stations is not limited by the reply buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// for station in hostapd.stations() {
+    ///     let station = station.unwrap();
+    ///     println!("{} {:?}", station.addr, station.signal);
+    /// }
+    /// ```
+    pub fn stations(&mut self) -> Stations<'_> {
+        Stations { ctrl: self, last: None, done: false }
+    }
+
+    /// Look up a single associated station by MAC address
+    ///
+    /// Returns None if no such station is associated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// println!("{:?}", hostapd.station("00:11:22:33:44:55".parse().unwrap()).unwrap());
+    /// ```
+    pub fn station(&mut self, addr: MacAddr) -> Result<Option<Station>> {
+        let reply = self.request(&format!("STA {}", addr))?;
+        match reply_error(&reply) {
+            // hostapd fails STA for an address it does not know
+            Some(Error::Failure) => Ok(None),
+            Some(e) => Err(e),
+            None if reply.is_empty() => Ok(None),
+            None => Station::parse(&reply).map(Some),
+        }
+    }
+
+    /// Deauthenticate a station
+    ///
+    /// A bare reason code can be given instead of `KickParams`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::KickParams;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.deauthenticate("00:11:22:33:44:55".parse().unwrap(), KickParams::default()).unwrap();
+    /// // 3: deauthenticated because sending station is leaving
+    /// hostapd.deauthenticate("00:11:22:33:44:66".parse().unwrap(), 3).unwrap();
+    /// ```
+    pub fn deauthenticate<P: Into<KickParams>>(&mut self, addr: MacAddr, params: P) -> Result<()> {
+        expect_ok(&self.request(&format!("DEAUTHENTICATE {}{}", addr, params.into().to_args()))?)
+    }
+
+    /// Disassociate a station
+    ///
+    /// A bare reason code can be given instead of `KickParams`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::KickParams;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let params = KickParams { reason: Some(5), test: false };
+    /// hostapd.disassociate("00:11:22:33:44:55".parse().unwrap(), params).unwrap();
+    /// ```
+    pub fn disassociate<P: Into<KickParams>>(&mut self, addr: MacAddr, params: P) -> Result<()> {
+        expect_ok(&self.request(&format!("DISASSOCIATE {}{}", addr, params.into().to_args()))?)
+    }
+
+    /// Add a MAC address to an access control list
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Acl;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.acl_add(Acl::Deny, "00:11:22:33:44:55".parse().unwrap()).unwrap();
+    /// ```
+    pub fn acl_add(&mut self, acl: Acl, addr: MacAddr) -> Result<()> {
+        expect_ok(&self.request(&format!("{} ADD_MAC {}", acl.command(), addr))?)
+    }
+
+    /// Remove a MAC address from an access control list
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Acl;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.acl_remove(Acl::Deny, "00:11:22:33:44:55".parse().unwrap()).unwrap();
+    /// ```
+    pub fn acl_remove(&mut self, acl: Acl, addr: MacAddr) -> Result<()> {
+        expect_ok(&self.request(&format!("{} DEL_MAC {}", acl.command(), addr))?)
+    }
+
+    /// List the MAC addresses in an access control list
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Acl;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// for addr in hostapd.acl(Acl::Accept).unwrap() {
+    ///     println!("{}", addr);
+    /// }
+    /// ```
+    pub fn acl(&mut self, acl: Acl) -> Result<Vec<MacAddr>> {
+        let reply = self.request(&format!("{} SHOW", acl.command()))?;
+        if let Some(e) = reply_error(&reply) {
+            return Err(e);
+        }
+        Ok(parse_acl(&reply))
+    }
+
+    /// Remove all MAC addresses from an access control list
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Acl;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.acl_clear(Acl::Deny).unwrap();
+    /// ```
+    pub fn acl_clear(&mut self, acl: Acl) -> Result<()> {
+        expect_ok(&self.request(&format!("{} CLEAR", acl.command()))?)
+    }
+
+    /// Enable the interface, bringing the access point up
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.enable().unwrap();
+    /// ```
+    pub fn enable(&mut self) -> Result<()> {
+        expect_ok(&self.request("ENABLE")?)
+    }
+
+    /// Disable the interface, taking the access point down
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.disable().unwrap();
+    /// ```
+    pub fn disable(&mut self) -> Result<()> {
+        expect_ok(&self.request("DISABLE")?)
+    }
+
+    /// Reload the interface, applying configuration changes
+    ///
+    /// Stations are disconnected while the interface restarts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.reload().unwrap();
+    /// ```
+    pub fn reload(&mut self) -> Result<()> {
+        expect_ok(&self.request("RELOAD")?)
+    }
+
+    /// Change the configuration parameter `key` of the running access point
+    ///
+    /// Most parameters only take effect once the beacon is updated or the
+    /// interface reloaded; call `apply` after the last change to do what
+    /// the changes need. Changes are lost when hostapd restarts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.set_config("ssid", "guest").unwrap();
+    /// hostapd.set_config("wpa_passphrase", "correct horse").unwrap();
+    /// hostapd.apply().unwrap();
+    /// ```
+    pub fn set_config<V: Display>(&mut self, key: &str, value: V) -> Result<()> {
+        expect_ok(&self.request(&format!("SET {} {}", key, value))?)?;
+        self.1 = self.1.max(ApplyAction::of(key));
+        Ok(())
+    }
+
+    /// Make the changes done with `set_config` take effect
+    ///
+    /// Reloads the interface if any change needs it, otherwise updates
+    /// the beacon if any change needs that, and returns what was done.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::ApplyAction;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.set_config("ignore_broadcast_ssid", 1).unwrap();
+    /// assert_eq!(hostapd.apply().unwrap(), ApplyAction::UpdateBeacon);
+    /// ```
+    pub fn apply(&mut self) -> Result<ApplyAction> {
+        let action = self.1;
+        match action {
+            ApplyAction::Immediate => (),
+            ApplyAction::UpdateBeacon => expect_ok(&self.request("UPDATE_BEACON")?)?,
+            ApplyAction::Reload => self.reload()?,
+        }
+        self.1 = ApplyAction::Immediate;
+        Ok(action)
+    }
+
+    /// Announce a channel switch and move the access point to a new channel
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::ChanSwitchParams;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// hostapd.chan_switch(ChanSwitchParams::new(5, 2437)).unwrap();
+    /// ```
+    pub fn chan_switch(&mut self, params: ChanSwitchParams) -> Result<()> {
+        expect_ok(&self.request(&format!("CHAN_SWITCH {}", params.to_args()))?)
+    }
+
+    /// Read the live configuration of the access point
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let config = hostapd.get_config().unwrap();
+    /// println!("{} ({}): {:?}", config.ssid, config.bssid, config.key_mgmt);
+    /// ```
+    pub fn get_config(&mut self) -> Result<ApConfig> {
+        ApConfig::parse(&self.request("GET_CONFIG")?)
+    }
+
+    /// Read the state of the access point, including its channel and stations per BSS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let status = hostapd.status().unwrap();
+    /// println!("{} on channel {:?}", status.state, status.channel);
+    /// for bss in &status.bss {
+    ///     println!("{}: {:?} stations", bss.ifname, bss.num_sta);
+    /// }
+    /// ```
+    pub fn status(&mut self) -> Result<ApStatus> {
+        ApStatus::parse(&self.request("STATUS")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn station() {
+        let station = Station::parse(concat!(
+            "00:11:22:33:44:55\n",
+            "flags=[AUTH][ASSOC][AUTHORIZED]\n",
+            "aid=1\n",
+            "rx_packets=120\n",
+            "tx_packets=80\n",
+            "rx_bytes=10240\n",
+            "tx_bytes=20480\n",
+            "inactive_msec=300\n",
+            "signal=-45\n",
+            "rx_rate_info=65 mcs 7\n",
+            "tx_rate_info=1300 mcs 15 shortGI\n",
+            "rx_airtime=1200\n",
+            "tx_airtime=3400\n",
+            "connected_time=62\n",
+        )).unwrap();
+        assert_eq!(station.addr.to_string(), "00:11:22:33:44:55");
+        assert_eq!(station.flags, vec!["AUTH", "ASSOC", "AUTHORIZED"]);
+        assert_eq!(station.rx_bytes, Some(10240));
+        assert_eq!(station.tx_bytes, Some(20480));
+        assert_eq!(station.signal, Some(-45));
+        assert_eq!(station.tx_rate, Some(130_000));
+        assert_eq!(station.rx_rate, Some(6500));
+        assert_eq!(station.tx_airtime, Some(3400));
+        assert_eq!(station.rx_airtime, Some(1200));
+        assert_eq!(station.connected_time, Some(62));
+        assert_eq!(parse::key_value(&station.raw, "aid"), Some("1"));
+    }
+
+    #[test]
+    fn kick_params() {
+        assert_eq!(KickParams::default().to_args(), "");
+        assert_eq!(KickParams::from(3).to_args(), " reason=3");
+        assert_eq!(KickParams { reason: Some(5), test: true }.to_args(), " reason=5 test=1");
+        assert_eq!(KickParams { reason: None, test: false }.to_args(), "");
+    }
+
+    #[test]
+    fn acl() {
+        assert_eq!(parse_acl("00:11:22:33:44:55 VLAN_ID=0\n66:77:88:99:aa:bb VLAN_ID=3\n"),
+                   vec!["00:11:22:33:44:55".parse().unwrap(), "66:77:88:99:aa:bb".parse().unwrap()]);
+        assert!(parse_acl("").is_empty());
+    }
+
+    #[test]
+    fn chan_switch_params() {
+        assert_eq!(ChanSwitchParams::new(5, 2437).to_args(), "5 2437");
+        assert_eq!(ChanSwitchParams::new(5, 5180).sec_channel_offset(1).center_freq1(5210).bandwidth(80).ht().vht().to_args(),
+                   "5 5180 sec_channel_offset=1 center_freq1=5210 bandwidth=80 ht vht");
+        assert_eq!(ChanSwitchParams::new(1, 5500).block_tx().to_args(), "1 5500 blocktx");
+    }
+
+    #[test]
+    fn ap_config() {
+        let config = ApConfig::parse(concat!(
+            "bssid=02:00:00:00:03:00\n",
+            "ssid=test-wpa2\n",
+            "wps_state=disabled\n",
+            "wpa=2\n",
+            "key_mgmt=WPA-PSK SAE \n",
+            "group_cipher=CCMP\n",
+            "rsn_pairwise_cipher=CCMP GCMP \n",
+        )).unwrap();
+        assert_eq!(config.bssid.to_string(), "02:00:00:00:03:00");
+        assert_eq!(config.ssid, "test-wpa2");
+        assert_eq!(config.wps_state.as_deref(), Some("disabled"));
+        assert_eq!(config.key_mgmt, vec!["WPA-PSK", "SAE"]);
+        assert_eq!(config.group_cipher.as_deref(), Some("CCMP"));
+        assert_eq!(config.rsn_pairwise_ciphers, vec!["CCMP", "GCMP"]);
+        assert!(config.wpa_pairwise_ciphers.is_empty());
+        assert!(ApConfig::parse("FAIL\n").is_err());
+    }
+
+    #[test]
+    fn ap_status() {
+        let status = ApStatus::parse(concat!(
+            "state=ENABLED\n",
+            "phy=phy0\n",
+            "freq=5180\n",
+            "num_sta_non_erp=0\n",
+            "channel=36\n",
+            "secondary_channel=1\n",
+            "ieee80211n=1\n",
+            "op_class=116\n",
+            "bss[0]=wlan0\n",
+            "bssid[0]=02:00:00:00:03:00\n",
+            "ssid[0]=test\n",
+            "num_sta[0]=2\n",
+            "bss[1]=wlan0_1\n",
+            "bssid[1]=02:00:00:00:03:01\n",
+            "ssid[1]=guest\n",
+            "num_sta[1]=1\n",
+        )).unwrap();
+        assert_eq!(status.state, "ENABLED");
+        assert_eq!(status.phy.as_deref(), Some("phy0"));
+        assert_eq!(status.freq, Some(5180));
+        assert_eq!(status.channel, Some(36));
+        assert_eq!(status.op_class, Some(116));
+        assert_eq!(status.secondary_channel, Some(1));
+        assert_eq!(status.bss.len(), 2);
+        assert_eq!(status.bss[1], BssStatus {
+            ifname: "wlan0_1".into(),
+            bssid: Some("02:00:00:00:03:01".parse().unwrap()),
+            ssid: Some("guest".into()),
+            num_sta: Some(1),
+        });
+        assert_eq!(status.num_sta(), 3);
+        let disabled = ApStatus::parse("state=DISABLED\nphy=phy0\nfreq=0\n").unwrap();
+        assert!(disabled.bss.is_empty());
+        assert!(ApStatus::parse("FAIL\n").is_err());
+    }
+
+    #[test]
+    fn apply() {
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_hostapd_apply_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        for cmd in &["SET max_num_sta 10", "SET ignore_broadcast_ssid 1", "SET ssid guest", "UPDATE_BEACON", "RELOAD"] {
+            mock.reply(*cmd, "OK\n");
+        }
+        let mut hostapd = WpaCtrl::new().ctrl_path(mock.path()).open_hostapd().unwrap();
+        hostapd.set_config("max_num_sta", 10).unwrap();
+        assert_eq!(hostapd.apply().unwrap(), ApplyAction::Immediate);
+        hostapd.set_config("ignore_broadcast_ssid", 1).unwrap();
+        assert_eq!(hostapd.apply().unwrap(), ApplyAction::UpdateBeacon);
+        hostapd.set_config("ssid", "guest").unwrap();
+        hostapd.set_config("ignore_broadcast_ssid", 1).unwrap();
+        assert_eq!(hostapd.apply().unwrap(), ApplyAction::Reload);
+        assert!(hostapd.set_config("bogus", 1).is_err());
+        assert_eq!(hostapd.apply().unwrap(), ApplyAction::Immediate);
+        assert_eq!(mock.commands(), vec![
+            "SET max_num_sta 10", "SET ignore_broadcast_ssid 1", "UPDATE_BEACON", "SET ssid guest",
+            "SET ignore_broadcast_ssid 1", "RELOAD", "SET bogus 1",
+        ]);
+    }
+
+    #[test]
+    fn acl_show() {
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_hostapd_acl_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("ACCEPT_ACL SHOW", "00:11:22:33:44:55 VLAN_ID=0\n");
+        let mut hostapd = WpaCtrl::new().ctrl_path(mock.path()).open_hostapd().unwrap();
+        assert_eq!(hostapd.acl(Acl::Accept).unwrap(), vec!["00:11:22:33:44:55".parse().unwrap()]);
+        assert!(matches!(hostapd.acl(Acl::Deny), Err(Error::UnknownCommand)));
+    }
+
+    #[test]
+    fn stations() {
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_hostapd_stations_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("STA-FIRST", "00:11:22:33:44:55\naid=1\n");
+        mock.reply("STA-NEXT 00:11:22:33:44:55", "FAIL\n");
+        mock.reply("STA 00:11:22:33:44:66", "FAIL\n");
+        let mut hostapd = WpaCtrl::new().ctrl_path(mock.path()).open_hostapd().unwrap();
+        let stations: Vec<_> = hostapd.stations().collect();
+        assert_eq!(stations.len(), 2);
+        assert!(stations[0].is_ok() && matches!(stations[1], Err(Error::Failure)));
+        assert_eq!(hostapd.station("00:11:22:33:44:66".parse().unwrap()).unwrap(), None);
+        assert!(matches!(hostapd.station("00:11:22:33:44:77".parse().unwrap()), Err(Error::UnknownCommand)));
+    }
+
+    #[test]
+    fn station_invalid() {
+        assert!(Station::parse("flags=[AUTH]\n").is_err());
+        assert!(Station::parse("").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use error::Error;
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use super::Result;
+
+pub(crate) const PATH_DEFAULT_CTRL_DIR: &str = "/var/run/wpa_supplicant";
+const ANY_INTERFACE_ATTEMPTS: u32 = 10;
+const ANY_INTERFACE_DELAY_MS: u64 = 100;
+
+/// List the interfaces with a control socket in a control directory
+///
+/// The directory defaults to `/var/run/wpa_supplicant`. Names are returned
+/// in sorted order, and can be joined to the directory to get a path for
+/// `WpaCtrlBuilder::ctrl_path`.
+///
+/// # Examples
+///
+/// ```
+/// for iface in wpactrl::list_interfaces(None).unwrap() {
+///     println!("{}", iface);
+/// }
+/// ```
+pub fn list_interfaces<'a, I: Into<Option<&'a Path>>>(dir: I) -> Result<Vec<String>> {
+    let dir = dir.into().unwrap_or_else(|| Path::new(PATH_DEFAULT_CTRL_DIR));
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_socket() {
+            if let Ok(name) = entry.file_name().into_string() {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Connect a socket to the first live interface in `dir`, retrying briefly if there is none
+pub(crate) fn connect_any(socket: &UnixDatagram, dir: &Path) -> Result<PathBuf> {
+    for attempt in 0..ANY_INTERFACE_ATTEMPTS {
+        if attempt > 0 {
+            thread::sleep(Duration::from_millis(ANY_INTERFACE_DELAY_MS));
+        }
+        for name in list_interfaces(dir).unwrap_or_default() {
+            let path = dir.join(name);
+            if socket.connect(&path).is_ok() {
+                return Ok(path);
+            }
+        }
+    }
+    Err(Error::NoInterface(dir.to_owned()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn list_interfaces() {
+        let dir = ::std::env::temp_dir().join(format!("wpactrl_test_ifaces_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let _wlan1 = UnixDatagram::bind(dir.join("wlan1")).unwrap();
+        let _wlan0 = UnixDatagram::bind(dir.join("wlan0")).unwrap();
+        fs::write(dir.join("not-a-socket"), b"").unwrap();
+        assert_eq!(super::list_interfaces(dir.as_path()).unwrap(), vec!["wlan0", "wlan1"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn connect_any() {
+        let dir = ::std::env::temp_dir().join(format!("wpactrl_test_any_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        drop(UnixDatagram::bind(dir.join("stale")).unwrap());
+        let _live = UnixDatagram::bind(dir.join("wlan0")).unwrap();
+        let (client, _) = UnixDatagram::pair().unwrap();
+        assert_eq!(super::connect_any(&client, &dir).unwrap(), dir.join("wlan0"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+use error::Error;
+use std::fmt;
+use std::str::FromStr;
+use super::Result;
+use wpactrl::{expect_ok, WpaCtrl};
+
+/// A wpasupplicant message level, from the most to the least verbose
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Level {
+    /// Very detailed debugging, such as individual driver events (`EXCESSIVE`)
+    Excessive,
+    /// Debugging including dumps of message contents (`MSGDUMP`)
+    MsgDump,
+    /// Debugging (`DEBUG`)
+    Debug,
+    /// Informational messages, which include most events (`INFO`)
+    Info,
+    /// Warnings (`WARNING`)
+    Warning,
+    /// Errors (`ERROR`)
+    Error,
+}
+
+impl Level {
+    /// The name of the level, as used by `LOG_LEVEL`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Level::Excessive => "EXCESSIVE",
+            Level::MsgDump => "MSGDUMP",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warning => "WARNING",
+            Level::Error => "ERROR",
+        }
+    }
+
+    /// The `<N>` message priority of the level, as used by `LEVEL`
+    pub fn priority(&self) -> u8 {
+        *self as u8
+    }
+
+    /// The level of a `<N>` message priority, or None if `n` is not a known level
+    pub fn from_priority(n: u8) -> Option<Self> {
+        match n {
+            0 => Some(Level::Excessive),
+            1 => Some(Level::MsgDump),
+            2 => Some(Level::Debug),
+            3 => Some(Level::Info),
+            4 => Some(Level::Warning),
+            5 => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+/// The level of a control interface message, from its `<N>` priority prefix
+fn message_level(msg: &str) -> Option<(Level, usize)> {
+    let end = msg.strip_prefix('<')?.find('>')?;
+    let level = msg[1..end + 1].parse().ok().and_then(Level::from_priority)?;
+    Some((level, end + 2))
+}
+
+/// Split a control interface message into its level and the message without the `<N>` prefix
+///
+/// Messages without a known prefix are taken to be `Level::Info`.
+pub(crate) fn split_level(msg: &str) -> (Level, &str) {
+    match message_level(msg) {
+        Some((level, start)) => (level, &msg[start..]),
+        None => (Level::Info, msg),
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    /// Parse a level name, ignoring case
+    fn from_str(s: &str) -> Result<Self> {
+        let levels = [Level::Excessive, Level::MsgDump, Level::Debug, Level::Info, Level::Warning, Level::Error];
+        levels.iter().cloned().find(|l| l.name().eq_ignore_ascii_case(s)).ok_or_else(|| Error::Parse(s.to_owned()))
+    }
+}
+
+/// Parse the reply to `LOG_LEVEL` without arguments
+fn parse_log_level(reply: &str) -> Result<(Level, bool)> {
+    let err = || Error::Parse(reply.to_owned());
+    let value = |key| {
+        reply.lines().find_map(|l| l.strip_prefix(key)).and_then(|v| v.strip_prefix(": ")).ok_or_else(err)
+    };
+    let level = value("Current level")?.parse().map_err(|_| err())?;
+    let timestamps = value("Timestamp")? != "0";
+    Ok((level, timestamps))
+}
+
+impl WpaCtrl {
+    /// The level of the messages wpasupplicant logs, and whether they are timestamped
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let (level, timestamps) = wpa.log_level().unwrap();
+    /// println!("Logging {} messages, timestamps: {}", level, timestamps);
+    /// ```
+    pub fn log_level(&mut self) -> Result<(Level, bool)> {
+        parse_log_level(&self.request("LOG_LEVEL")?)
+    }
+
+    /// Change the level of the messages wpasupplicant logs, and whether they are timestamped
+    ///
+    /// This affects wpasupplicant's own log output and which messages are
+    /// sent to attached connections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Level;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.set_log_level(Level::Debug, true).unwrap();
+    /// ```
+    pub fn set_log_level(&mut self, level: Level, timestamps: bool) -> Result<()> {
+        expect_ok(&self.request(&format!("LOG_LEVEL {} {}", level, timestamps as u8))?)
+    }
+
+    /// Reopen wpasupplicant's log file, eg after it was rotated
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.relog().unwrap();
+    /// ```
+    pub fn relog(&mut self) -> Result<()> {
+        expect_ok(&self.request("RELOG")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn level() {
+        assert_eq!("msgdump".parse::<Level>().unwrap(), Level::MsgDump);
+        assert_eq!(Level::Warning.to_string(), "WARNING");
+        assert!(Level::Debug < Level::Info);
+        assert_eq!(Level::from_priority(Level::Warning.priority()), Some(Level::Warning));
+        assert!("LOUD".parse::<Level>().is_err());
+        assert_eq!(split_level("<1>RX ctrl_iface - hexdump"), (Level::MsgDump, "RX ctrl_iface - hexdump"));
+        assert_eq!(split_level("<4>CTRL-EVENT-SCAN-FAILED ret=-16"), (Level::Warning, "CTRL-EVENT-SCAN-FAILED ret=-16"));
+        assert_eq!(split_level("<9>?"), (Level::Info, "<9>?"));
+        assert_eq!(split_level("CTRL-EVENT-SCAN-STARTED "), (Level::Info, "CTRL-EVENT-SCAN-STARTED "));
+    }
+
+    #[test]
+    fn log_level() {
+        assert_eq!(parse_log_level("Current level: DEBUG\nTimestamp: 1\n").unwrap(), (Level::Debug, true));
+        assert_eq!(parse_log_level("Current level: INFO\nTimestamp: 0\n").unwrap(), (Level::Info, false));
+        assert!(parse_log_level("FAIL\n").is_err());
+    }
+}
@@ -11,17 +11,186 @@
 //! println!("{}", wpa.request("LIST_NETWORKS").unwrap());
 //! ```
 //!
+//! # Raw access
+//!
+//! The typed methods only cover part of the control interface. Any command
+//! can be sent with `raw_request`, and events can be read unmodified with
+//! `WpaCtrlAttached::raw_recv`. Parsed types keep the text they were parsed
+//! from (eg `NetworkEntry::raw`), and the `parse` module exposes the
+//! splitting primitives used internally. These escape hatches are a
+//! permanent part of the API, so commands newer than this crate are never
+//! out of reach.
+//!
+//! # Threads
+//!
+//! `WpaCtrl`, `WpaCtrlAttached`, `HostapdCtrl` and `GlobalCtrl` are `Send`
+//! and `Sync`, so a connection can be moved into a worker thread. Sending
+//! a command needs `&mut self`, since replies are read from the same
+//! socket; to share one connection between threads, wrap it in a mutex:
+//!
+//! ```
+//! use std::sync::{Arc, Mutex};
+//! use std::thread;
+//! let wpa = Arc::new(Mutex::new(wpactrl::WpaCtrl::new().open().unwrap()));
+//! let poller = {
+//!     let wpa = wpa.clone();
+//!     thread::spawn(move || wpa.lock().unwrap().request("SIGNAL_POLL").unwrap())
+//! };
+//! wpa.lock().unwrap().request("PING").unwrap();
+//! println!("{}", poller.join().unwrap());
+//! ```
+//!
+//! Alternatively, open a separate connection for each thread.
+//!
+//! # Features
+//!
+//! - `cli`: the `wpactrl-cli` binary, an interactive client in the style of
+//!   `wpa_cli` with command history and live events, for targets where
+//!   `wpa_cli` is not installed.
+//! - `log` (enabled by default): log each request at debug level, and the
+//!   commands sent, replies received and events at trace level through the
+//!   `log` crate. Credentials are redacted from logged commands.
+//! - `serde`: `Serialize` and `Deserialize` for the parsed types and events,
+//!   such as `ScanResult`, `Bss`, `NetworkEntry`, `MacAddr` and `Ssid`.
+//! - `tracing`: a debug-level `request` span around each request, with its
+//!   id and command name as fields, and trace-level events for the command
+//!   sent, the reply and each received event, through the `tracing` crate.
+//!   Failures are recorded at debug level. Credentials are redacted as for
+//!   `log`.
+//! - `testing`: the `testing` module, with a fake wpasupplicant for
+//!   testing code that uses this crate without a wireless interface, or
+//!   replaying traffic recorded with `WpaCtrlBuilder::record`.
+//! - `zeroize`: wipe network variables such as passphrases and PSKs from
+//!   memory once they are dropped, and wipe `SET_NETWORK` commands once
+//!   they have been sent.
+//!
 //! The library currently only supports UNIX sockets, but additional
 //! connection methods (eg UDP or pipes) may be added in the future.
-#[macro_use]
-extern crate failure;
+#[cfg(feature = "log")]
 #[macro_use]
 extern crate log;
 extern crate nix;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[macro_use]
+extern crate bitflags;
+
+// Without the `log` feature, log records are type-checked but never built
+#[cfg(not(feature = "log"))]
+macro_rules! log_disabled {
+    ($($arg:tt)*) => { if false { let _ = format_args!($($arg)*); } }
+}
+#[cfg(not(feature = "log"))]
+macro_rules! trace { ($($arg:tt)*) => { log_disabled!($($arg)*) } }
+#[cfg(not(feature = "log"))]
+macro_rules! debug { ($($arg:tt)*) => { log_disabled!($($arg)*) } }
+#[cfg(not(feature = "log"))]
+macro_rules! warn { ($($arg:tt)*) => { log_disabled!($($arg)*) } }
 
+mod action;
+mod batch;
+mod capability;
+mod config;
+mod connect;
+mod country;
+mod dpp;
+mod dpp_uri;
+mod driver;
+mod eap;
+mod error;
+mod fils;
+mod freq;
+mod ft;
+mod global;
+mod health;
+mod hostapd;
+mod interfaces;
+mod level;
+mod lint;
+mod mac_policy;
+mod macaddr;
+mod mesh;
+mod mib;
+mod multi_bss;
+mod network;
+mod nfc;
+mod owe;
+mod p2p;
+mod p2p_service;
+pub mod parse;
+mod pmksa;
+mod power;
+mod psk;
+mod quality;
+mod record;
+mod roam;
+mod sae;
+mod scan;
+mod scan_flags;
+mod secret;
+mod ssid;
+mod station_monitor;
+mod tdls;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod version;
+mod watcher;
+mod wnm;
 mod wpactrl;
+mod wps;
+pub use action::Action;
+pub use batch::Batch;
+pub use capability::{CapabilityKind, CapabilitySet, FilsSupport, Modes};
+pub use config::{Property, PropertyValue};
+pub use connect::ConnectOutcome;
+pub use country::{CountryCode, RegdomChange};
+pub use dpp::{DppAuthParams, DppBootstrapParams, DppConfigurator, DppEvent, DppOutcome, DppRole};
+pub use dpp_uri::DppUri;
+pub use eap::{CertSource, EapMethod, EapNetworkConfig};
+pub use error::Error;
+pub use fils::FilsEvent;
+pub use freq::{channel_to_freq, freq_to_channel, Band};
+pub use ft::FtParams;
+pub use global::{GlobalCtrl, InterfaceConfig};
+pub use health::{Health, Keepalive};
+pub use hostapd::{Acl, ApConfig, ApStatus, ApplyAction, BssStatus, ChanSwitchParams, HostapdCtrl, KickParams, Station,
+                  Stations};
+pub use interfaces::list_interfaces;
+pub use level::Level;
+pub use lint::LintWarning;
+pub use mac_policy::MacAddrPolicy;
+pub use macaddr::MacAddr;
+pub use mesh::MeshEvent;
+pub use mib::Mib;
+pub use multi_bss::MultiBss;
+pub use network::{NetworkConfig, NetworkEntry, NetworkFlag, NetworkId, NetworkValue};
+pub use nfc::{HandoverCarrier, NfcFormat};
+pub use p2p::{P2pConnectParams, P2pEvent, P2pInviteGroup, P2pPeer, P2pPeers, P2pRole, P2pWpsMethod};
+pub use p2p_service::{P2pService, ServiceProtocol, ServiceQuery, ServiceTlv};
+pub use pmksa::{PmksaCacheEntry, PmksaEntry};
+pub use psk::psk_from_passphrase;
+pub use quality::{NetworkQuality, QualityScorer, QualityUpdate};
+pub use sae::SaeEvent;
+pub use scan::{Bss, BssEntries, ScanEvent, ScanEvents, ScanResult};
+pub use scan_flags::{Capabilities, Ciphers, KeyMgmt, Protocols, ScanFlags};
+pub use secret::redact;
+pub use ssid::Ssid;
+pub use station_monitor::{StationMonitor, StationReport, StationSnapshot};
+pub use tdls::TdlsStatus;
+pub use version::Version;
+pub use watcher::{InterfaceEvent, InterfaceWatcher};
+pub use wnm::BssTmResponse;
 pub use wpactrl::{WpaCtrl, WpaCtrlAttached, WpaCtrlBuilder};
+pub use wps::{WpsErEvent, WpsOutcome};
+
+/// The error type's former name
+#[deprecated(note = "renamed to Error")]
+pub type WpaError = Error;
 
-use failure::Error;
 /// Result type used for the library
 pub type Result<T> = ::std::result::Result<T, Error>;
@@ -0,0 +1,177 @@
+use capability::{CapabilityKind, CapabilitySet, FilsSupport, KEY_MGMT_NAMES};
+use country::CountryCode;
+use error::Error;
+use freq::{freq_to_channel, Band};
+use network::{NetworkConfig, NetworkValue};
+use scan_flags::KeyMgmt;
+use std::fmt;
+use super::Result;
+use wpactrl::WpaCtrl;
+
+/// A problem `WpaCtrl::lint_profile` found with a network configuration
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LintWarning {
+    /// A key management suite of `key_mgmt`, eg `SAE`, `OWE` or `FILS-SHA256`, is not supported
+    UnsupportedKeyMgmt(String),
+    /// `fils_dh_group` is set, but FILS with perfect forward secrecy is not supported
+    UnsupportedFilsPfs,
+    /// A frequency of `frequency` or `freq_list` is not the center of a known channel
+    UnknownFreq(u32),
+    /// A frequency is on a DFS channel, which the access point leaves when it detects radar
+    DfsChannel(u32),
+    /// A frequency is not allowed in the configured country, or in the world regulatory domain if None
+    DisallowedChannel(u32, Option<CountryCode>),
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LintWarning::UnsupportedKeyMgmt(ref suite) => write!(f, "Key management {} is not supported", suite),
+            LintWarning::UnsupportedFilsPfs => write!(f, "FILS with perfect forward secrecy is not supported"),
+            LintWarning::UnknownFreq(freq) => write!(f, "{} MHz is not a known channel", freq),
+            LintWarning::DfsChannel(freq) => write!(f, "{} MHz is a DFS channel", freq),
+            LintWarning::DisallowedChannel(freq, Some(country)) => {
+                write!(f, "{} MHz is not allowed in {}", freq, country)
+            }
+            LintWarning::DisallowedChannel(freq, None) => write!(f, "{} MHz is not allowed without a country", freq),
+        }
+    }
+}
+
+/// The text of a network variable
+fn var<'a>(config: &'a NetworkConfig, key: &str) -> Option<&'a str> {
+    match config.get(key)? {
+        NetworkValue::Str(s) | NetworkValue::Raw(s) => Some(s),
+    }
+}
+
+/// Whether a 5 GHz frequency needs radar detection, channels 52 to 144
+fn is_dfs(freq: u32) -> bool {
+    (5250..=5730).contains(&freq)
+}
+
+/// Whether a channel may be used in a country, for channels only some countries allow
+///
+/// Channel 14 and the 4.9 GHz channels are only allowed in Japan, and the
+/// world regulatory domain does not include the 6 GHz band. Other
+/// restrictions depend on the driver's regulatory database.
+fn is_allowed(band: Band, channel: u32, country: Option<CountryCode>) -> bool {
+    let japan = country.is_some_and(|country| country.as_str() == "JP");
+    match band {
+        Band::Ghz2_4 => channel != 14 || japan,
+        Band::Ghz5 => channel < 182 || japan,
+        Band::Ghz6 => country.is_some_and(|country| country != CountryCode::WORLD),
+    }
+}
+
+impl WpaCtrl {
+    /// Check a network configuration against the capabilities of the interface and the regulatory domain
+    ///
+    /// Finds key management suites the driver does not support, and
+    /// frequencies in `frequency` or `freq_list` that are DFS channels or
+    /// not allowed in the configured country, before the network is added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkConfig;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let config = NetworkConfig::sae("office", "correct horse").set("freq_list", 5260);
+    /// for warning in wpa.lint_profile(&config).unwrap() {
+    ///     println!("{}", warning);
+    /// }
+    /// ```
+    pub fn lint_profile(&mut self, config: &NetworkConfig) -> Result<Vec<LintWarning>> {
+        let mut warnings = Vec::new();
+        if let Some(key_mgmt) = var(config, "key_mgmt") {
+            let supported = match self.capabilities(CapabilityKind::KeyMgmt)? {
+                CapabilitySet::KeyMgmt(supported) => supported,
+                _ => KeyMgmt::empty(),
+            };
+            for suite in key_mgmt.split_whitespace() {
+                match KEY_MGMT_NAMES.iter().find(|&&(name, _)| name == suite) {
+                    Some(&(_, flag)) if !supported.contains(flag) => {
+                        warnings.push(LintWarning::UnsupportedKeyMgmt(suite.to_owned()))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        if config.get("fils_dh_group").is_some() {
+            let fils = match self.capabilities(CapabilityKind::Fils) {
+                Ok(CapabilitySet::Fils(fils)) => fils,
+                // Releases before FILS do not know the capability
+                Ok(_) | Err(Error::Failure) | Err(Error::UnknownCommand) => FilsSupport::empty(),
+                Err(e) => return Err(e),
+            };
+            if !fils.contains(FilsSupport::FILS_SK_PFS) {
+                warnings.push(LintWarning::UnsupportedFilsPfs);
+            }
+        }
+        let freqs: Vec<&str> = ["frequency", "freq_list"].iter()
+            .filter_map(|key| var(config, key))
+            .flat_map(str::split_whitespace)
+            .collect();
+        if freqs.is_empty() {
+            return Ok(warnings);
+        }
+        let country = self.get_country()?;
+        for freq in freqs {
+            let freq = freq.parse().map_err(|_| Error::Parse(freq.to_owned()))?;
+            match freq_to_channel(freq) {
+                None => warnings.push(LintWarning::UnknownFreq(freq)),
+                Some((band, channel)) if !is_allowed(band, channel, country) => {
+                    warnings.push(LintWarning::DisallowedChannel(freq, country))
+                }
+                Some(_) if is_dfs(freq) => warnings.push(LintWarning::DfsChannel(freq)),
+                Some(_) => (),
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn lint_profile() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_lint_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("GET_CAPABILITY key_mgmt", "NONE WPA-PSK WPA-EAP FILS-SHA256\n");
+        mock.reply("GET_CAPABILITY fils", "FILS\n");
+        mock.reply("GET country", "US\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        let config = NetworkConfig::sae_transition("office", "correct horse")
+            .set("freq_list", NetworkValue::Raw("2437 5260 2484 5961".into()));
+        let us = "US".parse().ok();
+        assert_eq!(wpa.lint_profile(&config).unwrap(), vec![
+            LintWarning::UnsupportedKeyMgmt("SAE".into()),
+            LintWarning::DfsChannel(5260),
+            LintWarning::DisallowedChannel(2484, us),
+            LintWarning::UnknownFreq(5961),
+        ]);
+        let config = NetworkConfig::owe("cafe").set("fils_dh_group", 19).set("frequency", 5975);
+        assert_eq!(wpa.lint_profile(&config).unwrap(), vec![
+            LintWarning::UnsupportedKeyMgmt("OWE".into()),
+            LintWarning::UnsupportedFilsPfs,
+        ]);
+        let config = NetworkConfig::wpa_psk("home", "hunter22").set("frequency", 2412);
+        assert_eq!(wpa.lint_profile(&config).unwrap(), vec![]);
+        mock.reply("GET country", "\n");
+        assert_eq!(wpa.lint_profile(&NetworkConfig::open("cafe").set("frequency", 5975)).unwrap(),
+                   vec![LintWarning::DisallowedChannel(5975, None)]);
+    }
+
+    #[test]
+    fn channels() {
+        assert!(is_dfs(5260) && is_dfs(5720) && !is_dfs(5240) && !is_dfs(5745));
+        let japan = "JP".parse().ok();
+        assert!(is_allowed(Band::Ghz2_4, 14, japan) && !is_allowed(Band::Ghz2_4, 14, None));
+        assert!(is_allowed(Band::Ghz5, 184, japan) && !is_allowed(Band::Ghz5, 184, "DE".parse().ok()));
+        assert!(!is_allowed(Band::Ghz6, 1, Some(CountryCode::WORLD)) && is_allowed(Band::Ghz6, 1, "US".parse().ok()));
+    }
+}
@@ -0,0 +1,206 @@
+use config::{Property, PropertyValue};
+use error::Error;
+use macaddr::MacAddr;
+use network::{NetworkConfig, NetworkId, NetworkValue};
+use parse;
+use std::fmt;
+use super::Result;
+use version::Version;
+use wpactrl::WpaCtrl;
+
+/// Which MAC address to use, as set by the `mac_addr` and `preassoc_mac_addr` variables
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MacAddrPolicy {
+    /// The permanent address of the device (`0`)
+    Permanent,
+    /// A random address, changed for each association (`1`)
+    Random,
+    /// A random address that keeps the vendor OUI of the permanent one (`2`)
+    RandomKeepOui,
+}
+
+impl MacAddrPolicy {
+    /// The value of the policy in the configuration
+    pub fn value(&self) -> u8 {
+        match *self {
+            MacAddrPolicy::Permanent => 0,
+            MacAddrPolicy::Random => 1,
+            MacAddrPolicy::RandomKeepOui => 2,
+        }
+    }
+}
+
+impl fmt::Display for MacAddrPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl From<MacAddrPolicy> for PropertyValue {
+    fn from(policy: MacAddrPolicy) -> Self {
+        policy.value().into()
+    }
+}
+
+impl From<MacAddrPolicy> for NetworkValue {
+    fn from(policy: MacAddrPolicy) -> Self {
+        policy.value().into()
+    }
+}
+
+impl From<MacAddr> for NetworkValue {
+    fn from(addr: MacAddr) -> Self {
+        NetworkValue::Raw(addr.to_string())
+    }
+}
+
+impl NetworkConfig {
+    /// Which address to use when connecting to this network, overriding `WpaCtrl::set_mac_addr`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{MacAddrPolicy, NetworkConfig};
+    /// let config = NetworkConfig::wpa_psk("cafe", "passphrase").mac_addr(MacAddrPolicy::Random);
+    /// ```
+    pub fn mac_addr(self, policy: MacAddrPolicy) -> Self {
+        self.set("mac_addr", policy)
+    }
+
+    /// Always use `addr` when connecting to this network
+    ///
+    /// This is a dedicated address for the network (`mac_addr=3`), so the
+    /// network sees a stable address that is not the permanent one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkConfig;
+    /// let config = NetworkConfig::wpa_psk("home", "passphrase").mac_value("02:12:34:56:78:9a".parse().unwrap());
+    /// ```
+    pub fn mac_value(self, addr: MacAddr) -> Self {
+        self.set("mac_addr", 3).set("mac_value", addr)
+    }
+}
+
+impl WpaCtrl {
+    /// Which address to use when connecting, for networks that do not set their own
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::MacAddrPolicy;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.set_mac_addr(MacAddrPolicy::Random).unwrap();
+    /// ```
+    pub fn set_mac_addr(&mut self, policy: MacAddrPolicy) -> Result<()> {
+        self.set(Property::MacAddr, policy)
+    }
+
+    /// Which address to use while scanning and otherwise not associated
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::MacAddrPolicy;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.set_preassoc_mac_addr(MacAddrPolicy::RandomKeepOui).unwrap();
+    /// ```
+    pub fn set_preassoc_mac_addr(&mut self, policy: MacAddrPolicy) -> Result<()> {
+        self.set(Property::PreassocMacAddr, policy)
+    }
+
+    /// Which address to use when connecting to a configured network
+    ///
+    /// With `None`, the network follows `set_mac_addr` again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{MacAddrPolicy, NetworkId};
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.set_network_mac_addr(NetworkId(0), Some(MacAddrPolicy::Permanent)).unwrap();
+    /// ```
+    pub fn set_network_mac_addr(&mut self, id: NetworkId, policy: Option<MacAddrPolicy>) -> Result<()> {
+        match policy {
+            Some(policy) => self.set_network(id, "mac_addr", policy),
+            None => self.set_network(id, "mac_addr", -1),
+        }
+    }
+
+    /// Always use `addr` when connecting to a configured network
+    ///
+    /// Fails with `Error::NotSupported` on releases before 2.11, which
+    /// lack dedicated per-network addresses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkId;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.set_network_mac_value(NetworkId(0), "02:12:34:56:78:9a".parse().unwrap()).unwrap();
+    /// ```
+    pub fn set_network_mac_value(&mut self, id: NetworkId, addr: MacAddr) -> Result<()> {
+        let set = self.set_network(id, "mac_value", addr);
+        self.since(Version::new(2, 11), set)?;
+        self.set_network(id, "mac_addr", 3)
+    }
+
+    /// The MAC address the interface currently uses, as shown by `STATUS`
+    ///
+    /// With randomization enabled this differs from the permanent address,
+    /// and changes between associations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let addr = wpa.current_mac_addr().unwrap();
+    /// println!("{} (randomized: {})", addr, addr.is_local());
+    /// ```
+    pub fn current_mac_addr(&mut self) -> Result<MacAddr> {
+        parse_address(&self.request("STATUS")?)
+    }
+}
+
+/// The `address` of a `STATUS` reply
+fn parse_address(status: &str) -> Result<MacAddr> {
+    parse::key_value(status, "address")
+        .and_then(|addr| addr.parse().ok())
+        .ok_or_else(|| Error::Parse(status.to_owned()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn network_config() {
+        let addr: MacAddr = "02:12:34:56:78:9a".parse().unwrap();
+        let config = NetworkConfig::open("cafe").mac_value(addr);
+        assert_eq!(config.get("mac_addr"), Some(&NetworkValue::Raw("3".into())));
+        assert_eq!(config.get("mac_value"), Some(&NetworkValue::Raw("02:12:34:56:78:9a".into())));
+        let config = config.mac_addr(MacAddrPolicy::RandomKeepOui);
+        assert_eq!(config.get("mac_addr"), Some(&NetworkValue::Raw("2".into())));
+    }
+
+    #[test]
+    fn address() {
+        let status = "bssid=02:00:00:00:01:00\nwpa_state=COMPLETED\naddress=02:12:34:56:78:9a\n";
+        assert_eq!(parse_address(status).unwrap().to_string(), "02:12:34:56:78:9a");
+        assert!(parse_address("wpa_state=DISCONNECTED\n").is_err());
+    }
+
+    #[test]
+    fn setters() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_mac_policy_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("SET preassoc_mac_addr 1", "OK\n");
+        mock.reply("SET_NETWORK 0 mac_addr -1", "OK\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        wpa.set_preassoc_mac_addr(MacAddrPolicy::Random).unwrap();
+        wpa.set_network_mac_addr(NetworkId(0), None).unwrap();
+        assert_eq!(mock.commands(), vec!["SET preassoc_mac_addr 1", "SET_NETWORK 0 mac_addr -1"]);
+    }
+}
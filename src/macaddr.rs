@@ -0,0 +1,132 @@
+use error::Error;
+use std::fmt;
+use std::str::FromStr;
+use super::Result;
+
+/// A MAC address, such as a BSSID or a station's address
+///
+/// Parsed from and formatted as six colon-separated hex bytes, the form
+/// used throughout the control interface. Parsing also accepts upper case
+/// and `-` separators, as long as the same separator is used throughout.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::MacAddr;
+/// let addr: MacAddr = "02:00:00:00:01:00".parse().unwrap();
+/// assert_eq!(addr.octets(), [2, 0, 0, 0, 1, 0]);
+/// assert_eq!(addr.to_string(), "02:00:00:00:01:00");
+/// assert!(addr.is_local());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MacAddr([u8; 6]);
+
+impl MacAddr {
+    /// The all-zero address, used by wpasupplicant for "any" or "none"
+    pub const ZERO: MacAddr = MacAddr([0; 6]);
+
+    /// The broadcast address, `ff:ff:ff:ff:ff:ff`
+    pub const BROADCAST: MacAddr = MacAddr([0xff; 6]);
+
+    /// An address from its bytes
+    pub fn new(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+
+    /// The bytes of the address
+    pub fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+
+    /// Whether this is a multicast (or broadcast) address
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 1 != 0
+    }
+
+    /// Whether this is a locally administered address, eg a randomized one
+    pub fn is_local(&self) -> bool {
+        self.0[0] & 2 != 0
+    }
+
+    /// Whether this is the all-zero address
+    pub fn is_zero(&self) -> bool {
+        *self == MacAddr::ZERO
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || Error::Parse(s.to_owned());
+        let sep = match s.as_bytes().get(2) {
+            Some(b':') => ':',
+            Some(b'-') => '-',
+            _ => return Err(err()),
+        };
+        let mut octets = [0; 6];
+        let mut parts = s.split(sep);
+        for octet in &mut octets {
+            let part = parts.next().filter(|p| p.len() == 2 && p.bytes().all(|b| b.is_ascii_hexdigit()));
+            *octet = u8::from_str_radix(part.ok_or_else(err)?, 16).map_err(|_| err())?;
+        }
+        if parts.next().is_some() {
+            return Err(err());
+        }
+        Ok(MacAddr(octets))
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let o = &self.0;
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", o[0], o[1], o[2], o[3], o[4], o[5])
+    }
+}
+
+/// Shows the address in its usual form rather than as bytes
+impl fmt::Debug for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MacAddr({})", self)
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+}
+
+impl From<MacAddr> for [u8; 6] {
+    fn from(addr: MacAddr) -> Self {
+        addr.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let addr: MacAddr = "00:11:22:AA:bb:FF".parse().unwrap();
+        assert_eq!(addr.octets(), [0x00, 0x11, 0x22, 0xaa, 0xbb, 0xff]);
+        assert_eq!(addr.to_string(), "00:11:22:aa:bb:ff");
+        assert_eq!("00-11-22-aa-bb-ff".parse::<MacAddr>().unwrap(), addr);
+        assert_eq!(format!("{:?}", addr), "MacAddr(00:11:22:aa:bb:ff)");
+        for bad in &["", "00:11:22:33:44", "00:11:22:33:44:55:66", "0:11:22:33:44:55", "00:11:22:33:44:zz",
+                     "+0:11:22:33:44:55", "00:11:22:33:44:+5", "00:11-22:33:44:55", "00-11-22-33-44:55",
+                     "001122334455", "00.11.22.33.44.55"] {
+            assert!(bad.parse::<MacAddr>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        assert!(MacAddr::BROADCAST.is_multicast());
+        assert!(MacAddr::ZERO.is_zero());
+        assert!(!MacAddr::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]).is_local());
+        assert!(MacAddr::new([0x02, 0x11, 0x22, 0x33, 0x44, 0x55]).is_local());
+    }
+}
@@ -0,0 +1,160 @@
+use macaddr::MacAddr;
+use network::NetworkId;
+use parse::{event, event_arg};
+use super::Result;
+use version::Version;
+use wpactrl::{expect_ok, WpaCtrl};
+
+/// The release adding `MESH_PEER_ADD` and `MESH_PEER_REMOVE`
+const MESH_PEER_SINCE: Version = Version::new(2, 6);
+
+/// An 802.11s mesh event
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MeshEvent {
+    /// This device joined a mesh (`MESH-GROUP-STARTED`)
+    GroupStarted {
+        /// The mesh ID
+        ssid: String,
+        /// The network block the mesh was started from
+        id: Option<NetworkId>,
+    },
+    /// This device left the mesh on the given interface (`MESH-GROUP-REMOVED`)
+    GroupRemoved(String),
+    /// A peering with the given mesh station was established (`MESH-PEER-CONNECTED`)
+    PeerConnected(MacAddr),
+    /// A peering with the given mesh station was closed (`MESH-PEER-DISCONNECTED`)
+    PeerDisconnected(MacAddr),
+    /// SAE authentication with the given mesh station failed (`MESH-SAE-AUTH-FAILURE`)
+    SaeAuthFailure(MacAddr),
+}
+
+impl MeshEvent {
+    /// Parse a mesh event, returning None for other messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::MeshEvent;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((_, msg)) = wpa.recv().unwrap() {
+    ///     if let Some(MeshEvent::PeerConnected(addr)) = MeshEvent::parse(&msg) {
+    ///         println!("Peered with {}", addr);
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        let word = || body.split(' ').next().filter(|w| !w.is_empty()).map(str::to_owned);
+        match event {
+            "MESH-GROUP-STARTED" => Some(MeshEvent::GroupStarted {
+                ssid: event_arg(body, "ssid")?.to_owned(),
+                id: event_arg(body, "id").and_then(|id| id.parse().ok()).map(NetworkId),
+            }),
+            "MESH-GROUP-REMOVED" => word().map(MeshEvent::GroupRemoved),
+            "MESH-PEER-CONNECTED" => word()?.parse().ok().map(MeshEvent::PeerConnected),
+            "MESH-PEER-DISCONNECTED" => word()?.parse().ok().map(MeshEvent::PeerDisconnected),
+            "MESH-SAE-AUTH-FAILURE" => event_arg(body, "addr")?.parse().ok().map(MeshEvent::SaeAuthFailure),
+            _ => None,
+        }
+    }
+}
+
+impl WpaCtrl {
+    /// Join the mesh described by a network block
+    ///
+    /// The network must have been configured with `mode=5`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkConfig;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let config = NetworkConfig::open("mesh").set("mode", 5).set("frequency", 2412);
+    /// let id = wpa.create_network(config).unwrap();
+    /// wpa.mesh_group_add(id).unwrap();
+    /// ```
+    pub fn mesh_group_add(&mut self, id: NetworkId) -> Result<()> {
+        expect_ok(&self.request(&format!("MESH_GROUP_ADD {}", id))?)
+    }
+
+    /// Leave the mesh running on the given network interface
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.mesh_group_remove("wlan0").unwrap();
+    /// ```
+    pub fn mesh_group_remove(&mut self, ifname: &str) -> Result<()> {
+        expect_ok(&self.request(&format!("MESH_GROUP_REMOVE {}", ifname))?)
+    }
+
+    /// Establish a peering with a mesh station
+    ///
+    /// `duration` is how long in seconds the peer stays blocked if the
+    /// peering fails. Fails with `Error::NotSupported` on releases before 2.6.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.mesh_peer_add("02:00:00:00:01:00".parse().unwrap(), None).unwrap();
+    /// ```
+    pub fn mesh_peer_add(&mut self, addr: MacAddr, duration: Option<u32>) -> Result<()> {
+        let cmd = match duration {
+            Some(duration) => format!("MESH_PEER_ADD {} duration={}", addr, duration),
+            None => format!("MESH_PEER_ADD {}", addr),
+        };
+        let added = expect_ok(&self.request(&cmd)?);
+        self.since(MESH_PEER_SINCE, added)
+    }
+
+    /// Close the peering with a mesh station
+    ///
+    /// Fails with `Error::NotSupported` on releases before 2.6.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.mesh_peer_remove("02:00:00:00:01:00".parse().unwrap()).unwrap();
+    /// ```
+    pub fn mesh_peer_remove(&mut self, addr: MacAddr) -> Result<()> {
+        let removed = expect_ok(&self.request(&format!("MESH_PEER_REMOVE {}", addr))?);
+        self.since(MESH_PEER_SINCE, removed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn events() {
+        assert_eq!(MeshEvent::parse("<3>MESH-GROUP-STARTED ssid=\"my mesh\" id=2"),
+                   Some(MeshEvent::GroupStarted { ssid: "my mesh".into(), id: Some(NetworkId(2)) }));
+        assert_eq!(MeshEvent::parse("<3>MESH-GROUP-REMOVED wlan0"), Some(MeshEvent::GroupRemoved("wlan0".into())));
+        assert_eq!(MeshEvent::parse("<3>MESH-PEER-CONNECTED 02:00:00:00:01:00"),
+                   Some(MeshEvent::PeerConnected("02:00:00:00:01:00".parse().unwrap())));
+        assert_eq!(MeshEvent::parse("<3>MESH-PEER-DISCONNECTED 02:00:00:00:01:00"),
+                   Some(MeshEvent::PeerDisconnected("02:00:00:00:01:00".parse().unwrap())));
+        assert_eq!(MeshEvent::parse("<3>MESH-SAE-AUTH-FAILURE addr=02:00:00:00:01:00"),
+                   Some(MeshEvent::SaeAuthFailure("02:00:00:00:01:00".parse().unwrap())));
+        assert_eq!(MeshEvent::parse("<3>MESH-PEER-CONNECTED"), None);
+        assert_eq!(MeshEvent::parse("<3>P2P-FIND-STOPPED"), None);
+    }
+
+    #[test]
+    fn peer_add_unsupported() {
+        use error::Error;
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_mesh_peer_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("MESH_PEER_ADD 02:00:00:00:01:00", "UNKNOWN COMMAND\n");
+        mock.reply("GET version", "2.5\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        let addr = "02:00:00:00:01:00".parse().unwrap();
+        assert!(matches!(wpa.mesh_peer_add(addr, None), Err(Error::NotSupported)));
+    }
+}
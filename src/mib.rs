@@ -0,0 +1,87 @@
+use parse;
+use super::Result;
+use wpactrl::{reply_error, WpaCtrl};
+
+/// The dot1x / RSN variables reported by `MIB`, in reply order
+///
+/// # Examples
+///
+/// ```
+/// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+/// let mib = wpa.mib().unwrap();
+/// if let Some(failures) = mib.counter("dot11RSNA4WayHandshakeFailures") {
+///     println!("{} failed handshakes", failures);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Mib {
+    /// The variables, in the order they were reported
+    pub entries: Vec<(String, String)>,
+    /// The unparsed reply
+    pub raw: String,
+}
+
+impl Mib {
+    /// Parse the reply to `MIB`
+    fn parse(reply: &str) -> Result<Self> {
+        if let Some(e) = reply_error(reply) {
+            return Err(e);
+        }
+        Ok(Mib {
+            entries: parse::key_values(reply).into_iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect(),
+            raw: reply.to_owned(),
+        })
+    }
+
+    /// The value of a variable, if reported
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// The value of a numeric variable such as a counter, if reported
+    pub fn counter(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(|v| v.parse().ok())
+    }
+}
+
+impl WpaCtrl {
+    /// Read the dot1x / RSN statistics
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for (key, value) in wpa.mib().unwrap().entries {
+    ///     println!("{}={}", key, value);
+    /// }
+    /// ```
+    pub fn mib(&mut self) -> Result<Mib> {
+        Mib::parse(&self.request("MIB")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mib() {
+        let mib = Mib::parse(concat!(
+            "dot11RSNAOptionImplemented=TRUE\n",
+            "dot11RSNAPreauthenticationImplemented=TRUE\n",
+            "dot11RSNAEnabled=FALSE\n",
+            "dot11RSNAConfigPMKLifetime=43200\n",
+            "dot11RSNAPairwiseCipherSelected=00-0f-ac-4\n",
+            "dot11RSNA4WayHandshakeFailures=0\n",
+            "dot1xSuppPaeState=5\n",
+        )).unwrap();
+        assert_eq!(mib.entries.len(), 7);
+        assert_eq!(mib.entries[0], ("dot11RSNAOptionImplemented".to_owned(), "TRUE".to_owned()));
+        assert_eq!(mib.get("dot11RSNAPairwiseCipherSelected"), Some("00-0f-ac-4"));
+        assert_eq!(mib.counter("dot11RSNAConfigPMKLifetime"), Some(43200));
+        assert_eq!(mib.counter("dot11RSNAEnabled"), None);
+        assert!(Mib::parse("FAIL\n").is_err());
+        assert!(Mib::parse("UNKNOWN COMMAND\n").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use error::Error;
+use hostapd::HostapdCtrl;
+use std::path::Path;
+use super::Result;
+use wpactrl::WpaCtrlBuilder;
+
+/// Connections to all BSSes of a hostapd interface
+///
+/// hostapd has a control socket per BSS, eg `wlan0` for the main network
+/// and `wlan0_1` for a guest network. This holds a connection to each,
+/// so commands can be sent to a specific BSS by name.
+///
+/// Created by `WpaCtrlBuilder::open_hostapd_bsses`.
+pub struct MultiBss {
+    bsses: Vec<(String, HostapdCtrl)>,
+}
+
+impl MultiBss {
+    /// The names of the BSSes, the first being the interface itself
+    pub fn names(&self) -> Vec<&str> {
+        self.bsses.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// The connection to the BSS `name`, if the interface has it
+    pub fn get(&mut self, name: &str) -> Option<&mut HostapdCtrl> {
+        self.bsses.iter_mut().find(|(bss, _)| bss == name).map(|(_, hostapd)| hostapd)
+    }
+
+    /// Send a command to the BSS `name`
+    ///
+    /// Fails with `Error::NoSuchBss` if the interface has no such BSS.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut bsses = wpactrl::WpaCtrl::new().open_hostapd_bsses().unwrap();
+    /// println!("{}", bsses.request("wlan0_1", "GET_CONFIG").unwrap());
+    /// ```
+    pub fn request(&mut self, name: &str, cmd: &str) -> Result<String> {
+        let missing = || Error::NoSuchBss(name.to_owned());
+        self.get(name).ok_or_else(missing)?.request(cmd)
+    }
+
+    /// Iterate over the BSSes and their connections
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut HostapdCtrl)> {
+        self.bsses.iter_mut().map(|(name, hostapd)| (name.as_str(), hostapd))
+    }
+
+    /// Split into the connections, by BSS name
+    pub fn into_inner(self) -> Vec<(String, HostapdCtrl)> {
+        self.bsses
+    }
+}
+
+impl HostapdCtrl {
+    /// The names of the BSSes of the interface, as shown by `STATUS`
+    ///
+    /// The first is the interface itself. Each name is also the name of
+    /// the control socket of the BSS.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// println!("{:?}", hostapd.bss_names().unwrap());
+    /// ```
+    pub fn bss_names(&mut self) -> Result<Vec<String>> {
+        Ok(self.status()?.bss.into_iter().map(|bss| bss.ifname).collect())
+    }
+}
+
+impl WpaCtrlBuilder {
+    /// Open a control interface to each BSS of a hostapd interface
+    ///
+    /// The interface is opened as with `open_hostapd`, and the other BSSes
+    /// it reports are opened with the same options, from the same control
+    /// directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut bsses = wpactrl::WpaCtrl::new().ctrl_path("/var/run/hostapd/wlan0").open_hostapd_bsses().unwrap();
+    /// for (name, hostapd) in bsses.iter_mut() {
+    ///     println!("{}: {} stations", name, hostapd.stations().count());
+    /// }
+    /// ```
+    pub fn open_hostapd_bsses(self) -> Result<MultiBss> {
+        let mut first = self.clone().open_hostapd()?;
+        let path = first.ctrl_path().to_owned();
+        let dir = path.parent().unwrap_or_else(|| Path::new("/"));
+        let own_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("").to_owned();
+        let mut names = first.bss_names()?;
+        if names.is_empty() {
+            names.push(own_name.clone());
+        }
+        let mut first = Some(first);
+        let mut bsses = Vec::with_capacity(names.len());
+        for name in names {
+            if name == own_name {
+                if let Some(hostapd) = first.take() {
+                    bsses.push((name, hostapd));
+                    continue;
+                }
+            }
+            let hostapd = self.clone().ctrl_path(dir.join(&name)).open_hostapd()?;
+            bsses.push((name, hostapd));
+        }
+        Ok(MultiBss { bsses })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use error::Error;
+    use testing::MockSupplicant;
+    use wpactrl::WpaCtrl;
+
+    #[test]
+    fn open_hostapd_bsses() {
+        let dir = ::std::env::temp_dir().join(format!("wpactrl_test_multi_bss_{}", ::std::process::id()));
+        ::std::fs::create_dir_all(&dir).unwrap();
+        let main = MockSupplicant::new(dir.join("wlan0")).unwrap();
+        let guest = MockSupplicant::new(dir.join("wlan0_1")).unwrap();
+        main.reply("STATUS", "state=ENABLED\nbss[0]=wlan0\nnum_sta[0]=3\nbss[1]=wlan0_1\nnum_sta[1]=1\n");
+        guest.reply("GET_CONFIG", "bssid=02:00:00:00:03:01\nssid=guest\n");
+        let mut bsses = WpaCtrl::new().ctrl_path(dir.join("wlan0")).open_hostapd_bsses().unwrap();
+        assert_eq!(bsses.names(), vec!["wlan0", "wlan0_1"]);
+        assert_eq!(bsses.get("wlan0_1").unwrap().get_config().unwrap().ssid, "guest");
+        assert_eq!(bsses.request("wlan0", "PING").unwrap(), "PONG\n");
+        assert!(matches!(bsses.request("wlan0_2", "PING"), Err(Error::NoSuchBss(ref name)) if name == "wlan0_2"));
+        assert_eq!(main.commands(), vec!["STATUS", "PING"]);
+        assert_eq!(guest.commands(), vec!["GET_CONFIG"]);
+        drop((bsses, main, guest));
+        ::std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,463 @@
+use error::Error;
+use macaddr::MacAddr;
+use parse;
+use secret;
+use std::collections::HashSet;
+use std::fmt;
+use super::Result;
+use wpactrl::{expect_ok, Request, WpaCtrl};
+
+/// Identifier of a network block in the wpasupplicant configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NetworkId(pub u32);
+
+impl fmt::Display for NetworkId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<u32> for NetworkId {
+    fn from(id: u32) -> Self {
+        NetworkId(id)
+    }
+}
+
+/// A value for a network variable, formatted as `SET_NETWORK` expects it
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetworkValue {
+    /// A string, such as an SSID or passphrase, which is quoted when sent
+    ///
+    /// Strings containing control characters are sent hex-encoded instead.
+    Str(String),
+    /// A value sent verbatim, such as `WPA-PSK` for `key_mgmt` or a raw PSK
+    Raw(String),
+}
+
+impl NetworkValue {
+    /// Format the value as it appears in a `SET_NETWORK` command
+    pub(crate) fn to_arg(&self) -> String {
+        match *self {
+            NetworkValue::Str(ref s) if s.chars().any(char::is_control) => {
+                parse::hex_encode(s.as_bytes())
+            }
+            NetworkValue::Str(ref s) => format!("\"{}\"", s),
+            NetworkValue::Raw(ref s) => s.clone(),
+        }
+    }
+}
+
+/// Wipes the value from memory, as it may be a credential
+#[cfg(feature = "zeroize")]
+impl Drop for NetworkValue {
+    fn drop(&mut self) {
+        match *self {
+            NetworkValue::Str(ref mut s) | NetworkValue::Raw(ref mut s) => secret::wipe(s),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for NetworkValue {
+    fn from(s: &'a str) -> Self {
+        NetworkValue::Str(s.to_owned())
+    }
+}
+
+impl From<String> for NetworkValue {
+    fn from(s: String) -> Self {
+        NetworkValue::Str(s)
+    }
+}
+
+macro_rules! network_value_from_int {
+    ($($t:ty),*) => {$(
+        impl From<$t> for NetworkValue {
+            fn from(n: $t) -> Self {
+                NetworkValue::Raw(n.to_string())
+            }
+        }
+    )*}
+}
+network_value_from_int!(i32, u8, u16, u32, i64, u64);
+
+impl From<bool> for NetworkValue {
+    fn from(b: bool) -> Self {
+        NetworkValue::Raw(if b { "1" } else { "0" }.to_owned())
+    }
+}
+
+/// A flag of a configured network, as shown by `LIST_NETWORKS`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NetworkFlag {
+    /// The network is currently in use
+    Current,
+    /// The network is disabled
+    Disabled,
+    /// The network is temporarily disabled after repeated failures
+    TempDisabled,
+    /// The network is a persistent P2P group
+    P2pPersistent,
+    /// A flag not known to this library
+    Other(String),
+}
+
+impl<'a> From<&'a str> for NetworkFlag {
+    fn from(flag: &'a str) -> Self {
+        match flag {
+            "CURRENT" => NetworkFlag::Current,
+            "DISABLED" => NetworkFlag::Disabled,
+            "TEMP-DISABLED" => NetworkFlag::TempDisabled,
+            "P2P-PERSISTENT" => NetworkFlag::P2pPersistent,
+            other => NetworkFlag::Other(other.to_owned()),
+        }
+    }
+}
+
+/// A configured network, as shown by `LIST_NETWORKS`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NetworkEntry {
+    /// The network id
+    pub id: NetworkId,
+    /// The SSID, as escaped by wpasupplicant
+    pub ssid: String,
+    /// The BSSID the network is restricted to, if any
+    pub bssid: Option<MacAddr>,
+    /// The network's flags
+    pub flags: HashSet<NetworkFlag>,
+    /// The unparsed row, for columns this type does not cover
+    pub raw: String,
+}
+
+impl NetworkEntry {
+    /// Parse a single tab-separated row of `LIST_NETWORKS` output
+    fn from_row(row: &[&str]) -> Result<Self> {
+        let err = || Error::Parse(row.join("\t"));
+        if row.len() < 3 {
+            return Err(err());
+        }
+        Ok(NetworkEntry {
+            id: NetworkId(row[0].parse().map_err(|_| err())?),
+            ssid: row[1].to_owned(),
+            bssid: match row[2] {
+                "any" | "" => None,
+                bssid => Some(bssid.parse().map_err(|_| err())?),
+            },
+            flags: row.get(3).map(|f| parse::flags(f).map(NetworkFlag::from).collect()).unwrap_or_default(),
+            raw: row.join("\t"),
+        })
+    }
+}
+
+/// Configuration for a new network block
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{NetworkConfig, NetworkValue};
+/// let config = NetworkConfig::wpa_psk("home", "correct horse battery staple")
+///     .set("scan_ssid", 1)
+///     .set("priority", 5);
+/// assert_eq!(config.get("priority"), Some(&NetworkValue::Raw("5".into())));
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct NetworkConfig {
+    vars: Vec<(String, NetworkValue)>,
+}
+
+/// Shows secret variables such as `psk` as `[REDACTED]`
+impl fmt::Debug for NetworkConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut map = f.debug_map();
+        for (key, value) in &self.vars {
+            if secret::is_secret(key) {
+                map.entry(key, &format_args!("{}", secret::REDACTED));
+            } else {
+                map.entry(key, value);
+            }
+        }
+        map.finish()
+    }
+}
+
+impl NetworkConfig {
+    /// An unencrypted network
+    pub fn open<S: Into<String>>(ssid: S) -> Self {
+        NetworkConfig { vars: Vec::new() }
+            .set("ssid", ssid.into())
+            .set("key_mgmt", NetworkValue::Raw("NONE".into()))
+    }
+
+    /// A WPA/WPA2-Personal network secured by a passphrase
+    pub fn wpa_psk<S: Into<String>, P: Into<String>>(ssid: S, passphrase: P) -> Self {
+        NetworkConfig { vars: Vec::new() }
+            .set("ssid", ssid.into())
+            .set("key_mgmt", NetworkValue::Raw("WPA-PSK".into()))
+            .set("psk", passphrase.into())
+    }
+
+    /// A WPA3-Personal network secured by a password, using SAE
+    ///
+    /// Management frame protection is required, as mandated for WPA3.
+    pub fn sae<S: Into<String>, P: Into<String>>(ssid: S, password: P) -> Self {
+        NetworkConfig { vars: Vec::new() }
+            .set("ssid", ssid.into())
+            .set("key_mgmt", NetworkValue::Raw("SAE".into()))
+            .set("sae_password", password.into())
+            .set("ieee80211w", 2)
+    }
+
+    /// A network in WPA2/WPA3-Personal transition mode
+    ///
+    /// SAE is used if the access point supports it, and WPA2-PSK otherwise;
+    /// management frame protection is used when available.
+    pub fn sae_transition<S: Into<String>, P: Into<String>>(ssid: S, passphrase: P) -> Self {
+        NetworkConfig { vars: Vec::new() }
+            .set("ssid", ssid.into())
+            .set("key_mgmt", NetworkValue::Raw("WPA-PSK SAE".into()))
+            .set("psk", passphrase.into())
+            .set("ieee80211w", 1)
+    }
+
+    /// Set a network variable, replacing any previous value
+    pub fn set<V: Into<NetworkValue>>(mut self, key: &str, value: V) -> Self {
+        let value = value.into();
+        match self.vars.iter_mut().find(|(k, _)| k == key) {
+            Some(var) => var.1 = value,
+            None => self.vars.push((key.to_owned(), value)),
+        }
+        self
+    }
+
+    /// The value of a network variable, if set
+    pub fn get(&self, key: &str) -> Option<&NetworkValue> {
+        self.vars.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// All variables of the configuration, in the order they are applied
+    pub fn vars(&self) -> &[(String, NetworkValue)] {
+        &self.vars
+    }
+}
+
+/// Parse the output of `LIST_NETWORKS`
+pub(crate) fn parse_list_networks(reply: &str) -> Result<Vec<NetworkEntry>> {
+    parse::rows(reply).map(|row| NetworkEntry::from_row(&row)).collect()
+}
+
+pub(crate) fn add_network<C: Request>(ctrl: &mut C) -> Result<NetworkId> {
+    let reply = ctrl.request("ADD_NETWORK")?;
+    reply.trim().parse().map(NetworkId).map_err(|_| Error::Parse(reply))
+}
+
+pub(crate) fn set_network<C: Request>(ctrl: &mut C, id: NetworkId, key: &str, value: &NetworkValue) -> Result<()> {
+    let mut arg = value.to_arg();
+    let mut cmd = format!("SET_NETWORK {} {} {}", id, key, arg);
+    let reply = ctrl.request(&cmd);
+    secret::wipe(&mut arg);
+    secret::wipe(&mut cmd);
+    expect_ok(&reply?)
+}
+
+pub(crate) fn select_network<C: Request>(ctrl: &mut C, id: NetworkId) -> Result<()> {
+    expect_ok(&ctrl.request(&format!("SELECT_NETWORK {}", id))?)
+}
+
+pub(crate) fn remove_network<C: Request>(ctrl: &mut C, id: NetworkId) -> Result<()> {
+    expect_ok(&ctrl.request(&format!("REMOVE_NETWORK {}", id))?)
+}
+
+/// Add a network block and apply a configuration to it, removing it again on failure
+pub(crate) fn create_network<C: Request>(ctrl: &mut C, config: NetworkConfig) -> Result<NetworkId> {
+    let id = add_network(ctrl)?;
+    for (key, value) in config.vars() {
+        if let Err(e) = set_network(ctrl, id, key, value) {
+            let _ = remove_network(ctrl, id);
+            return Err(e);
+        }
+    }
+    Ok(id)
+}
+
+impl WpaCtrl {
+    /// List the configured networks
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for network in wpa.list_networks().unwrap() {
+    ///     println!("{}: {}", network.id, network.ssid);
+    /// }
+    /// ```
+    pub fn list_networks(&mut self) -> Result<Vec<NetworkEntry>> {
+        parse_list_networks(&self.request("LIST_NETWORKS")?)
+    }
+
+    /// Add a new, disabled network block and return its id
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.add_network().unwrap();
+    /// wpa.remove_network(id).unwrap();
+    /// ```
+    pub fn add_network(&mut self) -> Result<NetworkId> {
+        add_network(self)
+    }
+
+    /// Set a variable of a network block
+    ///
+    /// Plain strings are quoted as needed; use `NetworkValue::Raw` for
+    /// values such as `key_mgmt` that must be passed through unquoted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkValue;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.add_network().unwrap();
+    /// wpa.set_network(id, "ssid", "my network").unwrap();
+    /// wpa.set_network(id, "key_mgmt", NetworkValue::Raw("WPA-PSK".into())).unwrap();
+    /// wpa.set_network(id, "psk", "correct horse battery staple").unwrap();
+    /// wpa.set_network(id, "priority", 5).unwrap();
+    /// wpa.remove_network(id).unwrap();
+    /// ```
+    pub fn set_network<V: Into<NetworkValue>>(&mut self, id: NetworkId, key: &str, value: V) -> Result<()> {
+        set_network(self, id, key, &value.into())
+    }
+
+    /// Enable a network block
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.add_network().unwrap();
+    /// wpa.set_network(id, "ssid", "my network").unwrap();
+    /// wpa.enable_network(id).unwrap();
+    /// wpa.remove_network(id).unwrap();
+    /// ```
+    pub fn enable_network(&mut self, id: NetworkId) -> Result<()> {
+        expect_ok(&self.request(&format!("ENABLE_NETWORK {}", id))?)
+    }
+
+    /// Select a network block, disabling all others
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.add_network().unwrap();
+    /// wpa.set_network(id, "ssid", "my network").unwrap();
+    /// wpa.select_network(id).unwrap();
+    /// wpa.remove_network(id).unwrap();
+    /// ```
+    pub fn select_network(&mut self, id: NetworkId) -> Result<()> {
+        select_network(self, id)
+    }
+
+    /// Remove a network block
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.add_network().unwrap();
+    /// wpa.remove_network(id).unwrap();
+    /// ```
+    pub fn remove_network(&mut self, id: NetworkId) -> Result<()> {
+        remove_network(self, id)
+    }
+
+    /// Add a network block configured from a `NetworkConfig` or `EapNetworkConfig`
+    ///
+    /// If any variable cannot be set, the network block is removed again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkConfig;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.create_network(NetworkConfig::wpa_psk("home", "correct horse battery staple")).unwrap();
+    /// wpa.enable_network(id).unwrap();
+    /// ```
+    pub fn create_network<C: Into<NetworkConfig>>(&mut self, config: C) -> Result<NetworkId> {
+        create_network(self, config.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn network_config_debug() {
+        let debug = format!("{:?}", NetworkConfig::wpa_psk("home", "hunter2"));
+        assert!(debug.contains("\"home\""));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    #[test]
+    fn list_networks() {
+        let networks = parse_list_networks(concat!(
+            "network id / ssid / bssid / flags\n",
+            "0\thome\tany\t[CURRENT]\n",
+            "1\twork wifi\t00:11:22:33:44:55\t[DISABLED][TEMP-DISABLED]\n",
+            "2\tcafe\tany\t\n",
+        )).unwrap();
+        assert_eq!(networks.len(), 3);
+        assert_eq!(networks[0].id, NetworkId(0));
+        assert_eq!(networks[0].ssid, "home");
+        assert_eq!(networks[0].bssid, None);
+        assert!(networks[0].flags.contains(&NetworkFlag::Current));
+        assert_eq!(networks[1].ssid, "work wifi");
+        assert_eq!(networks[1].bssid, "00:11:22:33:44:55".parse().ok());
+        assert!(networks[1].flags.contains(&NetworkFlag::Disabled));
+        assert!(networks[1].flags.contains(&NetworkFlag::TempDisabled));
+        assert!(networks[2].flags.is_empty());
+        assert_eq!(networks[2].raw, "2\tcafe\tany\t");
+    }
+
+    #[test]
+    fn network_value() {
+        assert_eq!(NetworkValue::from("my network").to_arg(), "\"my network\"");
+        assert_eq!(NetworkValue::from("say \"hi\"").to_arg(), "\"say \"hi\"\"");
+        assert_eq!(NetworkValue::from("a\nb").to_arg(), "610a62");
+        assert_eq!(NetworkValue::Raw("WPA-PSK".into()).to_arg(), "WPA-PSK");
+        assert_eq!(NetworkValue::from(5).to_arg(), "5");
+        assert_eq!(NetworkValue::from(true).to_arg(), "1");
+    }
+
+    #[test]
+    fn network_config() {
+        let config = NetworkConfig::wpa_psk("home", "secret").set("psk", "other").set("priority", 1);
+        assert_eq!(config.vars(), &[
+            ("ssid".to_owned(), NetworkValue::Str("home".into())),
+            ("key_mgmt".to_owned(), NetworkValue::Raw("WPA-PSK".into())),
+            ("psk".to_owned(), NetworkValue::Str("other".into())),
+            ("priority".to_owned(), NetworkValue::Raw("1".into())),
+        ][..]);
+        assert_eq!(NetworkConfig::open("cafe").get("key_mgmt"), Some(&NetworkValue::Raw("NONE".into())));
+    }
+
+    #[test]
+    fn sae() {
+        let config = NetworkConfig::sae("home", "correct horse");
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("SAE".into())));
+        assert_eq!(config.get("sae_password"), Some(&NetworkValue::Str("correct horse".into())));
+        assert_eq!(config.get("ieee80211w"), Some(&NetworkValue::Raw("2".into())));
+        let config = NetworkConfig::sae_transition("home", "correct horse");
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("WPA-PSK SAE".into())));
+        assert_eq!(config.get("psk"), Some(&NetworkValue::Str("correct horse".into())));
+        assert_eq!(config.get("ieee80211w"), Some(&NetworkValue::Raw("1".into())));
+    }
+
+    #[test]
+    fn list_networks_invalid() {
+        assert!(parse_list_networks("network id / ssid / bssid / flags\nfoo\tbar\tany\t\n").is_err());
+    }
+}
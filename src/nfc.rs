@@ -0,0 +1,192 @@
+use error::Error;
+use hostapd::HostapdCtrl;
+use network::NetworkId;
+use parse::{hex_decode, hex_encode};
+use std::fmt;
+use super::Result;
+use wpactrl::{expect_ok, reply_error, WpaCtrl};
+
+/// The encoding of an NFC payload
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NfcFormat {
+    /// A bare WPS attribute set
+    Wps,
+    /// An NDEF message, as written to NFC tags
+    Ndef,
+}
+
+impl fmt::Display for NfcFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            NfcFormat::Wps => "WPS",
+            NfcFormat::Ndef => "NDEF",
+        })
+    }
+}
+
+/// The carrier negotiated by an NFC connection handover
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HandoverCarrier {
+    /// WPS, using this device's password token
+    Wps,
+    /// WPS with the credentials of the current network (`WPS-CR`)
+    WpsCr,
+    /// P2P with the credentials of a group (`P2P-CR`)
+    P2pCr,
+    /// DPP bootstrapping
+    Dpp,
+}
+
+impl fmt::Display for HandoverCarrier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            HandoverCarrier::Wps => "WPS",
+            HandoverCarrier::WpsCr => "WPS-CR",
+            HandoverCarrier::P2pCr => "P2P-CR",
+            HandoverCarrier::Dpp => "DPP",
+        })
+    }
+}
+
+/// Parse a reply holding a hex dump
+fn parse_payload(reply: &str) -> Result<Vec<u8>> {
+    if let Some(e) = reply_error(reply) {
+        return Err(e);
+    }
+    hex_decode(reply).ok_or_else(|| Error::Parse(reply.to_owned()))
+}
+
+impl WpaCtrl {
+    /// Generate a WPS password token, to be written to an NFC tag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NfcFormat;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let token = wpa.wps_nfc_token(NfcFormat::Ndef).unwrap();
+    /// println!("{} bytes to write", token.len());
+    /// ```
+    pub fn wps_nfc_token(&mut self, format: NfcFormat) -> Result<Vec<u8>> {
+        parse_payload(&self.request(&format!("WPS_NFC_TOKEN {}", format))?)
+    }
+
+    /// Generate a configuration token holding the credentials of a network
+    ///
+    /// Written to an NFC tag, it lets other devices join the network by
+    /// touching the tag. Without `id`, the current network is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{NetworkId, NfcFormat};
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let token = wpa.wps_nfc_config_token(NfcFormat::Ndef, Some(NetworkId(0))).unwrap();
+    /// println!("{} bytes to write", token.len());
+    /// ```
+    pub fn wps_nfc_config_token(&mut self, format: NfcFormat, id: Option<NetworkId>) -> Result<Vec<u8>> {
+        let cmd = match id {
+            Some(id) => format!("WPS_NFC_CONFIG_TOKEN {} {}", format, id.0),
+            None => format!("WPS_NFC_CONFIG_TOKEN {}", format),
+        };
+        parse_payload(&self.request(&cmd)?)
+    }
+
+    /// Process an NDEF message read from an NFC tag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let tag = vec![0xd2, 0x02, 0x00];
+    /// wpa.wps_nfc_tag_read(&tag).unwrap();
+    /// ```
+    pub fn wps_nfc_tag_read(&mut self, payload: &[u8]) -> Result<()> {
+        expect_ok(&self.request(&format!("WPS_NFC_TAG_READ {}", hex_encode(payload)))?)
+    }
+
+    /// Build the NDEF handover request message to send to a peer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::HandoverCarrier;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let request = wpa.nfc_get_handover_req(HandoverCarrier::Wps).unwrap();
+    /// ```
+    pub fn nfc_get_handover_req(&mut self, carrier: HandoverCarrier) -> Result<Vec<u8>> {
+        parse_payload(&self.request(&format!("NFC_GET_HANDOVER_REQ NDEF {}", carrier))?)
+    }
+
+    /// Build the NDEF handover select message to send to a peer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::HandoverCarrier;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let select = wpa.nfc_get_handover_sel(HandoverCarrier::WpsCr).unwrap();
+    /// ```
+    pub fn nfc_get_handover_sel(&mut self, carrier: HandoverCarrier) -> Result<Vec<u8>> {
+        parse_payload(&self.request(&format!("NFC_GET_HANDOVER_SEL NDEF {}", carrier))?)
+    }
+}
+
+impl HostapdCtrl {
+    /// Generate a configuration token holding the credentials of the access point
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NfcFormat;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let token = hostapd.wps_nfc_config_token(NfcFormat::Ndef).unwrap();
+    /// ```
+    pub fn wps_nfc_config_token(&mut self, format: NfcFormat) -> Result<Vec<u8>> {
+        parse_payload(&self.request(&format!("WPS_NFC_CONFIG_TOKEN {}", format))?)
+    }
+
+    /// Generate a WPS password token for the access point, to be written to an NFC tag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NfcFormat;
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let token = hostapd.wps_nfc_token(NfcFormat::Wps).unwrap();
+    /// ```
+    pub fn wps_nfc_token(&mut self, format: NfcFormat) -> Result<Vec<u8>> {
+        parse_payload(&self.request(&format!("WPS_NFC_TOKEN {}", format))?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn payload() {
+        assert_eq!(parse_payload("d20200\n").unwrap(), vec![0xd2, 0x02, 0x00]);
+        assert!(matches!(parse_payload("FAIL\n"), Err(Error::Failure)));
+        assert!(matches!(parse_payload("UNKNOWN COMMAND\n"), Err(Error::UnknownCommand)));
+        assert!(parse_payload("OK\n").is_err());
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(NfcFormat::Ndef.to_string(), "NDEF");
+        assert_eq!(HandoverCarrier::P2pCr.to_string(), "P2P-CR");
+    }
+
+    #[test]
+    fn config_token() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_nfc_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("WPS_NFC_CONFIG_TOKEN NDEF 1", "d20200\n");
+        mock.reply("WPS_NFC_CONFIG_TOKEN WPS", "FAIL\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert_eq!(wpa.wps_nfc_config_token(NfcFormat::Ndef, Some(NetworkId(1))).unwrap(), vec![0xd2, 0x02, 0x00]);
+        assert!(matches!(wpa.wps_nfc_config_token(NfcFormat::Wps, None), Err(Error::Failure)));
+    }
+}
@@ -0,0 +1,69 @@
+use network::{NetworkConfig, NetworkValue};
+use scan_flags::ScanFlags;
+
+impl NetworkConfig {
+    /// An Enhanced Open network, encrypted without a password using OWE
+    ///
+    /// Management frame protection is used when available, which OWE
+    /// access points require. Unless `owe_only` is set, the open network of
+    /// a transition-mode pair is accepted too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkConfig;
+    /// let config = NetworkConfig::owe("cafe").owe_only();
+    /// ```
+    pub fn owe<S: Into<String>>(ssid: S) -> Self {
+        NetworkConfig::open(ssid)
+            .set("key_mgmt", NetworkValue::Raw("OWE".into()))
+            .set("ieee80211w", 1)
+    }
+
+    /// Only connect using OWE, never to the open network of a transition-mode pair
+    pub fn owe_only(self) -> Self {
+        self.set("owe_only", true)
+    }
+
+    /// The Diffie-Hellman group to use for OWE: 19, 20 or 21
+    ///
+    /// By default all three are tried, strongest last.
+    pub fn owe_group(self, group: u16) -> Self {
+        self.set("owe_group", group)
+    }
+
+    /// An unencrypted network seen in a scan, using OWE if the access point offers it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{NetworkConfig, NetworkValue, ScanFlags};
+    /// let flags = ScanFlags::parse(&["ESS", "OWE-TRANS-OPEN"]);
+    /// let config = NetworkConfig::open_preferring_owe("cafe", &flags);
+    /// assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("OWE".into())));
+    /// ```
+    pub fn open_preferring_owe<S: Into<String>>(ssid: S, flags: &ScanFlags) -> Self {
+        if flags.supports_owe() {
+            NetworkConfig::owe(ssid)
+        } else {
+            NetworkConfig::open(ssid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn owe() {
+        let config = NetworkConfig::owe("cafe").owe_only().owe_group(20);
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("OWE".into())));
+        assert_eq!(config.get("owe_only"), Some(&NetworkValue::Raw("1".into())));
+        assert_eq!(config.get("owe_group"), Some(&NetworkValue::Raw("20".into())));
+        let config = NetworkConfig::open_preferring_owe("cafe", &ScanFlags::parse(&["ESS"]));
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("NONE".into())));
+        let config = NetworkConfig::open_preferring_owe("cafe", &ScanFlags::parse(&["WPA2-OWE-CCMP", "OWE-TRANS"]));
+        assert_eq!(config.get("key_mgmt"), Some(&NetworkValue::Raw("OWE".into())));
+    }
+}
@@ -0,0 +1,793 @@
+use error::Error;
+use macaddr::MacAddr;
+use network::{NetworkEntry, NetworkFlag, NetworkId};
+use p2p_service::ServiceTlv;
+use parse::{self, event, event_arg};
+use secret::REDACTED;
+use std::fmt;
+use std::str::FromStr;
+use super::Result;
+use wpactrl::{expect_ok, reply_error, WpaCtrl};
+
+/// How WPS provisioning is done when connecting to a P2P peer
+#[derive(Clone, Debug, PartialEq)]
+pub enum P2pWpsMethod {
+    /// Push-button on both devices
+    Pbc,
+    /// This device displays a PIN that is entered on the peer
+    ///
+    /// With `None`, wpasupplicant generates the PIN and `p2p_connect`
+    /// returns it.
+    Display(Option<String>),
+    /// The PIN displayed by the peer is entered on this device
+    Keypad(String),
+}
+
+/// Parameters of `WpaCtrl::p2p_connect`
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{P2pConnectParams, P2pWpsMethod};
+/// let params = P2pConnectParams::new("02:00:00:00:01:00".parse().unwrap(), P2pWpsMethod::Pbc).go_intent(15).persistent();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct P2pConnectParams {
+    peer: MacAddr,
+    method: P2pWpsMethod,
+    persistent: bool,
+    join: bool,
+    go_intent: Option<u8>,
+    freq: Option<u32>,
+}
+
+impl P2pConnectParams {
+    /// Connect to the peer with the given P2P device address
+    pub fn new(peer: MacAddr, method: P2pWpsMethod) -> Self {
+        P2pConnectParams {
+            peer,
+            method,
+            persistent: false,
+            join: false,
+            go_intent: None,
+            freq: None,
+        }
+    }
+
+    /// Store the resulting group so it can be re-invoked later
+    pub fn persistent(mut self) -> Self {
+        self.persistent = true;
+        self
+    }
+
+    /// Join a group the peer is already running, instead of negotiating
+    pub fn join(mut self) -> Self {
+        self.join = true;
+        self
+    }
+
+    /// How much this device wants to be group owner, from 0 to 15
+    pub fn go_intent(mut self, intent: u8) -> Self {
+        self.go_intent = Some(intent);
+        self
+    }
+
+    /// The frequency in MHz to use for the group
+    pub fn freq(mut self, freq: u32) -> Self {
+        self.freq = Some(freq);
+        self
+    }
+
+    /// Format the arguments as expected by `P2P_CONNECT`
+    fn to_args(&self) -> String {
+        let mut args = format!("{} ", self.peer);
+        match self.method {
+            P2pWpsMethod::Pbc => args.push_str("pbc"),
+            P2pWpsMethod::Display(None) => args.push_str("pin"),
+            P2pWpsMethod::Display(Some(ref pin)) => args.push_str(&format!("{} display", pin)),
+            P2pWpsMethod::Keypad(ref pin) => args.push_str(&format!("{} keypad", pin)),
+        }
+        if self.persistent {
+            args.push_str(" persistent");
+        }
+        if self.join {
+            args.push_str(" join");
+        }
+        if let Some(intent) = self.go_intent {
+            args.push_str(&format!(" go_intent={}", intent));
+        }
+        if let Some(freq) = self.freq {
+            args.push_str(&format!(" freq={}", freq));
+        }
+        args
+    }
+}
+
+/// The group `WpaCtrl::p2p_invite` invites a peer into
+#[derive(Clone, Debug, PartialEq)]
+pub enum P2pInviteGroup {
+    /// A persistent group, which the peer was a member of before
+    Persistent(NetworkId),
+    /// The group running on a network interface, eg `p2p-wlan0-0`
+    Active(String),
+}
+
+/// The role of this device in a P2P group
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum P2pRole {
+    /// Group owner, acting as the access point
+    GroupOwner,
+    /// Client of another group owner
+    Client,
+}
+
+impl P2pRole {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "GO" => Some(P2pRole::GroupOwner),
+            "client" => Some(P2pRole::Client),
+            _ => None,
+        }
+    }
+}
+
+/// A P2P (Wi-Fi Direct) event
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum P2pEvent {
+    /// A peer was discovered (`P2P-DEVICE-FOUND`)
+    DeviceFound {
+        /// The P2P device address of the peer
+        p2p_dev_addr: MacAddr,
+        /// The primary device type, eg `1-0050F204-1`
+        pri_dev_type: String,
+        /// The device name of the peer
+        name: String,
+        /// The supported WPS configuration methods
+        config_methods: Option<u16>,
+    },
+    /// A peer is no longer available (`P2P-DEVICE-LOST`)
+    DeviceLost {
+        /// The P2P device address of the peer
+        p2p_dev_addr: MacAddr,
+    },
+    /// Discovery ended (`P2P-FIND-STOPPED`)
+    FindStopped,
+    /// A peer wants to form a group with this device (`P2P-GO-NEG-REQUEST`)
+    GoNegRequest {
+        /// The P2P device address of the peer
+        p2p_dev_addr: MacAddr,
+        /// The device password id the peer wants to use; 4 means push-button
+        dev_passwd_id: Option<u16>,
+        /// How much the peer wants to be group owner
+        go_intent: Option<u8>,
+    },
+    /// Group owner negotiation succeeded (`P2P-GO-NEG-SUCCESS`)
+    GoNegSuccess {
+        /// The role this device will take
+        role: P2pRole,
+        /// The frequency of the group in MHz
+        freq: Option<u32>,
+        /// The P2P device address of the peer
+        p2p_dev_addr: MacAddr,
+    },
+    /// Group owner negotiation failed with a P2P status code (`P2P-GO-NEG-FAILURE`)
+    GoNegFailure(i32),
+    /// A group was started (`P2P-GROUP-STARTED`)
+    GroupStarted {
+        /// The network interface of the group
+        ifname: String,
+        /// The role of this device in the group
+        role: P2pRole,
+        /// The SSID of the group
+        ssid: String,
+        /// The frequency of the group in MHz
+        freq: Option<u32>,
+        /// The passphrase of the group, as reported to the group owner
+        passphrase: Option<String>,
+        /// The P2P device address of the group owner
+        go_dev_addr: MacAddr,
+    },
+    /// A group was removed (`P2P-GROUP-REMOVED`)
+    GroupRemoved {
+        /// The network interface of the group
+        ifname: String,
+        /// The role this device had in the group
+        role: P2pRole,
+        /// Why the group was removed, eg `REQUESTED` or `IDLE`
+        reason: Option<String>,
+    },
+    /// A peer answered a service discovery request (`P2P-SERV-DISC-RESP`)
+    ServDiscResp {
+        /// The P2P device address of the peer
+        p2p_dev_addr: MacAddr,
+        /// Incremented by the peer whenever its services change
+        update_indicator: u16,
+        /// The answers, one per query
+        tlvs: Vec<ServiceTlv>,
+    },
+    /// A peer invited this device into a group (`P2P-INVITATION-RECEIVED`)
+    InvitationReceived {
+        /// The address of the peer that sent the invitation
+        sa: MacAddr,
+        /// The P2P device address of the group owner
+        go_dev_addr: Option<MacAddr>,
+        /// The BSSID of the group, if it is running
+        bssid: Option<MacAddr>,
+        /// The persistent group this device is invited back into, if any
+        persistent: Option<NetworkId>,
+        /// The frequency of the group in MHz
+        freq: Option<u32>,
+    },
+    /// An invitation sent with `p2p_invite` was answered (`P2P-INVITATION-RESULT`)
+    InvitationResult {
+        /// The P2P status code; 0 if the peer accepted
+        status: i32,
+        /// The BSSID of the group
+        bssid: Option<MacAddr>,
+    },
+}
+
+/// Shows the passphrase of a started group as `[REDACTED]`
+impl fmt::Debug for P2pEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            P2pEvent::DeviceFound { ref p2p_dev_addr, ref pri_dev_type, ref name, ref config_methods } => {
+                f.debug_struct("DeviceFound")
+                    .field("p2p_dev_addr", p2p_dev_addr)
+                    .field("pri_dev_type", pri_dev_type)
+                    .field("name", name)
+                    .field("config_methods", config_methods)
+                    .finish()
+            }
+            P2pEvent::DeviceLost { ref p2p_dev_addr } => {
+                f.debug_struct("DeviceLost").field("p2p_dev_addr", p2p_dev_addr).finish()
+            }
+            P2pEvent::FindStopped => f.write_str("FindStopped"),
+            P2pEvent::GoNegRequest { ref p2p_dev_addr, ref dev_passwd_id, ref go_intent } => {
+                f.debug_struct("GoNegRequest")
+                    .field("p2p_dev_addr", p2p_dev_addr)
+                    .field("dev_passwd_id", dev_passwd_id)
+                    .field("go_intent", go_intent)
+                    .finish()
+            }
+            P2pEvent::GoNegSuccess { ref role, ref freq, ref p2p_dev_addr } => {
+                f.debug_struct("GoNegSuccess")
+                    .field("role", role)
+                    .field("freq", freq)
+                    .field("p2p_dev_addr", p2p_dev_addr)
+                    .finish()
+            }
+            P2pEvent::GoNegFailure(ref status) => f.debug_tuple("GoNegFailure").field(status).finish(),
+            P2pEvent::GroupStarted { ref ifname, ref role, ref ssid, ref freq, ref passphrase, ref go_dev_addr } => {
+                f.debug_struct("GroupStarted")
+                    .field("ifname", ifname)
+                    .field("role", role)
+                    .field("ssid", ssid)
+                    .field("freq", freq)
+                    .field("passphrase", &passphrase.as_ref().map(|_| REDACTED))
+                    .field("go_dev_addr", go_dev_addr)
+                    .finish()
+            }
+            P2pEvent::GroupRemoved { ref ifname, ref role, ref reason } => {
+                f.debug_struct("GroupRemoved")
+                    .field("ifname", ifname)
+                    .field("role", role)
+                    .field("reason", reason)
+                    .finish()
+            }
+            P2pEvent::ServDiscResp { ref p2p_dev_addr, ref update_indicator, ref tlvs } => {
+                f.debug_struct("ServDiscResp")
+                    .field("p2p_dev_addr", p2p_dev_addr)
+                    .field("update_indicator", update_indicator)
+                    .field("tlvs", tlvs)
+                    .finish()
+            }
+            P2pEvent::InvitationReceived { ref sa, ref go_dev_addr, ref bssid, ref persistent, ref freq } => {
+                f.debug_struct("InvitationReceived")
+                    .field("sa", sa)
+                    .field("go_dev_addr", go_dev_addr)
+                    .field("bssid", bssid)
+                    .field("persistent", persistent)
+                    .field("freq", freq)
+                    .finish()
+            }
+            P2pEvent::InvitationResult { ref status, ref bssid } => {
+                f.debug_struct("InvitationResult").field("status", status).field("bssid", bssid).finish()
+            }
+        }
+    }
+}
+
+/// Look up a numeric event argument
+fn num<T: FromStr>(body: &str, key: &str) -> Option<T> {
+    event_arg(body, key).and_then(|v| v.parse().ok())
+}
+
+impl P2pEvent {
+    /// Parse a P2P event, returning None for other messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::P2pEvent;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((_, msg)) = wpa.recv().unwrap() {
+    ///     if let Some(P2pEvent::DeviceFound { name, p2p_dev_addr, .. }) = P2pEvent::parse(&msg) {
+    ///         println!("Found {} ({})", name, p2p_dev_addr);
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        let arg = |key| event_arg(body, key).map(str::to_owned);
+        let addr = |key| event_arg(body, key).and_then(|v| v.parse::<MacAddr>().ok());
+        let mut words = body.split(' ');
+        match event {
+            "P2P-DEVICE-FOUND" => Some(P2pEvent::DeviceFound {
+                p2p_dev_addr: addr("p2p_dev_addr")?,
+                pri_dev_type: arg("pri_dev_type").unwrap_or_default(),
+                name: arg("name").unwrap_or_default(),
+                config_methods: event_arg(body, "config_methods")
+                    .and_then(|v| u16::from_str_radix(v.trim_start_matches("0x"), 16).ok()),
+            }),
+            "P2P-DEVICE-LOST" => Some(P2pEvent::DeviceLost { p2p_dev_addr: addr("p2p_dev_addr")? }),
+            "P2P-FIND-STOPPED" => Some(P2pEvent::FindStopped),
+            "P2P-GO-NEG-REQUEST" => Some(P2pEvent::GoNegRequest {
+                p2p_dev_addr: words.next()?.parse().ok()?,
+                dev_passwd_id: num(body, "dev_passwd_id"),
+                go_intent: num(body, "go_intent"),
+            }),
+            "P2P-GO-NEG-SUCCESS" => Some(P2pEvent::GoNegSuccess {
+                role: P2pRole::parse(event_arg(body, "role")?)?,
+                freq: num(body, "freq"),
+                p2p_dev_addr: addr("peer_dev")?,
+            }),
+            "P2P-GO-NEG-FAILURE" => Some(P2pEvent::GoNegFailure(num(body, "status")?)),
+            "P2P-GROUP-STARTED" => Some(P2pEvent::GroupStarted {
+                ifname: words.next().filter(|w| !w.is_empty())?.to_owned(),
+                role: P2pRole::parse(words.next()?)?,
+                ssid: arg("ssid")?,
+                freq: num(body, "freq"),
+                passphrase: arg("passphrase"),
+                go_dev_addr: addr("go_dev_addr")?,
+            }),
+            "P2P-GROUP-REMOVED" => Some(P2pEvent::GroupRemoved {
+                ifname: words.next().filter(|w| !w.is_empty())?.to_owned(),
+                role: P2pRole::parse(words.next()?)?,
+                reason: arg("reason"),
+            }),
+            "P2P-SERV-DISC-RESP" => Some(P2pEvent::ServDiscResp {
+                p2p_dev_addr: words.next()?.parse().ok()?,
+                update_indicator: words.next()?.parse().ok()?,
+                tlvs: ServiceTlv::parse_all(words.next().unwrap_or(""))?,
+            }),
+            "P2P-INVITATION-RECEIVED" => Some(P2pEvent::InvitationReceived {
+                sa: addr("sa")?,
+                go_dev_addr: addr("go_dev_addr"),
+                bssid: addr("bssid"),
+                persistent: num(body, "persistent").map(NetworkId),
+                freq: num(body, "freq"),
+            }),
+            "P2P-INVITATION-RESULT" => Some(P2pEvent::InvitationResult {
+                status: num(body, "status")?,
+                bssid: words.nth(1).and_then(|w| w.parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A discovered P2P peer, as shown by `P2P_PEER`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct P2pPeer {
+    /// The P2P device address of the peer
+    pub p2p_dev_addr: MacAddr,
+    /// The device name of the peer
+    pub device_name: String,
+    /// The primary device type, eg `1-0050F204-1`
+    pub pri_dev_type: String,
+    /// The supported WPS configuration methods
+    pub config_methods: Option<u16>,
+    /// The frequency in MHz on which the peer listens
+    pub listen_freq: Option<u32>,
+    /// The unparsed reply, for variables this type does not cover
+    pub raw: String,
+}
+
+impl P2pPeer {
+    /// Parse the reply to `P2P_PEER`
+    fn parse(reply: &str) -> Result<Self> {
+        let addr = reply.lines().next().and_then(|l| l.parse().ok())
+            .ok_or_else(|| Error::Parse(reply.to_owned()))?;
+        let value = |key| parse::key_value(reply, key).unwrap_or("").to_owned();
+        Ok(P2pPeer {
+            p2p_dev_addr: addr,
+            device_name: value("device_name"),
+            pri_dev_type: value("pri_dev_type"),
+            config_methods: parse::key_value(reply, "config_methods")
+                .and_then(|v| u16::from_str_radix(v.trim_start_matches("0x"), 16).ok()),
+            listen_freq: parse::key_value(reply, "listen_freq").and_then(|v| v.parse().ok()),
+            raw: reply.to_owned(),
+        })
+    }
+}
+
+/// Iterator over the discovered P2P peers
+///
+/// Created by `WpaCtrl::p2p_peers`.
+pub struct P2pPeers<'a> {
+    ctrl: &'a mut WpaCtrl,
+    last: Option<MacAddr>,
+    done: bool,
+}
+
+impl<'a> Iterator for P2pPeers<'a> {
+    type Item = Result<P2pPeer>;
+
+    fn next(&mut self) -> Option<Result<P2pPeer>> {
+        if self.done {
+            return None;
+        }
+        let cmd = match self.last {
+            Some(addr) => format!("P2P_PEER NEXT-{}", addr),
+            None => "P2P_PEER FIRST".to_owned(),
+        };
+        let reply = match self.ctrl.request(&cmd) {
+            Ok(reply) => reply,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        match reply_error(&reply) {
+            None if !reply.is_empty() => (),
+            // FAIL after the last peer, or with no peers
+            None | Some(Error::Failure) => {
+                self.done = true;
+                return None;
+            }
+            Some(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        let peer = P2pPeer::parse(&reply);
+        match peer {
+            Ok(ref peer) => self.last = Some(peer.p2p_dev_addr),
+            Err(_) => self.done = true,
+        }
+        Some(peer)
+    }
+}
+
+impl WpaCtrl {
+    /// Start discovering P2P peers
+    ///
+    /// Discovery runs for `timeout` seconds, or until `p2p_stop_find` if
+    /// None. Peers found are reported as `P2pEvent::DeviceFound` events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_find(Some(30)).unwrap();
+    /// ```
+    pub fn p2p_find(&mut self, timeout: Option<u32>) -> Result<()> {
+        let cmd = match timeout {
+            Some(timeout) => format!("P2P_FIND {}", timeout),
+            None => "P2P_FIND".to_owned(),
+        };
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Stop discovering P2P peers
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_stop_find().unwrap();
+    /// ```
+    pub fn p2p_stop_find(&mut self) -> Result<()> {
+        expect_ok(&self.request("P2P_STOP_FIND")?)
+    }
+
+    /// Iterate over the P2P peers discovered so far
+    ///
+    /// Peers are fetched one at a time with `P2P_PEER FIRST` /
+    /// `P2P_PEER NEXT-<addr>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for peer in wpa.p2p_peers() {
+    ///     let peer = peer.unwrap();
+    ///     println!("{} {}", peer.p2p_dev_addr, peer.device_name);
+    /// }
+    /// ```
+    pub fn p2p_peers(&mut self) -> P2pPeers<'_> {
+        P2pPeers { ctrl: self, last: None, done: false }
+    }
+
+    /// Start connecting to a P2P peer
+    ///
+    /// Returns the generated PIN when using `P2pWpsMethod::Display(None)`.
+    /// The outcome is reported with `P2pEvent::GoNegSuccess` /
+    /// `GoNegFailure` and `GroupStarted` events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{P2pConnectParams, P2pWpsMethod};
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let params = P2pConnectParams::new("02:00:00:00:01:00".parse().unwrap(), P2pWpsMethod::Display(None));
+    /// println!("Enter PIN {:?} on the peer", wpa.p2p_connect(params).unwrap());
+    /// ```
+    pub fn p2p_connect(&mut self, params: P2pConnectParams) -> Result<Option<String>> {
+        let reply = self.request(&format!("P2P_CONNECT {}", params.to_args()))?;
+        if let Some(e) = reply_error(&reply) {
+            return Err(e);
+        }
+        match reply.trim_end_matches('\n') {
+            "OK" => Ok(None),
+            pin if params.method == P2pWpsMethod::Display(None) => Ok(Some(pin.to_owned())),
+            _ => Err(Error::Failure),
+        }
+    }
+
+    /// Start a P2P group with this device as group owner
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_group_add(false, Some(2437)).unwrap();
+    /// ```
+    pub fn p2p_group_add(&mut self, persistent: bool, freq: Option<u32>) -> Result<()> {
+        let mut cmd = "P2P_GROUP_ADD".to_owned();
+        if persistent {
+            cmd.push_str(" persistent");
+        }
+        if let Some(freq) = freq {
+            cmd.push_str(&format!(" freq={}", freq));
+        }
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Leave or stop the P2P group on the given network interface
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_group_remove("p2p-wlan0-0").unwrap();
+    /// ```
+    pub fn p2p_group_remove(&mut self, ifname: &str) -> Result<()> {
+        expect_ok(&self.request(&format!("P2P_GROUP_REMOVE {}", ifname))?)
+    }
+
+    /// Invite a peer into a group
+    ///
+    /// The answer of the peer is reported as a `P2pEvent::InvitationResult`
+    /// event. Inviting into a persistent group starts the group again once
+    /// the peer accepts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::{NetworkId, P2pInviteGroup};
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let peer = "02:00:00:00:01:00".parse().unwrap();
+    /// wpa.p2p_invite(P2pInviteGroup::Persistent(NetworkId(0)), peer).unwrap();
+    /// ```
+    pub fn p2p_invite(&mut self, group: P2pInviteGroup, peer: MacAddr) -> Result<()> {
+        let cmd = match group {
+            P2pInviteGroup::Persistent(id) => format!("P2P_INVITE persistent={} peer={}", id.0, peer),
+            P2pInviteGroup::Active(ifname) => format!("P2P_INVITE group={} peer={}", ifname, peer),
+        };
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// List the persistent P2P groups remembered by wpasupplicant
+    ///
+    /// These are the networks of `LIST_NETWORKS` flagged `[P2P-PERSISTENT]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for group in wpa.p2p_persistent_groups().unwrap() {
+    ///     println!("{:?}: {}", group.id, group.ssid);
+    /// }
+    /// ```
+    pub fn p2p_persistent_groups(&mut self) -> Result<Vec<NetworkEntry>> {
+        let mut networks = self.list_networks()?;
+        networks.retain(|network| network.flags.contains(&NetworkFlag::P2pPersistent));
+        Ok(networks)
+    }
+
+    /// Start a persistent P2P group again, with this device as group owner
+    ///
+    /// Peers that were members before can then reconnect without
+    /// provisioning.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkId;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_group_invoke(NetworkId(0), None).unwrap();
+    /// ```
+    pub fn p2p_group_invoke(&mut self, id: NetworkId, freq: Option<u32>) -> Result<()> {
+        let mut cmd = format!("P2P_GROUP_ADD persistent={}", id.0);
+        if let Some(freq) = freq {
+            cmd.push_str(&format!(" freq={}", freq));
+        }
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Forget a persistent P2P group
+    ///
+    /// Peers then need to be provisioned again to join a new group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkId;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_persistent_group_remove(NetworkId(0)).unwrap();
+    /// ```
+    pub fn p2p_persistent_group_remove(&mut self, id: NetworkId) -> Result<()> {
+        self.remove_network(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn persistent_groups() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_p2p_persistent_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("LIST_NETWORKS", concat!(
+            "network id / ssid / bssid / flags\n",
+            "0\thome\tany\t[CURRENT]\n",
+            "1\tDIRECT-xy\t02:00:00:00:02:00\t[DISABLED][P2P-PERSISTENT]\n",
+        ));
+        mock.reply("P2P_GROUP_ADD persistent=1 freq=2412", "OK\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        let groups = wpa.p2p_persistent_groups().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].id, NetworkId(1));
+        assert_eq!(groups[0].ssid, "DIRECT-xy");
+        wpa.p2p_group_invoke(NetworkId(1), Some(2412)).unwrap();
+        mock.reply("P2P_INVITE persistent=1 peer=02:00:00:00:02:00", "OK\n");
+        wpa.p2p_invite(P2pInviteGroup::Persistent(NetworkId(1)), "02:00:00:00:02:00".parse().unwrap()).unwrap();
+        assert_eq!(mock.commands(), vec![
+            "LIST_NETWORKS", "P2P_GROUP_ADD persistent=1 freq=2412", "P2P_INVITE persistent=1 peer=02:00:00:00:02:00",
+        ]);
+    }
+
+    #[test]
+    fn connect_params() {
+        let peer: MacAddr = "02:00:00:00:01:00".parse().unwrap();
+        assert_eq!(P2pConnectParams::new(peer, P2pWpsMethod::Pbc).to_args(), "02:00:00:00:01:00 pbc");
+        assert_eq!(P2pConnectParams::new(peer, P2pWpsMethod::Display(None)).go_intent(15).to_args(),
+                   "02:00:00:00:01:00 pin go_intent=15");
+        assert_eq!(P2pConnectParams::new(peer, P2pWpsMethod::Display(Some("12345670".into()))).to_args(),
+                   "02:00:00:00:01:00 12345670 display");
+        assert_eq!(P2pConnectParams::new(peer, P2pWpsMethod::Keypad("12345670".into())).persistent().join().freq(2412)
+                       .to_args(),
+                   "02:00:00:00:01:00 12345670 keypad persistent join freq=2412");
+    }
+
+    #[test]
+    fn peer() {
+        let peer = P2pPeer::parse(concat!(
+            "02:00:00:00:01:00\n",
+            "pri_dev_type=1-0050F204-1\n",
+            "device_name=Device A\n",
+            "manufacturer=\n",
+            "config_methods=0x188\n",
+            "dev_capab=0x27\n",
+            "group_capab=0x0\n",
+            "level=-40\n",
+            "listen_freq=2412\n",
+        )).unwrap();
+        assert_eq!(peer.p2p_dev_addr.to_string(), "02:00:00:00:01:00");
+        assert_eq!(peer.device_name, "Device A");
+        assert_eq!(peer.pri_dev_type, "1-0050F204-1");
+        assert_eq!(peer.config_methods, Some(0x188));
+        assert_eq!(peer.listen_freq, Some(2412));
+        assert!(P2pPeer::parse("device_name=Device A\n").is_err());
+    }
+
+    #[test]
+    fn peers_and_connect() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_p2p_peers_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("P2P_PEER FIRST", "02:00:00:00:01:00\ndevice_name=Device A\n");
+        mock.reply("P2P_PEER NEXT-02:00:00:00:01:00", "FAIL\n");
+        mock.reply("P2P_CONNECT 02:00:00:00:01:00 pin", "FAIL\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        let peers: Vec<_> = wpa.p2p_peers().collect::<Result<_>>().unwrap();
+        assert_eq!(peers.len(), 1);
+        let params = P2pConnectParams::new("02:00:00:00:01:00".parse().unwrap(), P2pWpsMethod::Display(None));
+        assert!(matches!(wpa.p2p_connect(params.clone()), Err(Error::Failure)));
+        mock.reply("P2P_CONNECT 02:00:00:00:01:00 pin", "12345670\n");
+        assert_eq!(wpa.p2p_connect(params).unwrap(), Some("12345670".to_owned()));
+        mock.reply("P2P_PEER FIRST", "");
+        assert!(matches!(wpa.p2p_peers().next(), Some(Err(Error::UnknownCommand))));
+    }
+
+    #[test]
+    fn events() {
+        assert_eq!(P2pEvent::parse("<3>P2P-DEVICE-FOUND 02:00:00:00:01:00 p2p_dev_addr=02:00:00:00:01:00 \
+                                    pri_dev_type=1-0050F204-1 name='Device A' config_methods=0x188 dev_capab=0x27 \
+                                    group_capab=0x0 new=1"),
+                   Some(P2pEvent::DeviceFound {
+                       p2p_dev_addr: "02:00:00:00:01:00".parse().unwrap(),
+                       pri_dev_type: "1-0050F204-1".into(),
+                       name: "Device A".into(),
+                       config_methods: Some(0x188),
+                   }));
+        assert_eq!(P2pEvent::parse("<3>P2P-GO-NEG-REQUEST 02:00:00:00:01:00 dev_passwd_id=4 go_intent=7"),
+                   Some(P2pEvent::GoNegRequest {
+                       p2p_dev_addr: "02:00:00:00:01:00".parse().unwrap(),
+                       dev_passwd_id: Some(4),
+                       go_intent: Some(7),
+                   }));
+        assert_eq!(P2pEvent::parse("<3>P2P-GO-NEG-SUCCESS role=client freq=2412 ht40=0 peer_dev=02:00:00:00:01:00 \
+                                    peer_iface=02:00:00:00:01:00 wps_method=PBC"),
+                   Some(P2pEvent::GoNegSuccess {
+                       role: P2pRole::Client,
+                       freq: Some(2412),
+                       p2p_dev_addr: "02:00:00:00:01:00".parse().unwrap(),
+                   }));
+        assert_eq!(P2pEvent::parse("<3>P2P-GO-NEG-FAILURE status=1"), Some(P2pEvent::GoNegFailure(1)));
+        assert_eq!(P2pEvent::parse("<3>P2P-GROUP-STARTED p2p-wlan0-0 GO ssid=\"DIRECT-ab my group\" freq=2412 \
+                                    passphrase=\"secret12\" go_dev_addr=02:00:00:00:00:00"),
+                   Some(P2pEvent::GroupStarted {
+                       ifname: "p2p-wlan0-0".into(),
+                       role: P2pRole::GroupOwner,
+                       ssid: "DIRECT-ab my group".into(),
+                       freq: Some(2412),
+                       passphrase: Some("secret12".into()),
+                       go_dev_addr: "02:00:00:00:00:00".parse().unwrap(),
+                   }));
+        let started = P2pEvent::parse("<3>P2P-GROUP-STARTED p2p-wlan0-0 GO ssid=\"x\" passphrase=\"secret12\" \
+                                       go_dev_addr=02:00:00:00:00:00").unwrap();
+        assert!(!format!("{:?}", started).contains("secret12"));
+        assert_eq!(P2pEvent::parse("<3>P2P-GROUP-REMOVED p2p-wlan0-0 client reason=REQUESTED"),
+                   Some(P2pEvent::GroupRemoved {
+                       ifname: "p2p-wlan0-0".into(),
+                       role: P2pRole::Client,
+                       reason: Some("REQUESTED".into()),
+                   }));
+        assert_eq!(P2pEvent::parse("<3>P2P-FIND-STOPPED "), Some(P2pEvent::FindStopped));
+        match P2pEvent::parse("<3>P2P-SERV-DISC-RESP 02:00:00:00:01:00 3 0600020101414243") {
+            Some(P2pEvent::ServDiscResp { update_indicator: 3, tlvs, .. }) => assert_eq!(tlvs[0].data, b"ABC"),
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert_eq!(P2pEvent::parse("<3>P2P-INVITATION-RECEIVED sa=02:00:00:00:01:00 persistent=2 freq=2437"),
+                   Some(P2pEvent::InvitationReceived {
+                       sa: "02:00:00:00:01:00".parse().unwrap(),
+                       go_dev_addr: None,
+                       bssid: None,
+                       persistent: Some(NetworkId(2)),
+                       freq: Some(2437),
+                   }));
+        assert_eq!(P2pEvent::parse("<3>P2P-INVITATION-RESULT status=0 02:00:00:00:02:00"),
+                   Some(P2pEvent::InvitationResult { status: 0, bssid: "02:00:00:00:02:00".parse().ok() }));
+        assert_eq!(P2pEvent::parse("<3>P2P-INVITATION-RESULT status=1 "),
+                   Some(P2pEvent::InvitationResult { status: 1, bssid: None }));
+        assert_eq!(P2pEvent::parse("<3>P2P-GROUP-STARTED"), None);
+        assert_eq!(P2pEvent::parse("<3>CTRL-EVENT-SCAN-STARTED "), None);
+    }
+}
@@ -0,0 +1,344 @@
+use error::Error;
+use macaddr::MacAddr;
+use parse::{hex_decode, hex_encode};
+use super::Result;
+use wpactrl::{expect_ok, reply_error, WpaCtrl};
+
+/// A service discovery protocol, as used in P2P service TLVs
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ServiceProtocol {
+    /// All protocols (`0`)
+    All,
+    /// Bonjour, ie DNS-SD (`1`)
+    Bonjour,
+    /// UPnP (`2`)
+    Upnp,
+    /// WS-Discovery (`3`)
+    WsDiscovery,
+    /// A protocol not known to this library
+    Other(u8),
+}
+
+impl ServiceProtocol {
+    /// The service protocol type of the TLV
+    pub fn code(&self) -> u8 {
+        match *self {
+            ServiceProtocol::All => 0,
+            ServiceProtocol::Bonjour => 1,
+            ServiceProtocol::Upnp => 2,
+            ServiceProtocol::WsDiscovery => 3,
+            ServiceProtocol::Other(code) => code,
+        }
+    }
+
+    /// The protocol of a service protocol type
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => ServiceProtocol::All,
+            1 => ServiceProtocol::Bonjour,
+            2 => ServiceProtocol::Upnp,
+            3 => ServiceProtocol::WsDiscovery,
+            code => ServiceProtocol::Other(code),
+        }
+    }
+}
+
+/// Encode a DNS name, eg `_ipp._tcp.local`, in the uncompressed wire format
+fn dns_name(name: &str) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for label in name.trim_end_matches('.').split('.').filter(|label| !label.is_empty()) {
+        encoded.push(label.len() as u8);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    encoded
+}
+
+/// The query for Bonjour records of type `rr_type` for `name`
+///
+/// This is the DNS name, the record type and the Bonjour version 1.
+fn bonjour_query(name: &str, rr_type: u16) -> Vec<u8> {
+    let mut query = dns_name(name);
+    query.extend_from_slice(&rr_type.to_be_bytes());
+    query.push(1);
+    query
+}
+
+/// DNS record type of PTR records
+const RR_TYPE_PTR: u16 = 12;
+/// DNS record type of TXT records
+const RR_TYPE_TXT: u16 = 16;
+
+/// What to look for with `WpaCtrl::p2p_serv_disc_req`
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServiceQuery {
+    /// All services, of all protocols
+    All,
+    /// All services of a protocol
+    AllOf(ServiceProtocol),
+    /// Bonjour records of a DNS type for a name, eg PTR (12) records for `_ipp._tcp.local`
+    Bonjour {
+        /// The DNS name
+        name: String,
+        /// The DNS record type
+        rr_type: u16,
+    },
+    /// UPnP services matching a search target, eg `ssdp:all`
+    Upnp {
+        /// The UPnP version, eg `0x10` for 1.0
+        version: u8,
+        /// The search target
+        search_target: String,
+    },
+}
+
+impl ServiceQuery {
+    /// Bonjour services of a type, eg `_ipp._tcp.local`
+    pub fn bonjour(service: &str) -> Self {
+        ServiceQuery::Bonjour { name: service.to_owned(), rr_type: RR_TYPE_PTR }
+    }
+
+    /// Format the arguments of `P2P_SERV_DISC_REQ` after the peer
+    fn to_args(&self) -> String {
+        let tlv = |protocol: ServiceProtocol, query: &[u8]| {
+            let mut tlv = ((query.len() + 2) as u16).to_le_bytes().to_vec();
+            tlv.push(protocol.code());
+            tlv.push(1);
+            tlv.extend_from_slice(query);
+            hex_encode(&tlv)
+        };
+        match *self {
+            ServiceQuery::All => tlv(ServiceProtocol::All, &[]),
+            ServiceQuery::AllOf(protocol) => tlv(protocol, &[]),
+            ServiceQuery::Bonjour { ref name, rr_type } => tlv(ServiceProtocol::Bonjour, &bonjour_query(name, rr_type)),
+            ServiceQuery::Upnp { version, ref search_target } => format!("upnp {:02x} {}", version, search_target),
+        }
+    }
+}
+
+/// A service advertised with `WpaCtrl::p2p_service_add`
+#[derive(Clone, Debug, PartialEq)]
+pub enum P2pService {
+    /// A Bonjour record, as the query it answers and its RDATA
+    Bonjour {
+        /// The query, as built for `ServiceQuery::Bonjour`
+        query: Vec<u8>,
+        /// The RDATA of the record
+        response: Vec<u8>,
+    },
+    /// A UPnP service
+    Upnp {
+        /// The UPnP version, eg `0x10` for 1.0
+        version: u8,
+        /// The service, eg `uuid:...::urn:schemas-upnp-org:device:InternetGatewayDevice:1`
+        service: String,
+    },
+}
+
+impl P2pService {
+    /// A Bonjour PTR record pointing from a service type to an instance
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::P2pService;
+    /// let printer = P2pService::bonjour_ptr("_ipp._tcp.local", "MyPrinter");
+    /// ```
+    pub fn bonjour_ptr(service: &str, instance: &str) -> Self {
+        P2pService::Bonjour {
+            query: bonjour_query(service, RR_TYPE_PTR),
+            response: dns_name(&format!("{}.{}", instance, service)),
+        }
+    }
+
+    /// A Bonjour TXT record of a service instance, eg `MyPrinter._ipp._tcp.local`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::P2pService;
+    /// let txt = P2pService::bonjour_txt("MyPrinter._ipp._tcp.local", &["txtvers=1", "pdl=application/pdf"]);
+    /// ```
+    pub fn bonjour_txt(instance: &str, entries: &[&str]) -> Self {
+        let mut response = Vec::new();
+        for entry in entries {
+            response.push(entry.len() as u8);
+            response.extend_from_slice(entry.as_bytes());
+        }
+        P2pService::Bonjour { query: bonjour_query(instance, RR_TYPE_TXT), response }
+    }
+
+    /// A UPnP service, with version 1.0
+    pub fn upnp(service: &str) -> Self {
+        P2pService::Upnp { version: 0x10, service: service.to_owned() }
+    }
+}
+
+/// A TLV of a P2P service discovery response
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ServiceTlv {
+    /// The protocol of the service
+    pub protocol: ServiceProtocol,
+    /// The transaction id of the query answered
+    pub transaction_id: u8,
+    /// The status code; 0 for success, 1 for an unsupported protocol, 2 if nothing was found
+    pub status: u8,
+    /// The response data, eg Bonjour RDATA or UPnP service names
+    pub data: Vec<u8>,
+}
+
+impl ServiceTlv {
+    /// Parse the hex encoded TLVs of a `P2P-SERV-DISC-RESP` event
+    pub(crate) fn parse_all(hex: &str) -> Option<Vec<Self>> {
+        let bytes = hex_decode(hex)?;
+        let mut rest = &bytes[..];
+        let mut tlvs = Vec::new();
+        while !rest.is_empty() {
+            if rest.len() < 5 {
+                return None;
+            }
+            let len = u16::from_le_bytes([rest[0], rest[1]]) as usize;
+            if len < 3 || rest.len() < 2 + len {
+                return None;
+            }
+            tlvs.push(ServiceTlv {
+                protocol: ServiceProtocol::from_code(rest[2]),
+                transaction_id: rest[3],
+                status: rest[4],
+                data: rest[5..2 + len].to_vec(),
+            });
+            rest = &rest[2 + len..];
+        }
+        Some(tlvs)
+    }
+}
+
+impl WpaCtrl {
+    /// Advertise a service to P2P peers
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::P2pService;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_service_add(&P2pService::bonjour_ptr("_ipp._tcp.local", "MyPrinter")).unwrap();
+    /// ```
+    pub fn p2p_service_add(&mut self, service: &P2pService) -> Result<()> {
+        let cmd = match *service {
+            P2pService::Bonjour { ref query, ref response } =>
+                format!("P2P_SERVICE_ADD bonjour {} {}", hex_encode(query), hex_encode(response)),
+            P2pService::Upnp { version, ref service } => format!("P2P_SERVICE_ADD upnp {:02x} {}", version, service),
+        };
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Stop advertising a service
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::P2pService;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.p2p_service_del(&P2pService::bonjour_ptr("_ipp._tcp.local", "MyPrinter")).unwrap();
+    /// ```
+    pub fn p2p_service_del(&mut self, service: &P2pService) -> Result<()> {
+        let cmd = match *service {
+            P2pService::Bonjour { ref query, .. } => format!("P2P_SERVICE_DEL bonjour {}", hex_encode(query)),
+            P2pService::Upnp { version, ref service } => format!("P2P_SERVICE_DEL upnp {:02x} {}", version, service),
+        };
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Ask a peer, or all peers found during `p2p_find` with None, for services
+    ///
+    /// Returns the id of the request, for `p2p_serv_disc_cancel_req`. The
+    /// answers are reported as `P2pEvent::ServDiscResp` events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::ServiceQuery;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.p2p_serv_disc_req(None, &ServiceQuery::bonjour("_ipp._tcp.local")).unwrap();
+    /// wpa.p2p_find(Some(30)).unwrap();
+    /// ```
+    pub fn p2p_serv_disc_req(&mut self, peer: Option<MacAddr>, query: &ServiceQuery) -> Result<u64> {
+        let peer = peer.map_or_else(|| "00:00:00:00:00:00".to_owned(), |peer| peer.to_string());
+        let reply = self.request(&format!("P2P_SERV_DISC_REQ {} {}", peer, query.to_args()))?;
+        if let Some(e) = reply_error(&reply) {
+            return Err(e);
+        }
+        u64::from_str_radix(reply.trim_end(), 16).map_err(|_| Error::Parse(reply))
+    }
+
+    /// Cancel a request made with `p2p_serv_disc_req`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::ServiceQuery;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.p2p_serv_disc_req(None, &ServiceQuery::All).unwrap();
+    /// wpa.p2p_serv_disc_cancel_req(id).unwrap();
+    /// ```
+    pub fn p2p_serv_disc_cancel_req(&mut self, id: u64) -> Result<()> {
+        expect_ok(&self.request(&format!("P2P_SERV_DISC_CANCEL_REQ {:x}", id))?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn queries() {
+        assert_eq!(ServiceQuery::All.to_args(), "02000001");
+        assert_eq!(ServiceQuery::AllOf(ServiceProtocol::Upnp).to_args(), "02000201");
+        assert_eq!(ServiceQuery::bonjour("_ipp._tcp.local").to_args(),
+                   "16000101045f697070045f746370056c6f63616c00000c01");
+        let upnp = ServiceQuery::Upnp { version: 0x10, search_target: "ssdp:all".into() };
+        assert_eq!(upnp.to_args(), "upnp 10 ssdp:all");
+    }
+
+    #[test]
+    fn services() {
+        assert_eq!(P2pService::bonjour_ptr("_ipp._tcp.local.", "MyPrinter"), P2pService::Bonjour {
+            query: hex_decode("045f697070045f746370056c6f63616c00000c01").unwrap(),
+            response: hex_decode("094d795072696e746572045f697070045f746370056c6f63616c00").unwrap(),
+        });
+        match P2pService::bonjour_txt("MyPrinter._ipp._tcp.local", &["txtvers=1"]) {
+            P2pService::Bonjour { query, response } => {
+                assert_eq!(&query[query.len() - 3..], &[0, 16, 1]);
+                assert_eq!(response, b"\x09txtvers=1");
+            }
+            other => panic!("unexpected service {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tlvs() {
+        let tlvs = ServiceTlv::parse_all("0300010100 0600020102414243".replace(' ', "").as_str()).unwrap();
+        assert_eq!(tlvs, vec![
+            ServiceTlv { protocol: ServiceProtocol::Bonjour, transaction_id: 1, status: 0, data: vec![] },
+            ServiceTlv { protocol: ServiceProtocol::Upnp, transaction_id: 1, status: 2, data: b"ABC".to_vec() },
+        ]);
+        assert!(ServiceTlv::parse_all("0900010100").is_none());
+        assert_eq!(ServiceTlv::parse_all("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn serv_disc_req() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_p2p_service_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("P2P_SERV_DISC_REQ 00:00:00:00:00:00 02000001", "1f77628\n");
+        mock.reply("P2P_SERV_DISC_CANCEL_REQ 1f77628", "OK\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        let id = wpa.p2p_serv_disc_req(None, &ServiceQuery::All).unwrap();
+        assert_eq!(id, 0x1f7_7628);
+        wpa.p2p_serv_disc_cancel_req(id).unwrap();
+        assert!(wpa.p2p_serv_disc_req(None, &ServiceQuery::AllOf(ServiceProtocol::Bonjour)).is_err());
+    }
+}
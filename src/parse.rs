@@ -0,0 +1,310 @@
+//! Primitives for splitting control interface replies and messages
+//!
+//! These are the building blocks used by the typed API of this crate, and
+//! can be used to write wrappers for commands the crate does not cover yet.
+//!
+//! # Examples
+//!
+//! ```
+//! use wpactrl::parse;
+//! let reply = "bssid / frequency / signal level / flags / ssid\n\
+//!              00:11:22:33:44:55\t2412\t-50\t[ESS]\thome\n";
+//! assert_eq!(parse::header(reply), vec!["bssid", "frequency", "signal level", "flags", "ssid"]);
+//! for row in parse::rows(reply) {
+//!     assert_eq!(row[4], "home");
+//! }
+//! ```
+
+use macaddr::MacAddr;
+use network::NetworkId;
+
+/// Strip the `<N>` priority prefix from a control interface message
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(wpactrl::parse::strip_level("<3>CTRL-EVENT-SCAN-STARTED "), "CTRL-EVENT-SCAN-STARTED ");
+/// ```
+pub fn strip_level(msg: &str) -> &str {
+    if msg.starts_with('<') {
+        if let Some(end) = msg.find('>') {
+            return &msg[end + 1..];
+        }
+    }
+    msg
+}
+
+/// Split a control interface message into its event name and body
+///
+/// The `<N>` priority prefix and surrounding whitespace are removed; the
+/// body is empty for events without arguments.
+///
+/// # Examples
+///
+/// ```
+/// let (event, body) = wpactrl::parse::event("<3>CTRL-EVENT-DISCONNECTED bssid=00:11:22:33:44:55 reason=3\n");
+/// assert_eq!(event, "CTRL-EVENT-DISCONNECTED");
+/// assert_eq!(body, "bssid=00:11:22:33:44:55 reason=3");
+/// ```
+pub fn event(msg: &str) -> (&str, &str) {
+    let msg = strip_level(msg).trim();
+    msg.split_once(' ').unwrap_or((msg, ""))
+}
+
+/// The fields of a `CTRL-EVENT-CONNECTED` event body
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Connected<'a> {
+    /// The BSSID of the access point
+    pub bssid: Option<MacAddr>,
+    /// The id of the network
+    pub id: Option<NetworkId>,
+    /// The `id_str` of the network, None if empty
+    pub id_str: Option<&'a str>,
+}
+
+/// Parse a `CTRL-EVENT-CONNECTED` event body, `- Connection to <bssid> completed [id=<id> id_str=<id_str>]`
+pub(crate) fn connected(body: &str) -> Connected<'_> {
+    let args = body.split_once('[').map_or("", |(_, args)| args.trim_end_matches(']'));
+    Connected {
+        bssid: body.split(' ').skip_while(|&w| w != "to").nth(1).and_then(|bssid| bssid.parse().ok()),
+        id: event_arg(args, "id").and_then(|id| id.parse().ok()).map(NetworkId),
+        id_str: event_arg(args, "id_str").filter(|id_str| !id_str.is_empty()),
+    }
+}
+
+/// The column names of a tabular reply (eg `LIST_NETWORKS`)
+///
+/// Columns in the header line are separated by ` / `.
+///
+/// # Examples
+///
+/// ```
+/// let header = wpactrl::parse::header("network id / ssid / bssid / flags\n");
+/// assert_eq!(header, vec!["network id", "ssid", "bssid", "flags"]);
+/// ```
+pub fn header(s: &str) -> Vec<&str> {
+    s.lines().next().map(|l| l.split(" / ").map(str::trim).collect()).unwrap_or_default()
+}
+
+/// Split a tabular reply (eg `LIST_NETWORKS`) into rows of tab-separated fields
+///
+/// The first line is a header naming the columns and is skipped.
+///
+/// # Examples
+///
+/// ```
+/// let rows: Vec<_> = wpactrl::parse::rows("network id / ssid / bssid / flags\n0\thome\tany\t\n").collect();
+/// assert_eq!(rows, vec![vec!["0", "home", "any", ""]]);
+/// ```
+pub fn rows(s: &str) -> impl Iterator<Item = Vec<&str>> {
+    s.lines().skip(1).filter(|l| !l.is_empty()).map(|l| l.split('\t').collect())
+}
+
+/// Split a block of `key=value` lines (eg `STATUS`) into pairs
+///
+/// Each line is split at its first `=`; lines without one are skipped.
+///
+/// # Examples
+///
+/// ```
+/// let pairs = wpactrl::parse::key_values("wpa_state=COMPLETED\nssid=a=b\n");
+/// assert_eq!(pairs, vec![("wpa_state", "COMPLETED"), ("ssid", "a=b")]);
+/// ```
+pub fn key_values(s: &str) -> Vec<(&str, &str)> {
+    s.lines().filter_map(|l| {
+        l.find('=').map(|eq| (&l[..eq], &l[eq + 1..]))
+    }).collect()
+}
+
+/// Look up a single value of a block of `key=value` lines by key
+///
+/// # Examples
+///
+/// ```
+/// let status = "bssid=00:11:22:33:44:55\nwpa_state=COMPLETED\n";
+/// assert_eq!(wpactrl::parse::key_value(status, "wpa_state"), Some("COMPLETED"));
+/// ```
+pub fn key_value<'a>(s: &'a str, key: &str) -> Option<&'a str> {
+    key_values(s).into_iter().find(|&(k, _)| k == key).map(|(_, v)| v)
+}
+
+/// Split a `[FOO][BAR]` style flag string into its individual flags
+///
+/// # Examples
+///
+/// ```
+/// let flags: Vec<_> = wpactrl::parse::flags("[WPA2-PSK-CCMP][ESS]").collect();
+/// assert_eq!(flags, vec!["WPA2-PSK-CCMP", "ESS"]);
+/// ```
+pub fn flags(s: &str) -> impl Iterator<Item = &str> {
+    s.split(['[', ']']).filter(|f| !f.is_empty())
+}
+
+/// Split an event body into its space-separated `key=value` arguments
+///
+/// Values may be quoted to include spaces (eg `ssid="my network"`, or
+/// `name='my device'` as used by P2P events), in which case the quotes are
+/// not included in the returned value.
+/// Words without an `=` are skipped.
+///
+/// # Examples
+///
+/// ```
+/// let args = wpactrl::parse::event_args("id=0 ssid=\"my network\" auth_failures=1");
+/// assert_eq!(args, vec![("id", "0"), ("ssid", "my network"), ("auth_failures", "1")]);
+/// ```
+pub fn event_args(s: &str) -> Vec<(&str, &str)> {
+    let mut args = Vec::new();
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let word_end = rest.find(' ').unwrap_or(rest.len());
+        let eq = match rest[..word_end].find('=') {
+            Some(eq) => eq,
+            None => {
+                rest = rest[word_end..].trim_start();
+                continue;
+            }
+        };
+        let key = &rest[..eq];
+        let after = &rest[eq + 1..];
+        let (value, next) = if let Some(quote) = after.chars().next().filter(|&c| c == '"' || c == '\'') {
+            let quoted = &after[1..];
+            match quoted.find(quote) {
+                Some(close) => (&quoted[..close], &quoted[close + 1..]),
+                None => (quoted, ""),
+            }
+        } else {
+            let end = after.find(' ').unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        args.push((key, value));
+        rest = next.trim_start();
+    }
+    args
+}
+
+/// Look up a single argument of an event body by key
+///
+/// # Examples
+///
+/// ```
+/// let body = "bssid=00:11:22:33:44:55 reason=3 locally_generated=1";
+/// assert_eq!(wpactrl::parse::event_arg(body, "reason"), Some("3"));
+/// ```
+pub fn event_arg<'a>(s: &'a str, key: &str) -> Option<&'a str> {
+    event_args(s).into_iter().find(|&(k, _)| k == key).map(|(_, v)| v)
+}
+
+/// Hex-encode binary data, as used for payloads and hex SSIDs
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(wpactrl::parse::hex_encode(b"home"), "686f6d65");
+/// ```
+pub fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode a hex dump, as returned for NFC and similar payloads
+///
+/// Returns None if `s` is not valid hex. Surrounding whitespace, such as
+/// the newline ending a reply, is ignored.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(wpactrl::parse::hex_decode("686f6d65\n"), Some(b"home".to_vec()));
+/// assert_eq!(wpactrl::parse::hex_decode("FAIL\n"), None);
+/// ```
+pub fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn strip_level() {
+        assert_eq!(super::strip_level("<3>CTRL-EVENT-SCAN-STARTED "), "CTRL-EVENT-SCAN-STARTED ");
+        assert_eq!(super::strip_level("OK\n"), "OK\n");
+    }
+
+    #[test]
+    fn event() {
+        assert_eq!(super::event("<3>CTRL-EVENT-SCAN-STARTED "), ("CTRL-EVENT-SCAN-STARTED", ""));
+        assert_eq!(super::event("<3>WPS-FAIL msg=8 config_error=18"), ("WPS-FAIL", "msg=8 config_error=18"));
+    }
+
+    #[test]
+    fn connected() {
+        let (_, body) = super::event("<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:55 completed \
+                                      [id=2 id_str=home]");
+        assert_eq!(super::connected(body), Connected {
+            bssid: "00:11:22:33:44:55".parse().ok(),
+            id: Some(NetworkId(2)),
+            id_str: Some("home"),
+        });
+        assert_eq!(super::connected("- Connection to 00:11:22:33:44:55 completed [id=0 id_str=]").id_str, None);
+        assert_eq!(super::connected("- Connection completed"), Connected { bssid: None, id: None, id_str: None });
+    }
+
+    #[test]
+    fn rows() {
+        let rows: Vec<_> = super::rows("network id / ssid / bssid / flags\n0\tfoo\tany\t[CURRENT]\n").collect();
+        assert_eq!(rows, vec![vec!["0", "foo", "any", "[CURRENT]"]]);
+    }
+
+    #[test]
+    fn header() {
+        assert_eq!(super::header("bssid / frequency / signal level / flags / ssid\n"),
+                   vec!["bssid", "frequency", "signal level", "flags", "ssid"]);
+        assert!(super::header("").is_empty());
+    }
+
+    #[test]
+    fn key_values() {
+        let status = "bssid=00:11:22:33:44:55\nssid=a=b\nno value\nwpa_state=COMPLETED\n";
+        assert_eq!(super::key_values(status), vec![
+            ("bssid", "00:11:22:33:44:55"),
+            ("ssid", "a=b"),
+            ("wpa_state", "COMPLETED"),
+        ]);
+        assert_eq!(key_value(status, "wpa_state"), Some("COMPLETED"));
+        assert_eq!(key_value(status, "ip_address"), None);
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(super::flags("[WPA2-PSK-CCMP][ESS]").collect::<Vec<_>>(), vec!["WPA2-PSK-CCMP", "ESS"]);
+        assert_eq!(super::flags("").count(), 0);
+    }
+
+    #[test]
+    fn event_args() {
+        let args = super::event_args("id=0 ssid=\"my net\" auth_failures=2 duration=10 reason=WRONG_KEY");
+        assert_eq!(args, vec![
+            ("id", "0"),
+            ("ssid", "my net"),
+            ("auth_failures", "2"),
+            ("duration", "10"),
+            ("reason", "WRONG_KEY"),
+        ]);
+        assert_eq!(event_arg("bssid=00:11:22:33:44:55 reason=3", "reason"), Some("3"));
+        assert_eq!(event_arg("name='Device A' config_methods=0x188", "name"), Some("Device A"));
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(hex_encode(&[0x00, 0xab, 0x7f]), "00ab7f");
+        assert_eq!(hex_decode("00AB7f"), Some(vec![0x00, 0xab, 0x7f]));
+        assert_eq!(hex_decode(""), Some(vec![]));
+        assert_eq!(hex_decode("abc"), None);
+        assert_eq!(hex_decode("+1"), None);
+    }
+}
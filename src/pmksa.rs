@@ -0,0 +1,217 @@
+use error::Error;
+use macaddr::MacAddr;
+use network::NetworkId;
+use secret::REDACTED;
+use std::fmt;
+use super::Result;
+use version::Version;
+use wpactrl::{expect_ok, reply_error, WpaCtrl};
+
+/// The release adding `PMKSA_GET` and `PMKSA_ADD`
+const PMKSA_EXTERNAL_SINCE: Version = Version::new(2, 7);
+
+/// An entry of the PMKSA cache, as shown by `PMKSA`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PmksaEntry {
+    /// The BSSID of the access point the PMK is cached for
+    pub bssid: MacAddr,
+    /// The PMK identifier, hex-encoded
+    pub pmkid: String,
+    /// Seconds until the entry expires
+    pub expiration: u64,
+    /// Whether the entry was added by opportunistic key caching
+    pub opportunistic: bool,
+}
+
+/// Parse the reply to `PMKSA`
+///
+/// Rows are space-separated, below a `Index / AA / PMKID / ...` header.
+fn parse_pmksa(reply: &str) -> Result<Vec<PmksaEntry>> {
+    reply.lines().skip(1).filter(|l| !l.is_empty()).map(|line| {
+        let fields: Vec<_> = line.split(' ').collect();
+        let err = || Error::Parse(line.to_owned());
+        if fields.len() < 5 {
+            return Err(err());
+        }
+        Ok(PmksaEntry {
+            bssid: fields[1].parse().map_err(|_| err())?,
+            pmkid: fields[2].to_owned(),
+            expiration: fields[3].parse().map_err(|_| err())?,
+            opportunistic: fields[4] == "1",
+        })
+    }).collect()
+}
+
+/// A complete PMKSA cache entry, including the PMK, as used by `PMKSA_GET` and `PMKSA_ADD`
+///
+/// These commands are only available from release 2.7, if wpasupplicant
+/// was built with `CONFIG_PMKSA_CACHE_EXTERNAL`; they allow the cache to be
+/// kept across restarts. On older releases they fail with
+/// `Error::NotSupported`.
+#[derive(Clone, PartialEq)]
+pub struct PmksaCacheEntry {
+    /// The BSSID of the access point the PMK is cached for
+    pub bssid: MacAddr,
+    /// The PMK identifier, hex-encoded
+    pub pmkid: String,
+    /// The PMK, hex-encoded
+    pub pmk: String,
+    /// Seconds until reauthentication is due
+    pub reauth_time: u64,
+    /// Seconds until the entry expires
+    pub expiration: u64,
+    /// The key management suite, as a number
+    pub akmp: u32,
+    /// Whether the entry was added by opportunistic key caching
+    pub opportunistic: bool,
+}
+
+/// Shows the PMK as `[REDACTED]`
+impl fmt::Debug for PmksaCacheEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PmksaCacheEntry")
+            .field("bssid", &self.bssid)
+            .field("pmkid", &self.pmkid)
+            .field("pmk", &format_args!("{}", REDACTED))
+            .field("reauth_time", &self.reauth_time)
+            .field("expiration", &self.expiration)
+            .field("akmp", &self.akmp)
+            .field("opportunistic", &self.opportunistic)
+            .finish()
+    }
+}
+
+impl PmksaCacheEntry {
+    /// Parse a line of the reply to `PMKSA_GET`
+    fn parse(line: &str) -> Result<Self> {
+        let fields: Vec<_> = line.split(' ').collect();
+        let err = || Error::Parse(line.to_owned());
+        if fields.len() < 7 {
+            return Err(err());
+        }
+        Ok(PmksaCacheEntry {
+            bssid: fields[0].parse().map_err(|_| err())?,
+            pmkid: fields[1].to_owned(),
+            pmk: fields[2].to_owned(),
+            reauth_time: fields[3].parse().map_err(|_| err())?,
+            expiration: fields[4].parse().map_err(|_| err())?,
+            akmp: fields[5].parse().map_err(|_| err())?,
+            opportunistic: fields[6] == "1",
+        })
+    }
+
+    /// Format the entry as expected by `PMKSA_ADD`
+    fn to_args(&self) -> String {
+        format!("{} {} {} {} {} {} {}", self.bssid, self.pmkid, self.pmk, self.reauth_time, self.expiration,
+                self.akmp, self.opportunistic as u8)
+    }
+}
+
+impl WpaCtrl {
+    /// List the entries of the PMKSA cache
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for entry in wpa.pmksa().unwrap() {
+    ///     println!("{} expires in {}s", entry.bssid, entry.expiration);
+    /// }
+    /// ```
+    pub fn pmksa(&mut self) -> Result<Vec<PmksaEntry>> {
+        parse_pmksa(&self.request("PMKSA")?)
+    }
+
+    /// Remove all entries from the PMKSA cache
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.pmksa_flush().unwrap();
+    /// ```
+    pub fn pmksa_flush(&mut self) -> Result<()> {
+        expect_ok(&self.request("PMKSA_FLUSH")?)
+    }
+
+    /// Export the PMKSA cache entries of a network, including the PMKs
+    ///
+    /// See `PmksaCacheEntry` for availability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkId;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let entries = wpa.pmksa_get(NetworkId(0)).unwrap();
+    /// ```
+    pub fn pmksa_get(&mut self, id: NetworkId) -> Result<Vec<PmksaCacheEntry>> {
+        let reply = self.request(&format!("PMKSA_GET {}", id))?;
+        let entries = match reply_error(&reply) {
+            Some(e) => Err(e),
+            None => reply.lines().filter(|l| !l.is_empty()).map(PmksaCacheEntry::parse).collect(),
+        };
+        self.since(PMKSA_EXTERNAL_SINCE, entries)
+    }
+
+    /// Import a PMKSA cache entry for a network, eg one saved by `pmksa_get`
+    ///
+    /// See `PmksaCacheEntry` for availability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkId;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for entry in wpa.pmksa_get(NetworkId(0)).unwrap() {
+    ///     wpa.pmksa_add(NetworkId(0), &entry).unwrap();
+    /// }
+    /// ```
+    pub fn pmksa_add(&mut self, id: NetworkId, entry: &PmksaCacheEntry) -> Result<()> {
+        let added = expect_ok(&self.request(&format!("PMKSA_ADD {} {}", id, entry.to_args()))?);
+        self.since(PMKSA_EXTERNAL_SINCE, added)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pmksa() {
+        let entries = parse_pmksa(concat!(
+            "Index / AA / PMKID / expiration (in seconds) / opportunistic\n",
+            "1 02:00:00:00:01:00 7a1b2c3d4e5f60718293a4b5c6d7e8f9 43175 0\n",
+            "2 02:00:00:00:02:00 00112233445566778899aabbccddeeff 43000 1\n",
+        )).unwrap();
+        assert_eq!(entries, vec![
+            PmksaEntry {
+                bssid: "02:00:00:00:01:00".parse().unwrap(),
+                pmkid: "7a1b2c3d4e5f60718293a4b5c6d7e8f9".into(),
+                expiration: 43175,
+                opportunistic: false,
+            },
+            PmksaEntry {
+                bssid: "02:00:00:00:02:00".parse().unwrap(),
+                pmkid: "00112233445566778899aabbccddeeff".into(),
+                expiration: 43000,
+                opportunistic: true,
+            },
+        ]);
+        assert!(parse_pmksa("Index / AA / PMKID / expiration (in seconds) / opportunistic\n").unwrap().is_empty());
+        assert!(parse_pmksa("header\n1 02:00:00:00:01:00\n").is_err());
+    }
+
+    #[test]
+    fn cache_entry() {
+        let line = "02:00:00:00:01:00 7a1b2c3d4e5f60718293a4b5c6d7e8f9 000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f 30000 43175 2 0";
+        let entry = PmksaCacheEntry::parse(line).unwrap();
+        assert_eq!(entry.bssid.to_string(), "02:00:00:00:01:00");
+        assert_eq!(entry.reauth_time, 30000);
+        assert_eq!(entry.akmp, 2);
+        assert!(!entry.opportunistic);
+        assert_eq!(entry.to_args(), line);
+        assert!(PmksaCacheEntry::parse("02:00:00:00:01:00 7a1b").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use super::Result;
+use wpactrl::{expect_ok, WpaCtrl};
+
+impl WpaCtrl {
+    /// Tell wpasupplicant the system is about to sleep
+    ///
+    /// Meant for power management daemons, which should call `resume`
+    /// once the system wakes up again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.suspend().unwrap();
+    /// // ... the system sleeps ...
+    /// wpa.resume().unwrap();
+    /// ```
+    pub fn suspend(&mut self) -> Result<()> {
+        expect_ok(&self.request("SUSPEND")?)
+    }
+
+    /// Tell wpasupplicant the system woke up after a `suspend`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.resume().unwrap();
+    /// ```
+    pub fn resume(&mut self) -> Result<()> {
+        expect_ok(&self.request("RESUME")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use error::Error;
+    use testing::MockSupplicant;
+    use wpactrl::WpaCtrl;
+
+    #[test]
+    fn suspend_resume() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_power_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("SUSPEND", "OK\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        wpa.suspend().unwrap();
+        assert!(matches!(wpa.resume(), Err(Error::UnknownCommand)));
+        assert_eq!(mock.commands(), vec!["SUSPEND", "RESUME"]);
+    }
+}
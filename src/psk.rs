@@ -0,0 +1,123 @@
+/// Derive the WPA pre-shared key from an SSID and passphrase, like `wpa_passphrase`
+///
+/// This is PBKDF2-HMAC-SHA1 with 4096 iterations, using the SSID as salt.
+/// Setting the hex-encoded result as the network's `psk` keeps the
+/// plaintext passphrase out of the configuration. Valid passphrases are 8
+/// to 63 ASCII characters long; this is not checked here.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{parse, psk_from_passphrase, NetworkValue};
+/// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+/// let psk = psk_from_passphrase("home", "correct horse battery staple");
+/// let id = wpa.add_network().unwrap();
+/// wpa.set_network(id, "ssid", "home").unwrap();
+/// wpa.set_network(id, "psk", NetworkValue::Raw(parse::hex_encode(&psk))).unwrap();
+/// ```
+pub fn psk_from_passphrase<S: AsRef<[u8]>>(ssid: S, passphrase: &str) -> [u8; 32] {
+    let mut psk = [0; 32];
+    for (i, chunk) in psk.chunks_mut(20).enumerate() {
+        let block = pbkdf2_sha1_block(passphrase.as_bytes(), ssid.as_ref(), 4096, i as u32 + 1);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    psk
+}
+
+/// Compute block `index` of PBKDF2-HMAC-SHA1
+fn pbkdf2_sha1_block(password: &[u8], salt: &[u8], iterations: u32, index: u32) -> [u8; 20] {
+    let mut msg = salt.to_vec();
+    msg.extend_from_slice(&index.to_be_bytes());
+    let mut u = hmac_sha1(password, &msg);
+    let mut block = u;
+    for _ in 1..iterations {
+        u = hmac_sha1(password, &u);
+        for (b, x) in block.iter_mut().zip(u.iter()) {
+            *b ^= x;
+        }
+    }
+    block
+}
+
+/// Compute the HMAC-SHA1 of `msg`
+fn hmac_sha1(key: &[u8], msg: &[u8]) -> [u8; 20] {
+    let mut k = [0u8; 64];
+    if key.len() > 64 {
+        k[..20].copy_from_slice(&sha1(key));
+    } else {
+        k[..key.len()].copy_from_slice(key);
+    }
+    let mut inner: Vec<u8> = k.iter().map(|b| b ^ 0x36).collect();
+    inner.extend_from_slice(msg);
+    let mut outer: Vec<u8> = k.iter().map(|b| b ^ 0x5c).collect();
+    outer.extend_from_slice(&sha1(&inner));
+    sha1(&outer)
+}
+
+/// Compute the SHA-1 digest of `data`
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for chunk in msg.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let (mut a, mut b, mut c, mut d, mut e) = (h[0], h[1], h[2], h[3], h[4]);
+        for (i, &wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, x) in h.iter_mut().zip([a, b, c, d, e].iter()) {
+            *h = h.wrapping_add(*x);
+        }
+    }
+    let mut digest = [0; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(h.iter()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use parse::hex_encode;
+
+    #[test]
+    fn sha1() {
+        assert_eq!(hex_encode(&super::sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex_encode(&super::sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    }
+
+    #[test]
+    fn psk() {
+        // Test vectors from IEEE 802.11i-2004, annex H.4
+        assert_eq!(hex_encode(&psk_from_passphrase("IEEE", "password")),
+                   "f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e");
+        assert_eq!(hex_encode(&psk_from_passphrase("ThisIsASSID", "ThisIsAPassword")),
+                   "0dc0d6eb90555ed6419756b9a15ec3e3209b63df707dd508d14581f8982721af");
+        // Longest SSID and passphrase
+        assert_eq!(hex_encode(&psk_from_passphrase(b"ZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZZ",
+                                                   "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")),
+                   "2d43d0dabfdd635377172efa1fc4b4b87dbfc4219193909ded9a7cfb89a3097b");
+    }
+}
@@ -0,0 +1,188 @@
+use macaddr::MacAddr;
+use network::NetworkId;
+use parse::{connected, event, event_arg, Connected};
+use std::collections::HashMap;
+
+const SIGNAL_BEST: i32 = -50;
+const SIGNAL_WORST: i32 = -90;
+const SIGNAL_UNKNOWN_SCORE: i32 = 50;
+const DISCONNECT_PENALTY: i32 = 10;
+const RETRY_PENALTY: i32 = 5;
+const ROAM_PENALTY: i32 = 5;
+
+/// Quality data accumulated for a single configured network
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NetworkQuality {
+    /// Most recently reported signal strength in dBm
+    pub signal: Option<i32>,
+    /// Number of disconnects that were not initiated locally
+    pub disconnects: u32,
+    /// IEEE 802.11 reason code of the most recent disconnect
+    pub last_disconnect_reason: Option<u16>,
+    /// Number of failed connection attempts
+    pub retries: u32,
+    /// Number of roams between BSSes of this network
+    pub roams: u32,
+}
+
+impl NetworkQuality {
+    /// Quality score between 0 (unusable) and 100 (excellent)
+    ///
+    /// The signal strength is mapped linearly from -90 dBm (0) to -50 dBm (100),
+    /// with an unknown signal counting as 50. Every disconnect costs 10 points,
+    /// and every failed attempt or roam costs 5.
+    pub fn score(&self) -> u8 {
+        let signal = match self.signal {
+            Some(dbm) => (dbm.clamp(SIGNAL_WORST, SIGNAL_BEST) - SIGNAL_WORST) * 100 / (SIGNAL_BEST - SIGNAL_WORST),
+            None => SIGNAL_UNKNOWN_SCORE,
+        };
+        let penalty = self.disconnects as i32 * DISCONNECT_PENALTY
+            + self.retries as i32 * RETRY_PENALTY
+            + self.roams as i32 * ROAM_PENALTY;
+        (signal - penalty).max(0) as u8
+    }
+}
+
+/// A new quality score for a network, produced by `QualityScorer::feed`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QualityUpdate {
+    /// The network id, as used by `SELECT_NETWORK` and friends
+    pub network: NetworkId,
+    /// The updated score
+    pub score: u8,
+}
+
+/// Folds control interface events into per-network quality scores
+///
+/// Feed every message received from an attached connection into the scorer;
+/// each message that affects a network yields a `QualityUpdate`.
+///
+/// # Examples
+///
+/// ```
+/// let mut scorer = wpactrl::QualityScorer::new();
+/// scorer.feed("<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:55 completed [id=0 id_str=]");
+/// let update = scorer.feed("<3>CTRL-EVENT-SIGNAL-CHANGE above=1 signal=-50 noise=-95 txrate=65000").unwrap();
+/// assert_eq!(update.network, wpactrl::NetworkId(0));
+/// assert_eq!(update.score, 100);
+/// ```
+#[derive(Debug, Default)]
+pub struct QualityScorer {
+    networks: HashMap<NetworkId, NetworkQuality>,
+    current: Option<(NetworkId, Option<MacAddr>)>,
+}
+
+impl QualityScorer {
+    /// Create a scorer with no accumulated history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a single control interface message
+    ///
+    /// Returns the new score of the affected network, or None if the
+    /// message does not influence any network's quality.
+    pub fn feed(&mut self, msg: &str) -> Option<QualityUpdate> {
+        let (event, body) = event(msg);
+        let network = match event {
+            "CTRL-EVENT-CONNECTED" => {
+                let Connected { id, bssid, .. } = connected(body);
+                let id = id?;
+                let roamed = match self.current {
+                    Some((cur, Some(cur_bssid))) => cur == id && bssid.is_some_and(|bssid| bssid != cur_bssid),
+                    _ => false,
+                };
+                self.current = Some((id, bssid));
+                let quality = self.networks.entry(id).or_default();
+                if roamed {
+                    quality.roams += 1;
+                }
+                id
+            }
+            "CTRL-EVENT-DISCONNECTED" => {
+                let (id, _) = self.current.take()?;
+                let quality = self.networks.entry(id).or_default();
+                quality.last_disconnect_reason = event_arg(body, "reason").and_then(|r| r.parse().ok());
+                if event_arg(body, "locally_generated") != Some("1") {
+                    quality.disconnects += 1;
+                }
+                id
+            }
+            "CTRL-EVENT-SIGNAL-CHANGE" => {
+                let id = self.current.as_ref()?.0;
+                let signal = event_arg(body, "signal")?.parse().ok()?;
+                self.networks.entry(id).or_default().signal = Some(signal);
+                id
+            }
+            "CTRL-EVENT-SSID-TEMP-DISABLED" => {
+                let id = event_arg(body, "id")?.parse().map(NetworkId).ok()?;
+                self.networks.entry(id).or_default().retries += 1;
+                id
+            }
+            _ => return None,
+        };
+        self.score(network).map(|score| QualityUpdate { network, score })
+    }
+
+    /// The accumulated quality data for a network, if any events for it were seen
+    pub fn quality(&self, network: NetworkId) -> Option<&NetworkQuality> {
+        self.networks.get(&network)
+    }
+
+    /// The current score of a network, if any events for it were seen
+    pub fn score(&self, network: NetworkId) -> Option<u8> {
+        self.quality(network).map(NetworkQuality::score)
+    }
+
+    /// Forget the accumulated history of a network
+    pub fn reset(&mut self, network: NetworkId) {
+        self.networks.remove(&network);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONNECTED: &str = "<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:55 completed [id=1 id_str=]";
+
+    #[test]
+    fn signal() {
+        let mut scorer = QualityScorer::new();
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-SIGNAL-CHANGE above=1 signal=-60"), None);
+        assert_eq!(scorer.feed(CONNECTED), Some(QualityUpdate { network: NetworkId(1), score: 50 }));
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-SIGNAL-CHANGE above=0 signal=-70 noise=-95"),
+                   Some(QualityUpdate { network: NetworkId(1), score: 50 }));
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-SIGNAL-CHANGE above=0 signal=-100"),
+                   Some(QualityUpdate { network: NetworkId(1), score: 0 }));
+    }
+
+    #[test]
+    fn disconnect() {
+        let mut scorer = QualityScorer::new();
+        scorer.feed(CONNECTED);
+        scorer.feed("<3>CTRL-EVENT-SIGNAL-CHANGE above=1 signal=-50");
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-DISCONNECTED bssid=00:11:22:33:44:55 reason=4"),
+                   Some(QualityUpdate { network: NetworkId(1), score: 90 }));
+        assert_eq!(scorer.quality(NetworkId(1)).unwrap().last_disconnect_reason, Some(4));
+        scorer.feed(CONNECTED);
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-DISCONNECTED bssid=00:11:22:33:44:55 reason=3 locally_generated=1"),
+                   Some(QualityUpdate { network: NetworkId(1), score: 90 }));
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-DISCONNECTED bssid=00:11:22:33:44:55 reason=3"), None);
+    }
+
+    #[test]
+    fn retries_and_roams() {
+        let mut scorer = QualityScorer::new();
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-SSID-TEMP-DISABLED id=1 ssid=\"foo bar\" auth_failures=1 duration=10 reason=WRONG_KEY"),
+                   Some(QualityUpdate { network: NetworkId(1), score: 45 }));
+        scorer.feed(CONNECTED);
+        assert_eq!(scorer.feed("<3>CTRL-EVENT-CONNECTED - Connection to 00:11:22:33:44:66 completed [id=1 id_str=]"),
+                   Some(QualityUpdate { network: NetworkId(1), score: 40 }));
+        assert_eq!(scorer.quality(NetworkId(1)).unwrap().roams, 1);
+        scorer.reset(NetworkId(1));
+        assert_eq!(scorer.score(NetworkId(1)), None);
+    }
+}
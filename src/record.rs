@@ -0,0 +1,155 @@
+use secret::{redact, redact_event, redact_reply};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// One entry of a recording of control interface traffic
+#[cfg(any(test, feature = "testing"))]
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Record {
+    /// A command sent, with credentials redacted
+    Command(String),
+    /// The reply to the preceding command
+    Reply(Vec<u8>),
+    /// An unsolicited message received
+    Event(Vec<u8>),
+}
+
+/// Writes the traffic of a connection to a file, one record per line
+///
+/// Lines start with `>` for commands, `<` for replies and `!` for events.
+/// Newlines, backslashes and non-printable bytes are escaped as `\n`,
+/// `\\` and `\xNN`, so every record fits on one line. Credentials are
+/// redacted as for logging.
+pub(crate) struct Recorder(File);
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        File::create(path).map(Recorder)
+    }
+
+    pub fn command(&mut self, cmd: &str) {
+        self.write('>', redact(cmd).as_bytes());
+    }
+
+    pub fn reply(&mut self, cmd: &str, reply: &[u8]) {
+        self.write('<', redact_reply(cmd, &String::from_utf8_lossy(reply)).as_bytes());
+    }
+
+    pub fn event(&mut self, msg: &[u8]) {
+        self.write('!', redact_event(&String::from_utf8_lossy(msg)).as_bytes());
+    }
+
+    /// Write a record, without failing the request if the recording cannot be written
+    fn write(&mut self, kind: char, data: &[u8]) {
+        if let Err(e) = writeln!(self.0, "{} {}", kind, escape(data)) {
+            warn!("Unable to write recording: {}", e);
+        }
+    }
+}
+
+/// Escape newlines, backslashes and non-printable bytes
+fn escape(data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.len());
+    for &b in data {
+        match b {
+            b'\\' => s.push_str("\\\\"),
+            b'\n' => s.push_str("\\n"),
+            0x20..=0x7e => s.push(b as char),
+            _ => s.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    s
+}
+
+/// Undo `escape`, returning None if an escape sequence is invalid
+#[cfg(any(test, feature = "testing"))]
+fn unescape(s: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'\\' {
+            data.push(b);
+            continue;
+        }
+        match bytes.next()? {
+            b'\\' => data.push(b'\\'),
+            b'n' => data.push(b'\n'),
+            b'x' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                data.push(u8::from_str_radix(::std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => return None,
+        }
+    }
+    Some(data)
+}
+
+/// Parse a recording written by `Recorder`
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn parse_recording(text: &str) -> io::Result<Vec<Record>> {
+    text.lines().filter(|line| !line.is_empty()).map(|line| {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid record: {:?}", line));
+        let (kind, data) = line.split_once(' ').ok_or_else(invalid)?;
+        let data = unescape(data).ok_or_else(invalid)?;
+        match kind {
+            ">" => String::from_utf8(data).map(Record::Command).map_err(|_| invalid()),
+            "<" => Ok(Record::Reply(data)),
+            "!" => Ok(Record::Event(data)),
+            _ => Err(invalid()),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn escape() {
+        let data = b"a\\b\nc\xff\x01 ";
+        assert_eq!(super::escape(data), "a\\\\b\\nc\\xff\\x01 ");
+        assert_eq!(unescape(&super::escape(data)).unwrap(), data.to_vec());
+        assert_eq!(unescape("bad\\q"), None);
+        assert_eq!(unescape("short\\x1"), None);
+    }
+
+    #[test]
+    fn recording() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_recording_{}", ::std::process::id()));
+        {
+            let mut recorder = Recorder::create(&path).unwrap();
+            recorder.command("SET_NETWORK 0 psk \"hunter2\"");
+            recorder.reply("SET_NETWORK 0 psk \"hunter2\"", b"OK\n");
+            recorder.event(b"<3>CTRL-EVENT-SCAN-STARTED ");
+        }
+        let text = ::std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, "> SET_NETWORK 0 psk [REDACTED]\n< OK\\n\n! <3>CTRL-EVENT-SCAN-STARTED \n");
+        assert_eq!(parse_recording(&text).unwrap(), vec![
+            Record::Command("SET_NETWORK 0 psk [REDACTED]".into()),
+            Record::Reply(b"OK\n".to_vec()),
+            Record::Event(b"<3>CTRL-EVENT-SCAN-STARTED ".to_vec()),
+        ]);
+        assert!(parse_recording("? what\n").is_err());
+        ::std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn redacted() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_recording_redacted_{}", ::std::process::id()));
+        {
+            let mut recorder = Recorder::create(&path).unwrap();
+            recorder.command("GET_NETWORK 0 psk");
+            recorder.reply("GET_NETWORK 0 psk", b"\"hunter2\"\n");
+            recorder.event(b"<3>P2P-GROUP-STARTED p2p-wlan0-0 GO ssid=\"x\" passphrase=\"hunter2\"");
+        }
+        let text = ::std::fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("hunter2"));
+        assert_eq!(parse_recording(&text).unwrap(), vec![
+            Record::Command("GET_NETWORK 0 psk".into()),
+            Record::Reply(b"[REDACTED]".to_vec()),
+            Record::Event(b"<3>P2P-GROUP-STARTED p2p-wlan0-0 GO ssid=\"x\" passphrase=[REDACTED]".to_vec()),
+        ]);
+        ::std::fs::remove_file(&path).unwrap();
+    }
+}
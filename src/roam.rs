@@ -0,0 +1,68 @@
+use error::Error;
+use macaddr::MacAddr;
+use network::{set_network, NetworkId, NetworkValue};
+use super::Result;
+use wpactrl::{expect_ok, WpaCtrl};
+
+impl WpaCtrl {
+    /// Start RSN preauthentication with an access point
+    ///
+    /// Performs IEEE 802.1X authentication with another access point of the
+    /// current ESS ahead of time, so that roaming to it can use the cached
+    /// PMKSA instead of a full authentication.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.preauth("02:00:00:00:01:00".parse().unwrap()).unwrap();
+    /// ```
+    pub fn preauth(&mut self, bssid: MacAddr) -> Result<()> {
+        expect_ok(&self.request(&format!("PREAUTH {}", bssid))?)
+    }
+
+    /// Roam to another access point of the current ESS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.roam("02:00:00:00:01:00".parse().unwrap()).unwrap();
+    /// ```
+    pub fn roam(&mut self, bssid: MacAddr) -> Result<()> {
+        expect_ok(&self.request(&format!("ROAM {}", bssid))?)
+    }
+
+    /// Roam to another access point, after checking it is in the scan results
+    ///
+    /// Fails with `Error::UnknownBss` without roaming if the access
+    /// point was not seen by the most recent scans.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.roam_checked("02:00:00:00:01:00".parse().unwrap()).unwrap();
+    /// ```
+    pub fn roam_checked(&mut self, bssid: MacAddr) -> Result<()> {
+        if !self.scan_results()?.iter().any(|bss| bss.bssid == bssid) {
+            return Err(Error::UnknownBss(bssid));
+        }
+        self.roam(bssid)
+    }
+
+    /// Restrict a network to a single access point, or lift the restriction with None
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let id = wpa.add_network().unwrap();
+    /// wpa.set_network_bssid(id, "02:00:00:00:01:00".parse().ok()).unwrap();
+    /// wpa.set_network_bssid(id, None).unwrap();
+    /// ```
+    pub fn set_network_bssid(&mut self, id: NetworkId, bssid: Option<MacAddr>) -> Result<()> {
+        let bssid = bssid.map_or_else(|| "any".to_owned(), |bssid| bssid.to_string());
+        set_network(self, id, "bssid", &NetworkValue::Raw(bssid))
+    }
+}
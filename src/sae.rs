@@ -0,0 +1,105 @@
+use macaddr::MacAddr;
+use network::NetworkConfig;
+use parse::{event, event_arg};
+
+/// The status code with which an access point rejects an unknown password identifier
+const UNKNOWN_PASSWORD_IDENTIFIER: u16 = 123;
+
+/// The authentication algorithm number of SAE
+const AUTH_TYPE_SAE: &str = "3";
+
+impl NetworkConfig {
+    /// The identifier of the SAE password, for access points with a password per user
+    ///
+    /// The access point picks the password matching the identifier, so
+    /// several users can share an SSID with their own passwords.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::NetworkConfig;
+    /// let config = NetworkConfig::sae("office", "alice's password").sae_password_id("alice");
+    /// ```
+    pub fn sae_password_id<I: Into<String>>(self, id: I) -> Self {
+        self.set("sae_password_id", id.into())
+    }
+}
+
+/// An SAE (WPA3-Personal) authentication event
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SaeEvent {
+    /// The access point did not know the password identifier (`CTRL-EVENT-AUTH-REJECT` with status 123)
+    PasswordIdRejected(MacAddr),
+    /// The access point rejected SAE authentication for another reason (`CTRL-EVENT-AUTH-REJECT`)
+    AuthRejected {
+        /// The BSSID of the access point
+        bssid: MacAddr,
+        /// The IEEE 802.11 status code
+        status_code: u16,
+    },
+    /// A station used a password identifier hostapd does not know (`CTRL-EVENT-SAE-UNKNOWN-PASSWORD-IDENTIFIER`)
+    UnknownPasswordId(MacAddr),
+}
+
+impl SaeEvent {
+    /// Parse an SAE event, returning None for other messages
+    ///
+    /// Authentication rejections are only reported for SAE, not for
+    /// other authentication algorithms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::SaeEvent;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((_, msg)) = wpa.recv().unwrap() {
+    ///     if let Some(SaeEvent::PasswordIdRejected(bssid)) = SaeEvent::parse(&msg) {
+    ///         println!("{} does not know our password identifier", bssid);
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        let addr = || body.split(' ').next()?.parse().ok();
+        match event {
+            "CTRL-EVENT-AUTH-REJECT" if event_arg(body, "auth_type") == Some(AUTH_TYPE_SAE) => {
+                match event_arg(body, "status_code")?.parse().ok()? {
+                    UNKNOWN_PASSWORD_IDENTIFIER => addr().map(SaeEvent::PasswordIdRejected),
+                    status_code => Some(SaeEvent::AuthRejected { bssid: addr()?, status_code }),
+                }
+            }
+            "CTRL-EVENT-SAE-UNKNOWN-PASSWORD-IDENTIFIER" => addr().map(SaeEvent::UnknownPasswordId),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use network::NetworkValue;
+
+    #[test]
+    fn password_id() {
+        let config = NetworkConfig::sae("office", "secret").sae_password_id("alice");
+        assert_eq!(config.get("sae_password_id"), Some(&NetworkValue::Str("alice".into())));
+    }
+
+    #[test]
+    fn events() {
+        let bssid: MacAddr = "02:00:00:00:01:00".parse().unwrap();
+        assert_eq!(SaeEvent::parse("<3>CTRL-EVENT-AUTH-REJECT 02:00:00:00:01:00 auth_type=3 auth_transaction=1 \
+                                    status_code=123"),
+                   Some(SaeEvent::PasswordIdRejected(bssid)));
+        assert_eq!(SaeEvent::parse("<3>CTRL-EVENT-AUTH-REJECT 02:00:00:00:01:00 auth_type=3 auth_transaction=2 \
+                                    status_code=15 ie=dd00"),
+                   Some(SaeEvent::AuthRejected { bssid, status_code: 15 }));
+        assert_eq!(SaeEvent::parse("<3>CTRL-EVENT-AUTH-REJECT 02:00:00:00:01:00 auth_type=0 auth_transaction=2 \
+                                    status_code=1"),
+                   None);
+        assert_eq!(SaeEvent::parse("<3>CTRL-EVENT-SAE-UNKNOWN-PASSWORD-IDENTIFIER 02:00:00:00:01:00"),
+                   Some(SaeEvent::UnknownPasswordId(bssid)));
+        assert_eq!(SaeEvent::parse("<3>CTRL-EVENT-SAE-UNKNOWN-PASSWORD-IDENTIFIER"), None);
+    }
+}
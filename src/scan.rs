@@ -0,0 +1,376 @@
+use error::Error;
+use freq::{freq_to_channel, Band};
+use macaddr::MacAddr;
+use parse::{self, event, event_arg};
+use scan_flags::ScanFlags;
+use std::collections::VecDeque;
+use std::time::Duration;
+use super::Result;
+use wpactrl::{WpaCtrl, WpaCtrlAttached};
+
+/// An access point found by scanning, as shown by `SCAN_RESULTS`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanResult {
+    /// The BSSID of the access point
+    pub bssid: MacAddr,
+    /// The frequency in MHz
+    pub frequency: u32,
+    /// The signal level in dBm
+    pub signal: i32,
+    /// The flags, eg `WPA2-PSK-CCMP` and `ESS`
+    pub flags: Vec<String>,
+    /// The SSID, as escaped by wpasupplicant
+    pub ssid: String,
+    /// The unparsed row, for columns this type does not cover
+    pub raw: String,
+}
+
+impl ScanResult {
+    /// The flags, parsed into security suites and capabilities
+    pub fn scan_flags(&self) -> ScanFlags {
+        ScanFlags::parse(&self.flags)
+    }
+
+    /// The channel number of the access point, or None if its frequency is not in a known band
+    pub fn channel(&self) -> Option<u32> {
+        freq_to_channel(self.frequency).map(|(_, channel)| channel)
+    }
+
+    /// The band of the access point, or None if its frequency is not in a known band
+    pub fn band(&self) -> Option<Band> {
+        Band::from_freq(self.frequency)
+    }
+
+    /// Parse a single tab-separated row of `SCAN_RESULTS` output
+    fn from_row(row: &[&str]) -> Result<Self> {
+        let err = || Error::Parse(row.join("\t"));
+        if row.len() < 4 {
+            return Err(err());
+        }
+        Ok(ScanResult {
+            bssid: row[0].parse().map_err(|_| err())?,
+            frequency: row[1].parse().map_err(|_| err())?,
+            signal: row[2].parse().map_err(|_| err())?,
+            flags: parse::flags(row[3]).map(str::to_owned).collect(),
+            ssid: row.get(4).map_or("", |s| s).to_owned(),
+            raw: row.join("\t"),
+        })
+    }
+}
+
+/// Parse the output of `SCAN_RESULTS`
+fn parse_scan_results(reply: &str) -> Result<Vec<ScanResult>> {
+    parse::rows(reply).map(|row| ScanResult::from_row(&row)).collect()
+}
+
+/// An entry of wpasupplicant's BSS table, as shown by `BSS`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bss {
+    /// The id of the entry, which increases as access points are found
+    pub id: u32,
+    /// The BSSID of the access point
+    pub bssid: MacAddr,
+    /// The frequency in MHz
+    pub frequency: u32,
+    /// The signal level in dBm
+    pub signal: i32,
+    /// The flags, eg `WPA2-PSK-CCMP` and `ESS`
+    pub flags: Vec<String>,
+    /// The SSID, as escaped by wpasupplicant
+    pub ssid: String,
+    /// Seconds since the access point was last seen
+    pub age: Option<u32>,
+    /// The unparsed entry, for variables this type does not cover
+    pub raw: String,
+}
+
+impl Bss {
+    /// The flags, parsed into security suites and capabilities
+    pub fn scan_flags(&self) -> ScanFlags {
+        ScanFlags::parse(&self.flags)
+    }
+
+    /// The channel number of the access point, or None if its frequency is not in a known band
+    pub fn channel(&self) -> Option<u32> {
+        freq_to_channel(self.frequency).map(|(_, channel)| channel)
+    }
+
+    /// The band of the access point, or None if its frequency is not in a known band
+    pub fn band(&self) -> Option<Band> {
+        Band::from_freq(self.frequency)
+    }
+
+    /// Parse a single entry of `BSS` output
+    fn parse(entry: &str) -> Result<Self> {
+        let err = || Error::Parse(entry.to_owned());
+        let value = |key| parse::key_value(entry, key).ok_or_else(err);
+        Ok(Bss {
+            id: value("id")?.parse().map_err(|_| err())?,
+            bssid: value("bssid")?.parse().map_err(|_| err())?,
+            frequency: value("freq")?.parse().map_err(|_| err())?,
+            signal: value("level")?.parse().map_err(|_| err())?,
+            flags: parse::key_value(entry, "flags").map(|f| parse::flags(f).map(str::to_owned).collect()).unwrap_or_default(),
+            ssid: parse::key_value(entry, "ssid").unwrap_or("").to_owned(),
+            age: parse::key_value(entry, "age").and_then(|v| v.parse().ok()),
+            raw: entry.to_owned(),
+        })
+    }
+}
+
+/// Parse the reply to `BSS RANGE=...` with the delimiter mask bit set
+fn parse_bss_range(reply: &str) -> Result<Vec<Bss>> {
+    reply.split("====\n").filter(|entry| !entry.trim().is_empty()).map(Bss::parse).collect()
+}
+
+/// Iterator over the entries of wpasupplicant's BSS table
+///
+/// Created by `WpaCtrl::bss_entries`.
+pub struct BssEntries<'a> {
+    ctrl: &'a mut WpaCtrl,
+    next_id: u32,
+    pending: VecDeque<Bss>,
+    done: bool,
+}
+
+impl<'a> Iterator for BssEntries<'a> {
+    type Item = Result<Bss>;
+
+    fn next(&mut self) -> Option<Result<Bss>> {
+        if let Some(bss) = self.pending.pop_front() {
+            return Some(Ok(bss));
+        }
+        if self.done {
+            return None;
+        }
+        // All variables, plus the bit that ends each entry with a delimiter
+        let cmd = format!("BSS RANGE={}- MASK=0xffffffff", self.next_id);
+        let entries = self.ctrl.request(&cmd).and_then(|reply| {
+            if reply.starts_with("FAIL") {
+                Err(Error::Failure)
+            } else {
+                parse_bss_range(&reply)
+            }
+        });
+        match entries {
+            Ok(entries) => {
+                match entries.last() {
+                    Some(last) => self.next_id = last.id + 1,
+                    None => self.done = true,
+                }
+                self.pending.extend(entries);
+                self.pending.pop_front().map(Ok)
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// A step in the life of a scan
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ScanEvent {
+    /// A scan was started (`CTRL-EVENT-SCAN-STARTED`)
+    Started,
+    /// A scan completed and its results are available (`CTRL-EVENT-SCAN-RESULTS`)
+    ResultsReady,
+    /// A scan could not be started (`CTRL-EVENT-SCAN-FAILED`)
+    Failed {
+        /// The error code from the driver, eg -16 when it is busy
+        ret: Option<i32>,
+        /// Whether wpasupplicant will try the scan again by itself
+        retry: bool,
+    },
+}
+
+impl ScanEvent {
+    /// Parse a scan event, returning None for other messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::ScanEvent;
+    /// assert_eq!(ScanEvent::parse("<3>CTRL-EVENT-SCAN-FAILED ret=-16 retry=1"),
+    ///            Some(ScanEvent::Failed { ret: Some(-16), retry: true }));
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        match event {
+            "CTRL-EVENT-SCAN-STARTED" => Some(ScanEvent::Started),
+            "CTRL-EVENT-SCAN-RESULTS" => Some(ScanEvent::ResultsReady),
+            "CTRL-EVENT-SCAN-FAILED" => Some(ScanEvent::Failed {
+                ret: event_arg(body, "ret").and_then(|v| v.parse().ok()),
+                retry: event_arg(body, "retry") == Some("1"),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Iterator over the scan events received by an attached connection
+///
+/// Created by `WpaCtrlAttached::scan_events`.
+pub struct ScanEvents<'a> {
+    ctrl: &'a mut WpaCtrlAttached,
+}
+
+impl<'a> Iterator for ScanEvents<'a> {
+    type Item = Result<ScanEvent>;
+
+    fn next(&mut self) -> Option<Result<ScanEvent>> {
+        loop {
+            match self.ctrl.recv() {
+                Ok(Some((_, msg))) => {
+                    if let Some(event) = ScanEvent::parse(&msg) {
+                        return Some(Ok(event));
+                    }
+                }
+                Ok(None) => {
+                    let queued = self.ctrl.queued();
+                    if let Err(e) = self.ctrl.wait_for(queued, Duration::from_secs(60), |_| Some(())) {
+                        return Some(Err(e));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl WpaCtrlAttached {
+    /// Iterate over scan events, waiting for each one
+    ///
+    /// Other messages received meanwhile are discarded, so this suits a
+    /// connection dedicated to following scans. The iterator never ends
+    /// by itself; after `Error::ConnectionLost`, reopen the connection to
+    /// carry on.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wpactrl::ScanEvent;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// wpa.request("SCAN").unwrap();
+    /// for event in wpa.scan_events() {
+    ///     match event.unwrap() {
+    ///         ScanEvent::ResultsReady => break,
+    ///         ScanEvent::Failed { retry: false, .. } => panic!("scan failed"),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// ```
+    pub fn scan_events(&mut self) -> ScanEvents<'_> {
+        ScanEvents { ctrl: self }
+    }
+}
+
+impl WpaCtrl {
+    /// Iterate over the BSS table, fetching it a chunk at a time
+    ///
+    /// wpasupplicant returns as many entries as fit in one reply, and the
+    /// next chunk is requested from the id following the last one
+    /// received. Unlike `scan_results`, this is not limited by the size of
+    /// a single reply, so it suits dense environments. Entries added or
+    /// removed while iterating may or may not be seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for bss in wpa.bss_entries() {
+    ///     let bss = bss.unwrap();
+    ///     println!("{} {} {}dBm", bss.bssid, bss.ssid, bss.signal);
+    /// }
+    /// ```
+    pub fn bss_entries(&mut self) -> BssEntries<'_> {
+        BssEntries { ctrl: self, next_id: 0, pending: VecDeque::new(), done: false }
+    }
+
+    /// The access points found by the most recent scans
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// for bss in wpa.scan_results().unwrap() {
+    ///     println!("{} {} {}dBm", bss.bssid, bss.ssid, bss.signal);
+    /// }
+    /// ```
+    pub fn scan_results(&mut self) -> Result<Vec<ScanResult>> {
+        parse_scan_results(&self.request("SCAN_RESULTS")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scan_results() {
+        let results = parse_scan_results(concat!(
+            "bssid / frequency / signal level / flags / ssid\n",
+            "02:00:00:00:01:00\t2412\t-45\t[WPA2-PSK-CCMP][ESS]\thome\n",
+            "02:00:00:00:02:00\t5180\t-70\t[ESS]\t\n",
+        )).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].bssid.to_string(), "02:00:00:00:01:00");
+        assert_eq!(results[0].frequency, 2412);
+        assert_eq!(results[0].signal, -45);
+        assert_eq!(results[0].flags, vec!["WPA2-PSK-CCMP", "ESS"]);
+        assert_eq!(results[0].ssid, "home");
+        assert_eq!(results[1].ssid, "");
+        assert_eq!((results[0].band(), results[0].channel()), (Some(Band::Ghz2_4), Some(1)));
+        assert_eq!((results[1].band(), results[1].channel()), (Some(Band::Ghz5), Some(36)));
+        assert!(!results[0].scan_flags().is_open());
+        assert!(results[1].scan_flags().is_open());
+    }
+
+    #[test]
+    fn bss_range() {
+        let entries = parse_bss_range(concat!(
+            "id=3\nbssid=02:00:00:00:01:00\nfreq=2412\nlevel=-45\nage=2\nflags=[WPA2-PSK-CCMP][ESS]\nssid=home\n====\n",
+            "id=7\nbssid=02:00:00:00:02:00\nfreq=5180\nlevel=-70\nflags=[ESS]\nssid=\n====\n",
+        )).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 3);
+        assert_eq!(entries[0].bssid.to_string(), "02:00:00:00:01:00");
+        assert_eq!(entries[0].frequency, 2412);
+        assert_eq!(entries[0].signal, -45);
+        assert_eq!(entries[0].age, Some(2));
+        assert_eq!(entries[0].flags, vec!["WPA2-PSK-CCMP", "ESS"]);
+        assert_eq!(entries[0].ssid, "home");
+        assert_eq!(entries[1].id, 7);
+        assert_eq!(entries[1].age, None);
+        assert!(parse_bss_range("").unwrap().is_empty());
+        assert!(parse_bss_range("bssid=02:00:00:00:01:00\n====\n").is_err());
+    }
+
+    #[test]
+    fn scan_events() {
+        assert_eq!(ScanEvent::parse("<3>CTRL-EVENT-SCAN-STARTED "), Some(ScanEvent::Started));
+        assert_eq!(ScanEvent::parse("<3>CTRL-EVENT-SCAN-RESULTS "), Some(ScanEvent::ResultsReady));
+        assert_eq!(ScanEvent::parse("<3>CTRL-EVENT-SCAN-FAILED ret=-16"),
+                   Some(ScanEvent::Failed { ret: Some(-16), retry: false }));
+        assert_eq!(ScanEvent::parse("<3>CTRL-EVENT-SCAN-RESULTSX"), None);
+
+        use testing::MockSupplicant;
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_scan_events_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        mock.event("<3>CTRL-EVENT-SCAN-STARTED ");
+        mock.event("<3>CTRL-EVENT-BSS-ADDED 0 02:00:00:00:01:00");
+        mock.event("<3>CTRL-EVENT-SCAN-RESULTS ");
+        let events: Vec<_> = wpa.scan_events().take(2).map(Result::unwrap).collect();
+        assert_eq!(events, vec![ScanEvent::Started, ScanEvent::ResultsReady]);
+    }
+
+    #[test]
+    fn scan_results_invalid() {
+        assert!(parse_scan_results("bssid / frequency / signal level / flags / ssid\n02:00:00:00:01:00\tx\t-45\t\t\n")
+            .is_err());
+    }
+}
@@ -0,0 +1,326 @@
+bitflags! {
+    /// Security protocols offered by an access point
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Protocols: u8 {
+        /// WPA, the `WPA` flag
+        const WPA = 1;
+        /// RSN, also known as WPA2 and WPA3, the `WPA2` or `RSN` flag
+        const RSN = 1 << 1;
+        /// OSEN, used by Hotspot 2.0 online sign-up
+        const OSEN = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// Key management suites offered by an access point
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct KeyMgmt: u32 {
+        /// WPA-Enterprise (802.1X)
+        const EAP = 1;
+        /// WPA-Personal with a pre-shared key
+        const PSK = 1 << 1;
+        /// Fast transition with 802.1X
+        const FT_EAP = 1 << 2;
+        /// Fast transition with a pre-shared key
+        const FT_PSK = 1 << 3;
+        /// 802.1X with SHA-256
+        const EAP_SHA256 = 1 << 4;
+        /// Pre-shared key with SHA-256
+        const PSK_SHA256 = 1 << 5;
+        /// SAE, ie WPA3-Personal
+        const SAE = 1 << 6;
+        /// Fast transition with SAE
+        const FT_SAE = 1 << 7;
+        /// SAE with a group-dependent hash
+        const SAE_EXT_KEY = 1 << 8;
+        /// 802.1X Suite B
+        const EAP_SUITE_B = 1 << 9;
+        /// 802.1X Suite B 192-bit, ie WPA3-Enterprise 192-bit mode
+        const EAP_SUITE_B_192 = 1 << 10;
+        /// Opportunistic Wireless Encryption, ie Enhanced Open
+        const OWE = 1 << 11;
+        /// Device Provisioning Protocol
+        const DPP = 1 << 12;
+        /// FILS with SHA-256
+        const FILS_SHA256 = 1 << 13;
+        /// FILS with SHA-384
+        const FILS_SHA384 = 1 << 14;
+        /// Fast transition with FILS and SHA-256
+        const FT_FILS_SHA256 = 1 << 15;
+        /// Fast transition with FILS and SHA-384
+        const FT_FILS_SHA384 = 1 << 16;
+        /// Fast transition with 802.1X and SHA-384
+        const FT_EAP_SHA384 = 1 << 17;
+        /// OSEN key management
+        const OSEN = 1 << 18;
+    }
+}
+
+bitflags! {
+    /// Pairwise ciphers offered by an access point
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Ciphers: u8 {
+        /// CCMP (AES), 128-bit
+        const CCMP = 1;
+        /// TKIP
+        const TKIP = 1 << 1;
+        /// GCMP, 128-bit
+        const GCMP = 1 << 2;
+        /// CCMP, 256-bit
+        const CCMP_256 = 1 << 3;
+        /// GCMP, 256-bit
+        const GCMP_256 = 1 << 4;
+    }
+}
+
+bitflags! {
+    /// Capabilities of an access point, from flags other than security suites
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Capabilities: u32 {
+        /// An infrastructure network, the `ESS` flag
+        const ESS = 1;
+        /// An ad-hoc network, the `IBSS` flag
+        const IBSS = 1 << 1;
+        /// A mesh network, the `MESH` flag
+        const MESH = 1 << 2;
+        /// WEP encryption, the `WEP` flag
+        const WEP = 1 << 3;
+        /// WPS is enabled, the `WPS` flag or one of its variants
+        const WPS = 1 << 4;
+        /// WPS push button is active, the `WPS-PBC` flag
+        const WPS_PBC = 1 << 5;
+        /// A P2P group owner, the `P2P` flag
+        const P2P = 1 << 6;
+        /// Hotspot 2.0, the `HS20` flag
+        const HS20 = 1 << 7;
+        /// Either network of an open / OWE transition-mode pair, the `OWE-TRANS` or `OWE-TRANS-OPEN` flag
+        const OWE_TRANS = 1 << 8;
+        /// FILS is supported, the `FILS` flag
+        const FILS = 1 << 9;
+        /// Fast session transfer, the `FST` flag
+        const FST = 1 << 10;
+        /// The SSID is UTF-8, the `UTF-8` flag
+        const UTF8_SSID = 1 << 11;
+        /// SAE hash-to-element is supported, the `SAE-H2E` flag
+        const SAE_H2E = 1 << 12;
+        /// SAE public keys are supported, the `SAE-PK` flag
+        const SAE_PK = 1 << 13;
+        /// The open network of an open / OWE transition-mode pair, the `OWE-TRANS-OPEN` flag
+        const OWE_TRANS_OPEN = 1 << 14;
+    }
+}
+
+/// Key management suites by name, as they appear in flags
+///
+/// Names that are prefixes of others come after them.
+const KEY_MGMT_NAMES: &[(&str, KeyMgmt)] = &[
+    ("EAP-SUITE-B-192", KeyMgmt::EAP_SUITE_B_192),
+    ("EAP-SUITE-B", KeyMgmt::EAP_SUITE_B),
+    ("EAP-SHA256", KeyMgmt::EAP_SHA256),
+    ("EAP", KeyMgmt::EAP),
+    ("PSK-SHA256", KeyMgmt::PSK_SHA256),
+    ("PSK", KeyMgmt::PSK),
+    ("FT/EAP-SHA384", KeyMgmt::FT_EAP_SHA384),
+    ("FT/EAP", KeyMgmt::FT_EAP),
+    ("FT/PSK", KeyMgmt::FT_PSK),
+    ("FT/SAE-EXT-KEY", KeyMgmt::SAE_EXT_KEY),
+    ("FT/SAE", KeyMgmt::FT_SAE),
+    ("SAE-EXT-KEY", KeyMgmt::SAE_EXT_KEY),
+    ("SAE", KeyMgmt::SAE),
+    ("OWE", KeyMgmt::OWE),
+    ("DPP", KeyMgmt::DPP),
+    ("FT-FILS-SHA256", KeyMgmt::FT_FILS_SHA256),
+    ("FT-FILS-SHA384", KeyMgmt::FT_FILS_SHA384),
+    ("FILS-SHA256", KeyMgmt::FILS_SHA256),
+    ("FILS-SHA384", KeyMgmt::FILS_SHA384),
+    ("OSEN", KeyMgmt::OSEN),
+];
+
+/// The flags of an access point in scan results, parsed
+///
+/// Flags this type does not know are ignored; they remain available in
+/// `ScanResult::flags`.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::{KeyMgmt, ScanFlags};
+/// let flags = ScanFlags::parse(&["WPA2-PSK+SAE-CCMP", "WPS", "ESS"]);
+/// assert!(flags.key_mgmt.contains(KeyMgmt::PSK | KeyMgmt::SAE));
+/// assert!(flags.is_wpa3());
+/// assert!(!flags.is_open());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanFlags {
+    /// The security protocols offered
+    pub protocols: Protocols,
+    /// The key management suites offered, over all protocols
+    pub key_mgmt: KeyMgmt,
+    /// The pairwise ciphers offered, over all protocols
+    pub ciphers: Ciphers,
+    /// The other capabilities
+    pub capabilities: Capabilities,
+}
+
+impl ScanFlags {
+    /// Parse flags such as `WPA2-PSK-CCMP` and `ESS`, without brackets
+    pub fn parse<'a, I: IntoIterator<Item = &'a S>, S: AsRef<str> + ?Sized + 'a>(flags: I) -> Self {
+        let mut parsed = ScanFlags::default();
+        for flag in flags {
+            parsed.add(flag.as_ref());
+        }
+        parsed
+    }
+
+    fn add(&mut self, flag: &str) {
+        let capability = match flag {
+            "ESS" => Capabilities::ESS,
+            "IBSS" => Capabilities::IBSS,
+            "MESH" => Capabilities::MESH,
+            "WEP" => Capabilities::WEP,
+            "WPS-PBC" => Capabilities::WPS | Capabilities::WPS_PBC,
+            "P2P" => Capabilities::P2P,
+            "HS20" => Capabilities::HS20,
+            "OWE-TRANS" => Capabilities::OWE_TRANS,
+            "OWE-TRANS-OPEN" => Capabilities::OWE_TRANS | Capabilities::OWE_TRANS_OPEN,
+            "FILS" => Capabilities::FILS,
+            "FST" => Capabilities::FST,
+            "UTF-8" => Capabilities::UTF8_SSID,
+            "SAE-H2E" => Capabilities::SAE_H2E,
+            "SAE-PK" => Capabilities::SAE_PK,
+            f if f.starts_with("WPS") => Capabilities::WPS,
+            f => return self.add_suites(f),
+        };
+        self.capabilities |= capability;
+    }
+
+    /// Parse a flag of the form `PROTO-KEYMGMT+KEYMGMT-CIPHER+CIPHER`
+    fn add_suites(&mut self, flag: &str) {
+        let (protocol, mut rest) = match flag.find('-') {
+            Some(i) => (&flag[..i], &flag[i + 1..]),
+            None => return,
+        };
+        self.protocols |= match protocol {
+            "WPA" => Protocols::WPA,
+            "WPA2" | "RSN" => Protocols::RSN,
+            "OSEN" => Protocols::OSEN,
+            _ => return,
+        };
+        rest = rest.strip_suffix("-preauth").unwrap_or(rest);
+        // Key management suites are followed by `+` or `-`, and may be absent
+        while let Some(&(name, key_mgmt)) = KEY_MGMT_NAMES.iter().find(|&&(name, _)| {
+            rest.starts_with(name) && matches!(rest.as_bytes().get(name.len()), Some(b'+') | Some(b'-'))
+        }) {
+            self.key_mgmt |= key_mgmt;
+            rest = &rest[name.len() + 1..];
+        }
+        let rest = rest.trim_start_matches(['?', '-']);
+        for cipher in rest.split('+') {
+            self.ciphers |= match cipher {
+                "CCMP" => Ciphers::CCMP,
+                "TKIP" => Ciphers::TKIP,
+                "GCMP" => Ciphers::GCMP,
+                "CCMP-256" => Ciphers::CCMP_256,
+                "GCMP-256" => Ciphers::GCMP_256,
+                _ => Ciphers::empty(),
+            };
+        }
+    }
+
+    /// Whether the network is unencrypted, ignoring OWE transition mode
+    pub fn is_open(&self) -> bool {
+        self.protocols.is_empty() && !self.capabilities.contains(Capabilities::WEP)
+    }
+
+    /// Whether the network uses WEP
+    pub fn is_wep(&self) -> bool {
+        self.capabilities.contains(Capabilities::WEP)
+    }
+
+    /// Whether the network offers WPA3, ie SAE or 802.1X Suite B 192-bit
+    pub fn is_wpa3(&self) -> bool {
+        self.key_mgmt.intersects(KeyMgmt::SAE | KeyMgmt::FT_SAE | KeyMgmt::SAE_EXT_KEY | KeyMgmt::EAP_SUITE_B_192)
+    }
+
+    /// Whether the network uses 802.1X (enterprise) authentication
+    pub fn is_enterprise(&self) -> bool {
+        self.key_mgmt.intersects(KeyMgmt::EAP | KeyMgmt::FT_EAP | KeyMgmt::EAP_SHA256 | KeyMgmt::EAP_SUITE_B |
+                                 KeyMgmt::EAP_SUITE_B_192 | KeyMgmt::FT_EAP_SHA384 | KeyMgmt::FILS_SHA256 |
+                                 KeyMgmt::FILS_SHA384 | KeyMgmt::FT_FILS_SHA256 | KeyMgmt::FT_FILS_SHA384)
+    }
+
+    /// Whether the network offers Enhanced Open (OWE)
+    pub fn is_owe(&self) -> bool {
+        self.key_mgmt.contains(KeyMgmt::OWE)
+    }
+
+    /// Whether Enhanced Open can be used, directly or through the OWE network paired with this one
+    pub fn supports_owe(&self) -> bool {
+        self.is_owe() || self.capabilities.contains(Capabilities::OWE_TRANS_OPEN)
+    }
+
+    /// Whether the network offers fast transition (802.11r) for roaming between its access points
+    pub fn supports_ft(&self) -> bool {
+        self.key_mgmt.intersects(KeyMgmt::FT_EAP | KeyMgmt::FT_PSK | KeyMgmt::FT_SAE | KeyMgmt::FT_EAP_SHA384 |
+                                 KeyMgmt::FT_FILS_SHA256 | KeyMgmt::FT_FILS_SHA384)
+    }
+
+    /// Whether WPS is enabled
+    pub fn has_wps(&self) -> bool {
+        self.capabilities.contains(Capabilities::WPS)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn suites() {
+        let flags = ScanFlags::parse(&["WPA-PSK-TKIP", "WPA2-PSK-CCMP+TKIP", "ESS"]);
+        assert_eq!(flags.protocols, Protocols::WPA | Protocols::RSN);
+        assert_eq!(flags.key_mgmt, KeyMgmt::PSK);
+        assert_eq!(flags.ciphers, Ciphers::CCMP | Ciphers::TKIP);
+        assert_eq!(flags.capabilities, Capabilities::ESS);
+        assert!(!flags.is_wpa3() && !flags.is_open() && !flags.is_enterprise() && !flags.supports_ft());
+
+        let flags = ScanFlags::parse(&["WPA2-EAP+FT/EAP-CCMP-preauth"]);
+        assert_eq!(flags.key_mgmt, KeyMgmt::EAP | KeyMgmt::FT_EAP);
+        assert_eq!(flags.ciphers, Ciphers::CCMP);
+        assert!(flags.is_enterprise() && flags.supports_ft());
+
+        let flags = ScanFlags::parse(&["RSN-EAP-SUITE-B-192-GCMP-256"]);
+        assert_eq!(flags.key_mgmt, KeyMgmt::EAP_SUITE_B_192);
+        assert_eq!(flags.ciphers, Ciphers::GCMP_256);
+        assert!(flags.is_wpa3());
+
+        let flags = ScanFlags::parse(&["WPA2-SAE+FT/SAE-CCMP", "SAE-H2E"]);
+        assert_eq!(flags.key_mgmt, KeyMgmt::SAE | KeyMgmt::FT_SAE);
+        assert!(flags.supports_ft());
+        assert_eq!(flags.capabilities, Capabilities::SAE_H2E);
+
+        let flags = ScanFlags::parse(&["WPA2--CCMP"]);
+        assert_eq!(flags.key_mgmt, KeyMgmt::empty());
+        assert_eq!(flags.ciphers, Ciphers::CCMP);
+    }
+
+    #[test]
+    fn capabilities() {
+        let flags = ScanFlags::parse(&["ESS"]);
+        assert!(flags.is_open());
+        let flags = ScanFlags::parse(&["WEP", "ESS"]);
+        assert!(flags.is_wep() && !flags.is_open());
+        let flags = ScanFlags::parse(&["WPA2-OWE-CCMP", "WPS-PBC", "OWE-TRANS", "UTF-8", "BOGUS"]);
+        assert!(flags.is_owe() && flags.supports_owe() && flags.has_wps());
+        assert_eq!(flags.capabilities,
+                   Capabilities::WPS | Capabilities::WPS_PBC | Capabilities::OWE_TRANS | Capabilities::UTF8_SSID);
+        let flags = ScanFlags::parse(&["ESS", "OWE-TRANS-OPEN"]);
+        assert!(flags.is_open() && !flags.is_owe() && flags.supports_owe());
+        assert!(!ScanFlags::parse(&["ESS"]).supports_owe());
+    }
+}
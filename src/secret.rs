@@ -0,0 +1,224 @@
+use std::borrow::Cow;
+#[cfg(feature = "zeroize")]
+use std::ptr;
+#[cfg(feature = "zeroize")]
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Shown in place of a credential
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// Whether a network or credential variable holds a secret
+pub(crate) fn is_secret(key: &str) -> bool {
+    matches!(key, "psk" | "sae_password" | "password" | "private_key_passwd" | "private_key2_passwd" |
+             "pin" | "pin2" | "mka_cak" | "wep_key0" | "wep_key1" | "wep_key2" | "wep_key3")
+}
+
+/// Whether a hostapd configuration parameter holds a secret
+fn is_hostapd_secret(key: &str) -> bool {
+    matches!(key, "wpa_passphrase" | "wpa_psk" | "sae_password" | "auth_server_shared_secret" |
+             "acct_server_shared_secret" | "ap_pin")
+}
+
+/// Replace credentials in a command with `[REDACTED]`, so it can be logged
+///
+/// This covers secret network and credential variables, secret hostapd
+/// parameters, blobs, WPS and P2P PINs (including those of the external
+/// registrar), PMKs, replies to credential requests (`CTRL-RSP-PASSWORD-...` etc),
+/// and `pass=`, `psk=` and `key=` arguments such as those of DPP commands.
+/// Commands without credentials are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::redact;
+/// assert_eq!(redact("SET_NETWORK 0 psk \"hunter2\""), "SET_NETWORK 0 psk [REDACTED]");
+/// assert_eq!(redact("SET_NETWORK 0 ssid \"home\""), "SET_NETWORK 0 ssid \"home\"");
+/// ```
+pub fn redact(cmd: &str) -> Cow<'_, str> {
+    let words: Vec<&str> = cmd.split(' ').collect();
+    let keep = match words[0] {
+        "SET_NETWORK" | "SET_CRED" if words.get(2).is_some_and(|key| is_secret(key)) => 3,
+        "SET" if words.get(1) == Some(&"blob") => 3,
+        "SET" if words.get(1).is_some_and(|key| is_secret(key) || is_hostapd_secret(key)) => 2,
+        "WPS_PIN" | "WPS_REG" | "WPS_ER_PIN" | "WPS_AP_PIN" | "WPS_ER_LEARN" | "WPS_ER_CONFIG" => 2,
+        // The PIN follows the peer address, unless a method such as `pbc` is given instead
+        "P2P_CONNECT" if words.get(2).is_some_and(|pin| pin.bytes().all(|b| b.is_ascii_digit())) => {
+            let mut words = words;
+            words[2] = REDACTED;
+            return Cow::Owned(words.join(" "));
+        }
+        "PMKSA_ADD" => 1,
+        name if name.starts_with("CTRL-RSP-") => {
+            return match cmd.find(':') {
+                Some(colon) => Cow::Owned(format!("{}:{}", &cmd[..colon], REDACTED)),
+                None => Cow::Borrowed(cmd),
+            };
+        }
+        _ => {
+            let secret_arg = |word: &&str| ["pass=", "psk=", "key="].iter().any(|p| word.starts_with(p));
+            if !words.iter().any(secret_arg) {
+                return Cow::Borrowed(cmd);
+            }
+            let words: Vec<String> = words.iter().map(|word| match word.find('=') {
+                Some(eq) if secret_arg(word) => format!("{}={}", &word[..eq], REDACTED),
+                _ => (*word).to_owned(),
+            }).collect();
+            return Cow::Owned(words.join(" "));
+        }
+    };
+    if words.len() <= keep {
+        Cow::Borrowed(cmd)
+    } else {
+        Cow::Owned(format!("{} {}", words[..keep].join(" "), REDACTED))
+    }
+}
+
+/// The position and length of the first `passphrase=` or `psk=` argument name in `s`
+fn find_secret_arg(s: &str) -> Option<(usize, usize)> {
+    ["passphrase=", "psk="].iter()
+        .filter_map(|arg| {
+            let at_word = |&(i, _): &(usize, &str)| i == 0 || matches!(s.as_bytes()[i - 1], b' ' | b'\n');
+            s.match_indices(arg).find(at_word).map(|(i, _)| (i, arg.len()))
+        })
+        .min()
+}
+
+/// Replace credentials in an event or reply with `[REDACTED]`, so it can be logged
+///
+/// This covers `passphrase=` and `psk=` arguments, such as those of
+/// `P2P-GROUP-STARTED`, the `key=` of `WPS-ER-AP-SETTINGS` and the
+/// credentials of DPP configuration objects.
+pub(crate) fn redact_event(msg: &str) -> Cow<'_, str> {
+    for marker in ["DPP-CONFOBJ-PASS ", "DPP-CONFOBJ-PSK "] {
+        if let Some(i) = msg.find(marker) {
+            return Cow::Owned(format!("{}{}", &msg[..i + marker.len()], REDACTED));
+        }
+    }
+    if msg.contains("WPS-ER-AP-SETTINGS ") {
+        if let Some(i) = msg.find(" key=") {
+            return Cow::Owned(format!("{} key={}", &msg[..i], REDACTED));
+        }
+    }
+    let mut redacted = String::new();
+    let mut rest = msg;
+    while let Some((start, len)) = find_secret_arg(rest) {
+        let value = &rest[start + len..];
+        // Quoted values may contain spaces
+        let end = match value.strip_prefix('"') {
+            Some(quoted) => quoted.find('"').map_or(value.len(), |i| i + 2),
+            None => value.find([' ', '\n']).unwrap_or(value.len()),
+        };
+        redacted.push_str(&rest[..start + len]);
+        redacted.push_str(REDACTED);
+        rest = &value[end..];
+    }
+    if redacted.is_empty() {
+        return Cow::Borrowed(msg);
+    }
+    redacted.push_str(rest);
+    Cow::Owned(redacted)
+}
+
+/// Replace credentials in the reply to `cmd` with `[REDACTED]`, so it can be logged
+///
+/// Replies to reading a secret variable are redacted entirely, as are the
+/// PINs returned by `WPS_PIN` and `WPS_AP_PIN` and the NFC tokens, which
+/// carry the network key.
+pub(crate) fn redact_reply<'a>(cmd: &str, reply: &'a str) -> Cow<'a, str> {
+    let words: Vec<&str> = cmd.split(' ').collect();
+    let secret = match words[0] {
+        "GET_NETWORK" | "GET_CRED" => words.get(2).is_some_and(|key| is_secret(key)),
+        "GET" => words.get(1).is_some_and(|key| is_secret(key) || is_hostapd_secret(key)),
+        "P2P_GET_PASSPHRASE" | "WPS_PIN" | "WPS_AP_PIN" | "WPS_NFC_TOKEN" | "WPS_NFC_CONFIG_TOKEN" => true,
+        _ => false,
+    };
+    if secret {
+        Cow::Borrowed(REDACTED)
+    } else {
+        redact_event(reply)
+    }
+}
+
+/// Overwrite the contents of a string with zeros, then clear it
+///
+/// The writes are volatile so they are not optimized away even though the
+/// string is about to be dropped. Copies left behind by earlier
+/// reallocations are out of reach.
+#[cfg(feature = "zeroize")]
+pub(crate) fn wipe(s: &mut String) {
+    // Zero bytes are valid UTF-8, so the string stays valid throughout
+    for b in unsafe { s.as_mut_vec() }.iter_mut() {
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+    s.clear();
+}
+
+/// Without the `zeroize` feature, strings are left as they are
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn wipe(_: &mut String) {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn redact() {
+        assert_eq!(super::redact("SET_NETWORK 0 sae_password \"a b\""), "SET_NETWORK 0 sae_password [REDACTED]");
+        assert_eq!(super::redact("SET_NETWORK 0 key_mgmt SAE"), "SET_NETWORK 0 key_mgmt SAE");
+        assert_eq!(super::redact("SET blob ca 3082"), "SET blob ca [REDACTED]");
+        assert_eq!(super::redact("SET wpa_passphrase hunter2"), "SET wpa_passphrase [REDACTED]");
+        assert_eq!(super::redact("SET ssid home"), "SET ssid home");
+        assert_eq!(super::redact("WPS_PIN any"), "WPS_PIN any");
+        assert_eq!(super::redact("WPS_PIN any 12345670"), "WPS_PIN any [REDACTED]");
+        assert_eq!(super::redact("WPS_ER_LEARN 02:00:00:00:01:00 12345670"),
+                   "WPS_ER_LEARN 02:00:00:00:01:00 [REDACTED]");
+        assert_eq!(super::redact("WPS_ER_CONFIG 2050cd42 12345670 686f6d65 WPA2PSK CCMP 6869"),
+                   "WPS_ER_CONFIG 2050cd42 [REDACTED]");
+        assert_eq!(super::redact("P2P_CONNECT 02:00:00:00:01:00 12345670 display go_intent=15"),
+                   "P2P_CONNECT 02:00:00:00:01:00 [REDACTED] display go_intent=15");
+        assert_eq!(super::redact("P2P_CONNECT 02:00:00:00:01:00 pbc"), "P2P_CONNECT 02:00:00:00:01:00 pbc");
+        assert_eq!(super::redact("PMKSA_ADD 0 02:00:00:00:01:00 00 11 1 2 2 0"), "PMKSA_ADD [REDACTED]");
+        assert_eq!(super::redact("CTRL-RSP-PASSWORD-1:hunter2"), "CTRL-RSP-PASSWORD-1:[REDACTED]");
+        assert_eq!(super::redact("DPP_AUTH_INIT peer=1 ssid=68 pass=6869"), "DPP_AUTH_INIT peer=1 ssid=68 pass=[REDACTED]");
+        assert_eq!(super::redact("PING"), "PING");
+        assert!(is_secret("psk") && !is_secret("ssid"));
+    }
+
+    #[test]
+    fn redact_event() {
+        assert_eq!(super::redact_event("<3>P2P-GROUP-STARTED p2p-wlan0-0 GO ssid=\"DIRECT-ab\" freq=2412 \
+                                        passphrase=\"a b\" go_dev_addr=02:00:00:00:01:00"),
+                   "<3>P2P-GROUP-STARTED p2p-wlan0-0 GO ssid=\"DIRECT-ab\" freq=2412 \
+                    passphrase=[REDACTED] go_dev_addr=02:00:00:00:01:00");
+        assert_eq!(super::redact_event("<3>P2P-GROUP-STARTED p2p-wlan0-0 client ssid=\"x\" freq=2412 psk=00ff"),
+                   "<3>P2P-GROUP-STARTED p2p-wlan0-0 client ssid=\"x\" freq=2412 psk=[REDACTED]");
+        assert_eq!(super::redact_event("<3>DPP-CONFOBJ-PASS 736563726574"), "<3>DPP-CONFOBJ-PASS [REDACTED]");
+        assert_eq!(super::redact_event("<3>WPS-ER-AP-SETTINGS uuid=2050cd42 ssid=home key=a secret"),
+                   "<3>WPS-ER-AP-SETTINGS uuid=2050cd42 ssid=home key=[REDACTED]");
+        assert_eq!(super::redact_event("<3>CTRL-EVENT-SCAN-STARTED "), "<3>CTRL-EVENT-SCAN-STARTED ");
+        assert_eq!(super::redact_event("wpa_state=COMPLETED\nkey_mgmt=WPA2-PSK\n"),
+                   "wpa_state=COMPLETED\nkey_mgmt=WPA2-PSK\n");
+        assert_eq!(super::redact_event("mode=P2P GO\npassphrase=hunter2\nssid=x\n"),
+                   "mode=P2P GO\npassphrase=[REDACTED]\nssid=x\n");
+        assert_eq!(redact_reply("GET_NETWORK 0 psk", "\"hunter2\"\n"), "[REDACTED]");
+        assert_eq!(redact_reply("GET wpa_passphrase", "hunter2\n"), "[REDACTED]");
+        assert_eq!(redact_reply("GET_NETWORK 0 ssid", "\"home\"\n"), "\"home\"\n");
+        assert_eq!(redact_reply("WPS_PIN any", "12345670\n"), "[REDACTED]");
+        assert_eq!(redact_reply("WPS_AP_PIN random 300", "12345670\n"), "[REDACTED]");
+        assert_eq!(redact_reply("WPS_NFC_TOKEN NDEF", "d20200\n"), "[REDACTED]");
+        assert_eq!(redact_reply("WPS_NFC_CONFIG_TOKEN NDEF 0", "d20200\n"), "[REDACTED]");
+        assert_eq!(redact_reply("WPS_PBC", "OK\n"), "OK\n");
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn wipe() {
+        let mut s = String::from("hunter2");
+        super::wipe(&mut s);
+        assert!(s.is_empty());
+        assert!(s.capacity() >= 7);
+        unsafe { s.as_mut_vec().set_len(7) };
+        assert_eq!(s, "\0\0\0\0\0\0\0");
+    }
+}
@@ -0,0 +1,163 @@
+use network::NetworkValue;
+use parse::{hex_decode, hex_encode};
+use std::fmt;
+
+/// An SSID as raw bytes, which need not be valid UTF-8
+///
+/// wpasupplicant reports SSIDs in events, scan results and network lists
+/// in a printf-escaped form (eg `my\x20net` or `caf\xc3\xa9`), and accepts
+/// them hex-encoded in `SET_NETWORK ssid`. This converts between these
+/// forms without losing any bytes.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::Ssid;
+/// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+/// for bss in wpa.scan_results().unwrap() {
+///     let ssid = Ssid::from_escaped(&bss.ssid);
+///     println!("{} ({} bytes)", ssid, ssid.as_bytes().len());
+///     let id = wpa.add_network().unwrap();
+///     wpa.set_network(id, "ssid", ssid).unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ssid(Vec<u8>);
+
+impl Ssid {
+    /// An SSID from its raw bytes
+    pub fn new<B: Into<Vec<u8>>>(bytes: B) -> Self {
+        Ssid(bytes.into())
+    }
+
+    /// Decode the printf-escaped form used in events and scan results
+    pub fn from_escaped(s: &str) -> Self {
+        let bytes = s.as_bytes();
+        let mut ssid = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] != b'\\' || i + 1 == bytes.len() {
+                ssid.push(bytes[i]);
+                i += 1;
+                continue;
+            }
+            i += 1;
+            match bytes[i] {
+                b'n' => ssid.push(b'\n'),
+                b'r' => ssid.push(b'\r'),
+                b't' => ssid.push(b'\t'),
+                b'e' => ssid.push(0x1b),
+                b'x' => {
+                    let hex = s.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok());
+                    match hex {
+                        Some(b) => {
+                            ssid.push(b);
+                            i += 2;
+                        }
+                        None => ssid.extend_from_slice(b"\\x"),
+                    }
+                }
+                b'0'..=b'7' => {
+                    let len = bytes[i..].iter().take(3).take_while(|b| (b'0'..=b'7').contains(b)).count();
+                    ssid.push(u8::from_str_radix(&s[i..i + len], 8).unwrap_or(0));
+                    i += len - 1;
+                }
+                other => ssid.push(other),
+            }
+            i += 1;
+        }
+        Ssid(ssid)
+    }
+
+    /// Decode the hex form, as accepted by `SET_NETWORK ssid`
+    ///
+    /// Returns None if `s` is not valid hex.
+    pub fn from_hex(s: &str) -> Option<Self> {
+        hex_decode(s).map(Ssid)
+    }
+
+    /// The raw bytes of the SSID
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The printf-escaped form, as used in events and scan results
+    pub fn escaped(&self) -> String {
+        let mut s = String::with_capacity(self.0.len());
+        for &b in &self.0 {
+            match b {
+                b'"' => s.push_str("\\\""),
+                b'\\' => s.push_str("\\\\"),
+                b'\n' => s.push_str("\\n"),
+                b'\r' => s.push_str("\\r"),
+                b'\t' => s.push_str("\\t"),
+                0x1b => s.push_str("\\e"),
+                0x20..=0x7e => s.push(b as char),
+                _ => s.push_str(&format!("\\x{:02x}", b)),
+            }
+        }
+        s
+    }
+
+    /// The hex form, as accepted by `SET_NETWORK ssid`
+    pub fn hex(&self) -> String {
+        hex_encode(&self.0)
+    }
+}
+
+/// Shows the SSID as UTF-8, replacing invalid sequences
+impl fmt::Display for Ssid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.0))
+    }
+}
+
+impl<'a> From<&'a str> for Ssid {
+    fn from(s: &'a str) -> Self {
+        Ssid(s.as_bytes().to_vec())
+    }
+}
+
+impl<'a> From<&'a [u8]> for Ssid {
+    fn from(bytes: &'a [u8]) -> Self {
+        Ssid(bytes.to_vec())
+    }
+}
+
+/// Sends the SSID hex-encoded, so any bytes survive
+impl From<Ssid> for NetworkValue {
+    fn from(ssid: Ssid) -> Self {
+        NetworkValue::Raw(ssid.hex())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn escaped() {
+        let ssid = Ssid::new(&b"caf\xc3\xa9 \"quoted\"\\\n\x01"[..]);
+        assert_eq!(ssid.escaped(), "caf\\xc3\\xa9 \\\"quoted\\\"\\\\\\n\\x01");
+        assert_eq!(Ssid::from_escaped(&ssid.escaped()), ssid);
+        assert_eq!(ssid.to_string(), "café \"quoted\"\\\n\u{1}");
+    }
+
+    #[test]
+    fn from_escaped() {
+        assert_eq!(Ssid::from_escaped("plain").as_bytes(), b"plain");
+        assert_eq!(Ssid::from_escaped("\\e\\t\\101").as_bytes(), b"\x1b\tA");
+        assert_eq!(Ssid::from_escaped("bad\\xzz").as_bytes(), b"bad\\xzz");
+        assert_eq!(Ssid::from_escaped("trailing\\").as_bytes(), b"trailing\\");
+    }
+
+    #[test]
+    fn hex() {
+        let ssid = Ssid::from("home");
+        assert_eq!(ssid.hex(), "686f6d65");
+        assert_eq!(Ssid::from_hex("686f6d65"), Some(ssid.clone()));
+        assert_eq!(Ssid::from_hex("68z"), None);
+        assert_eq!(NetworkValue::from(ssid), NetworkValue::Raw("686f6d65".into()));
+    }
+}
@@ -0,0 +1,188 @@
+use error::Error;
+use hostapd::{HostapdCtrl, Station};
+use macaddr::MacAddr;
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+use super::Result;
+
+/// The stations of an access point at one point in time, taken by a `StationMonitor`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StationSnapshot {
+    /// When the stations were read
+    pub time: SystemTime,
+    /// The associated stations, with their signal, rates and airtime
+    pub stations: Vec<Station>,
+}
+
+/// A report from a `StationMonitor`
+#[derive(Debug)]
+pub enum StationReport {
+    /// The stations were read
+    Snapshot(StationSnapshot),
+    /// The signal of a station dropped below the threshold
+    Weak {
+        /// The station
+        addr: MacAddr,
+        /// Its signal strength in dBm
+        signal: i32,
+    },
+    /// The signal of a weak station is at or above the threshold again
+    Recovered {
+        /// The station
+        addr: MacAddr,
+        /// Its signal strength in dBm
+        signal: i32,
+    },
+    /// Reading the stations failed
+    Failed(Error),
+}
+
+/// Tracks which stations are weak, to report only threshold crossings
+#[derive(Debug)]
+struct Thresholds {
+    weak_below: i32,
+    weak: HashSet<MacAddr>,
+}
+
+impl Thresholds {
+    /// The crossings of the threshold since the previous snapshot
+    ///
+    /// Stations that left are forgotten, so they are reported again if they
+    /// return weak. Stations that do not report a signal keep their state.
+    fn crossings(&mut self, snapshot: &StationSnapshot) -> Vec<StationReport> {
+        self.weak.retain(|addr| snapshot.stations.iter().any(|station| station.addr == *addr));
+        snapshot.stations.iter().filter_map(|station| {
+            let signal = station.signal?;
+            if signal < self.weak_below && self.weak.insert(station.addr) {
+                Some(StationReport::Weak { addr: station.addr, signal })
+            } else if signal >= self.weak_below && self.weak.remove(&station.addr) {
+                Some(StationReport::Recovered { addr: station.addr, signal })
+            } else {
+                None
+            }
+        }).collect()
+    }
+}
+
+/// A background thread that periodically reads the stations of an access point
+///
+/// Created by `HostapdCtrl::monitor_stations`.
+pub struct StationMonitor {
+    stop: Sender<()>,
+    thread: JoinHandle<HostapdCtrl>,
+}
+
+impl StationMonitor {
+    /// Stop monitoring and return the connection used to read the stations
+    pub fn stop(self) -> HostapdCtrl {
+        let _ = self.stop.send(());
+        match self.thread.join() {
+            Ok(hostapd) => hostapd,
+            Err(e) => ::std::panic::resume_unwind(e),
+        }
+    }
+}
+
+impl HostapdCtrl {
+    /// Read all associated stations at once
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let snapshot = hostapd.station_snapshot().unwrap();
+    /// for station in snapshot.stations {
+    ///     println!("{}: {:?} dBm", station.addr, station.signal);
+    /// }
+    /// ```
+    pub fn station_snapshot(&mut self) -> Result<StationSnapshot> {
+        let time = SystemTime::now();
+        let stations = self.stations().collect::<Result<_>>()?;
+        Ok(StationSnapshot { time, stations })
+    }
+
+    /// Read the stations every `interval` on a background thread
+    ///
+    /// `f` gets each snapshot, followed by a `StationReport::Weak` for each
+    /// station whose signal dropped below `weak_below` dBm and a
+    /// `StationReport::Recovered` for each that is at or above it again.
+    /// Since the connection is moved to the background thread, use a
+    /// separate connection for other commands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wpactrl::StationReport;
+    /// let hostapd = wpactrl::WpaCtrl::new().open_hostapd().unwrap();
+    /// let monitor = hostapd.monitor_stations(Duration::from_secs(10), -75, |report| match report {
+    ///     StationReport::Weak { addr, signal } => println!("{} is weak ({} dBm)", addr, signal),
+    ///     StationReport::Failed(e) => eprintln!("unable to read stations: {}", e),
+    ///     _ => (),
+    /// });
+    /// let hostapd = monitor.stop();
+    /// ```
+    pub fn monitor_stations<F>(mut self, interval: Duration, weak_below: i32, mut f: F) -> StationMonitor
+        where F: FnMut(StationReport) + Send + 'static {
+        let (stop, stopped) = channel();
+        let thread = thread::spawn(move || {
+            let mut thresholds = Thresholds { weak_below, weak: HashSet::new() };
+            loop {
+                match self.station_snapshot() {
+                    Ok(snapshot) => {
+                        let crossings = thresholds.crossings(&snapshot);
+                        f(StationReport::Snapshot(snapshot));
+                        crossings.into_iter().for_each(&mut f);
+                    }
+                    Err(e) => f(StationReport::Failed(e)),
+                }
+                match stopped.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    _ => return self,
+                }
+            }
+        });
+        StationMonitor { stop, thread }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot(stations: &[(&str, Option<i32>)]) -> StationSnapshot {
+        let stations = stations.iter().map(|&(addr, signal)| Station {
+            addr: addr.parse().unwrap(),
+            flags: Vec::new(),
+            rx_bytes: None,
+            tx_bytes: None,
+            signal,
+            tx_rate: None,
+            rx_rate: None,
+            tx_airtime: None,
+            rx_airtime: None,
+            connected_time: None,
+            raw: String::new(),
+        }).collect();
+        StationSnapshot { time: SystemTime::now(), stations }
+    }
+
+    #[test]
+    fn crossings() {
+        let (a, b) = ("02:00:00:00:00:01", "02:00:00:00:00:02");
+        let mut thresholds = Thresholds { weak_below: -75, weak: HashSet::new() };
+        let reports = thresholds.crossings(&snapshot(&[(a, Some(-80)), (b, Some(-60))]));
+        assert!(matches!(reports[..], [StationReport::Weak { signal: -80, .. }]));
+        assert_eq!(thresholds.crossings(&snapshot(&[(a, Some(-82)), (b, Some(-76))])).len(), 1);
+        assert!(thresholds.crossings(&snapshot(&[(a, None), (b, Some(-76))])).is_empty());
+        let reports = thresholds.crossings(&snapshot(&[(a, Some(-75)), (b, Some(-70))]));
+        assert!(matches!(reports[..], [StationReport::Recovered { signal: -75, .. },
+                                       StationReport::Recovered { signal: -70, .. }]));
+        thresholds.crossings(&snapshot(&[(a, Some(-90))]));
+        thresholds.crossings(&snapshot(&[]));
+        assert!(thresholds.weak.is_empty());
+    }
+}
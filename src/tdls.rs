@@ -0,0 +1,104 @@
+use error::Error;
+use macaddr::MacAddr;
+use super::Result;
+use wpactrl::{expect_ok, WpaCtrl};
+
+/// The state of a TDLS direct link, as shown by `TDLS_LINK_STATUS`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TdlsStatus {
+    /// The direct link is established
+    Connected,
+    /// The peer is known but the link is not set up
+    NotConnected,
+    /// No such peer is known
+    NoPeer,
+    /// TDLS is disabled or not supported by the driver
+    Disabled,
+    /// Any other status, verbatim
+    Other(String),
+}
+
+impl TdlsStatus {
+    /// Parse the reply to `TDLS_LINK_STATUS`
+    fn parse(reply: &str) -> Result<Self> {
+        let status = reply.trim_end_matches('\n').strip_prefix("TDLS link status: ")
+            .ok_or_else(|| Error::Parse(reply.to_owned()))?;
+        Ok(match status {
+            "connected" => TdlsStatus::Connected,
+            "peer not connected" => TdlsStatus::NotConnected,
+            "peer does not exist" => TdlsStatus::NoPeer,
+            "disabled" => TdlsStatus::Disabled,
+            other => TdlsStatus::Other(other.to_owned()),
+        })
+    }
+}
+
+impl WpaCtrl {
+    /// Send a TDLS discovery request to a station in the same BSS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.tdls_discover("02:00:00:00:01:00".parse().unwrap()).unwrap();
+    /// ```
+    pub fn tdls_discover(&mut self, addr: MacAddr) -> Result<()> {
+        expect_ok(&self.request(&format!("TDLS_DISCOVER {}", addr))?)
+    }
+
+    /// Set up a TDLS direct link with a station in the same BSS
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.tdls_setup("02:00:00:00:01:00".parse().unwrap()).unwrap();
+    /// ```
+    pub fn tdls_setup(&mut self, addr: MacAddr) -> Result<()> {
+        expect_ok(&self.request(&format!("TDLS_SETUP {}", addr))?)
+    }
+
+    /// Tear down the TDLS direct link with a station
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.tdls_teardown("02:00:00:00:01:00".parse().unwrap()).unwrap();
+    /// ```
+    pub fn tdls_teardown(&mut self, addr: MacAddr) -> Result<()> {
+        expect_ok(&self.request(&format!("TDLS_TEARDOWN {}", addr))?)
+    }
+
+    /// The state of the TDLS direct link with a station
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::TdlsStatus;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// if wpa.tdls_link_status("02:00:00:00:01:00".parse().unwrap()).unwrap() == TdlsStatus::Connected {
+    ///     println!("Direct link is up");
+    /// }
+    /// ```
+    pub fn tdls_link_status(&mut self, addr: MacAddr) -> Result<TdlsStatus> {
+        TdlsStatus::parse(&self.request(&format!("TDLS_LINK_STATUS {}", addr))?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn status() {
+        assert_eq!(TdlsStatus::parse("TDLS link status: connected\n").unwrap(), TdlsStatus::Connected);
+        assert_eq!(TdlsStatus::parse("TDLS link status: peer not connected\n").unwrap(), TdlsStatus::NotConnected);
+        assert_eq!(TdlsStatus::parse("TDLS link status: peer does not exist\n").unwrap(), TdlsStatus::NoPeer);
+        assert_eq!(TdlsStatus::parse("TDLS link status: disabled\n").unwrap(), TdlsStatus::Disabled);
+        assert_eq!(TdlsStatus::parse("TDLS link status: setting up\n").unwrap(),
+                   TdlsStatus::Other("setting up".into()));
+        assert!(TdlsStatus::parse("FAIL\n").is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! An in-process stand-in for wpasupplicant, for testing code that uses this crate
+//!
+//! Enabled by the `testing` feature.
+use record::{parse_recording, Record};
+use secret::redact;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// State shared between a `MockSupplicant` and its thread
+#[derive(Default)]
+struct State {
+    replies: HashMap<String, Vec<u8>>,
+    commands: Vec<String>,
+    monitors: Vec<PathBuf>,
+    script: Option<VecDeque<Exchange>>,
+}
+
+/// A recorded command with its reply, and the events received around it
+#[derive(Debug, Default)]
+struct Exchange {
+    command: String,
+    reply: Vec<u8>,
+    /// Events received while waiting for the reply
+    before: Vec<Vec<u8>>,
+    /// Events received after the reply, until the next command
+    after: Vec<Vec<u8>>,
+}
+
+/// Group the records of a recording by command
+///
+/// Events recorded before the first command cannot be replayed, as no
+/// connection is attached yet, and are dropped.
+fn exchanges(records: Vec<Record>) -> VecDeque<Exchange> {
+    let mut exchanges = VecDeque::new();
+    let mut current: Option<Exchange> = None;
+    for record in records {
+        match record {
+            Record::Command(command) => {
+                exchanges.extend(current.take());
+                current = Some(Exchange { command, ..Exchange::default() });
+            }
+            Record::Reply(reply) => {
+                if let Some(ref mut exchange) = current {
+                    exchange.reply = reply;
+                }
+            }
+            Record::Event(msg) => {
+                if let Some(ref mut exchange) = current {
+                    if exchange.reply.is_empty() {
+                        exchange.before.push(msg);
+                    } else {
+                        exchange.after.push(msg);
+                    }
+                }
+            }
+        }
+    }
+    exchanges.extend(current);
+    exchanges
+}
+
+/// A fake wpasupplicant control socket that answers scripted commands
+///
+/// Replies are looked up by the exact command. `PING` is answered with
+/// `PONG`, `ATTACH` and `DETACH` with `OK`, and any other command without
+/// a scripted reply with `UNKNOWN COMMAND`. Attached connections receive
+/// the events injected with `event`. The socket is removed when the mock
+/// is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::testing::MockSupplicant;
+/// use wpactrl::WpaCtrl;
+/// let path = std::env::temp_dir().join(format!("mock_wlan0_{}", std::process::id()));
+/// let mock = MockSupplicant::new(&path).unwrap();
+/// mock.reply("STATUS", "wpa_state=COMPLETED\nssid=home\n");
+/// let mut wpa = WpaCtrl::new().ctrl_path(&path).open().unwrap().attach().unwrap();
+/// assert_eq!(wpa.request("STATUS").unwrap(), "wpa_state=COMPLETED\nssid=home\n");
+/// mock.event("<3>CTRL-EVENT-DISCONNECTED bssid=02:00:00:00:01:00 reason=3");
+/// # while wpa.recv().unwrap().is_none() {}
+/// assert_eq!(mock.commands(), vec!["ATTACH", "STATUS"]);
+/// ```
+pub struct MockSupplicant {
+    path: PathBuf,
+    socket: UnixDatagram,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockSupplicant {
+    /// Bind a control socket at `path`, replacing any stale socket there
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::with_state(path.as_ref(), State::default())
+    }
+
+    /// Bind a control socket at `path` that replays a recording
+    ///
+    /// The recording is one written by `WpaCtrlBuilder::record`. Commands
+    /// are answered in the recorded order, each with its recorded reply,
+    /// and the events recorded around it are sent to attached connections.
+    /// A command that differs from the next recorded one, once credentials
+    /// are redacted, is answered with `FAIL` and does not advance the
+    /// replay; so is any command after the end of the recording. `commands`
+    /// shows where a replay diverged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use wpactrl::testing::MockSupplicant;
+    /// use wpactrl::WpaCtrl;
+    /// let mock = MockSupplicant::replay("/tmp/mock_wlan0", "router.rec").unwrap();
+    /// let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+    /// println!("{}", wpa.request("STATUS").unwrap());
+    /// ```
+    pub fn replay<P: AsRef<Path>, R: AsRef<Path>>(path: P, recording: R) -> io::Result<Self> {
+        let records = parse_recording(&::std::fs::read_to_string(recording)?)?;
+        let state = State { script: Some(exchanges(records)), ..State::default() };
+        Self::with_state(path.as_ref(), state)
+    }
+
+    fn with_state(path: &Path, state: State) -> io::Result<Self> {
+        let path = path.to_owned();
+        let _ = ::std::fs::remove_file(&path);
+        let socket = UnixDatagram::bind(&path)?;
+        socket.set_read_timeout(Some(Duration::from_millis(10)))?;
+        let state = Arc::new(Mutex::new(state));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (socket, state, stop) = (socket.try_clone()?, state.clone(), stop.clone());
+            thread::spawn(move || serve(&socket, &state, &stop))
+        };
+        Ok(MockSupplicant { path, socket, state, stop, thread: Some(thread) })
+    }
+
+    /// The path of the control socket, to pass to `WpaCtrlBuilder::ctrl_path`
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Answer `cmd` with `reply` from now on
+    pub fn reply<C: Into<String>, R: Into<Vec<u8>>>(&self, cmd: C, reply: R) {
+        self.state.lock().unwrap().replies.insert(cmd.into(), reply.into());
+    }
+
+    /// Send an event, including its `<N>` priority prefix, to all attached connections
+    pub fn event(&self, msg: &str) {
+        let monitors = self.state.lock().unwrap().monitors.clone();
+        for monitor in monitors {
+            let _ = self.socket.send_to(msg.as_bytes(), &monitor);
+        }
+    }
+
+    /// The commands received so far, oldest first
+    pub fn commands(&self) -> Vec<String> {
+        self.state.lock().unwrap().commands.clone()
+    }
+}
+
+impl Drop for MockSupplicant {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        let _ = ::std::fs::remove_file(&self.path);
+    }
+}
+
+/// Answer commands on `socket` until `stop` is set
+fn serve(socket: &UnixDatagram, state: &Mutex<State>, stop: &AtomicBool) {
+    let mut buf = [0; 4096];
+    while !stop.load(Ordering::Relaxed) {
+        let (len, addr) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(_) => continue,
+        };
+        let addr = match addr.as_pathname() {
+            Some(addr) => addr.to_owned(),
+            None => continue,
+        };
+        let cmd = String::from_utf8_lossy(&buf[..len]).into_owned();
+        let (exchange, monitors) = {
+            let mut state = state.lock().unwrap();
+            state.commands.push(cmd.clone());
+            let exchange = answer(&mut state, &cmd, &addr);
+            (exchange, state.monitors.clone())
+        };
+        let events = |events: &[Vec<u8>]| for msg in events {
+            for monitor in &monitors {
+                let _ = socket.send_to(msg, monitor);
+            }
+        };
+        events(&exchange.before);
+        let _ = socket.send_to(&exchange.reply, &addr);
+        events(&exchange.after);
+    }
+}
+
+/// The reply to `cmd` from `addr`, with any events to send around it
+fn answer(state: &mut State, cmd: &str, addr: &Path) -> Exchange {
+    let mut exchange = match state.script {
+        Some(ref mut script) => match script.front() {
+            Some(next) if next.command == redact(cmd) => script.pop_front().unwrap(),
+            _ => Exchange { reply: b"FAIL\n".to_vec(), ..Exchange::default() },
+        },
+        None => match state.replies.get(cmd) {
+            Some(reply) => Exchange { reply: reply.clone(), ..Exchange::default() },
+            None => Exchange::default(),
+        },
+    };
+    if exchange.reply.is_empty() {
+        exchange.reply = match cmd {
+            "PING" => b"PONG\n".to_vec(),
+            "ATTACH" | "DETACH" => b"OK\n".to_vec(),
+            _ => b"UNKNOWN COMMAND\n".to_vec(),
+        };
+    }
+    if exchange.reply == b"OK\n" {
+        match cmd {
+            "ATTACH" => state.monitors.push(addr.to_owned()),
+            "DETACH" => state.monitors.retain(|monitor| monitor != addr),
+            _ => {}
+        }
+    }
+    exchange
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::Error;
+    use level::Level;
+    use wpactrl::WpaCtrl;
+
+    fn mock(name: &str) -> MockSupplicant {
+        MockSupplicant::new(::std::env::temp_dir().join(format!("wpactrl_test_mock_{}_{}", name, ::std::process::id())))
+            .unwrap()
+    }
+
+    #[test]
+    fn replies() {
+        let mock = mock("replies");
+        mock.reply("LIST_NETWORKS", "network id / ssid / bssid / flags\n0\thome\tany\t[CURRENT]\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        assert_eq!(wpa.list_networks().unwrap()[0].ssid, "home");
+        assert!(matches!(wpa.request_checked("BOGUS"), Err(Error::UnknownCommand)));
+        assert_eq!(mock.commands(), vec!["PING", "LIST_NETWORKS", "BOGUS"]);
+    }
+
+    #[test]
+    fn events() {
+        let mock = mock("events");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        mock.event("<3>CTRL-EVENT-SCAN-STARTED ");
+        let msg = loop {
+            if let Some(msg) = wpa.recv().unwrap() {
+                break msg;
+            }
+            thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(msg, (Level::Info, "CTRL-EVENT-SCAN-STARTED ".to_owned()));
+        let (_, remaining) = wpa.detach().unwrap();
+        assert!(remaining.is_empty());
+        mock.event("<3>CTRL-EVENT-SCAN-RESULTS ");
+        assert_eq!(mock.commands(), vec!["ATTACH", "DETACH"]);
+    }
+
+    #[test]
+    fn replay() {
+        let recording = ::std::env::temp_dir().join(format!("wpactrl_test_replay_{}.rec", ::std::process::id()));
+        {
+            let mock = mock("record");
+            mock.reply("STATUS", "wpa_state=SCANNING\n");
+            let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).record(&recording).open().unwrap().attach().unwrap();
+            assert_eq!(wpa.request("STATUS").unwrap(), "wpa_state=SCANNING\n");
+            mock.event("<3>CTRL-EVENT-SCAN-RESULTS ");
+            while wpa.recv().unwrap().is_none() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            wpa.request("SET_NETWORK 0 psk \"hunter2\"").unwrap();
+        }
+        let mock = MockSupplicant::replay(::std::env::temp_dir().join(format!("wpactrl_test_mock_replay_{}", ::std::process::id())), &recording).unwrap();
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        assert_eq!(wpa.request("STATUS").unwrap(), "wpa_state=SCANNING\n");
+        let msg = loop {
+            if let Some(msg) = wpa.recv().unwrap() {
+                break msg;
+            }
+            thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(msg, (Level::Info, "CTRL-EVENT-SCAN-RESULTS ".to_owned()));
+        assert_eq!(wpa.request("SET_NETWORK 0 psk \"other\"").unwrap(), "UNKNOWN COMMAND\n");
+        assert_eq!(wpa.request("STATUS").unwrap(), "FAIL\n");
+        ::std::fs::remove_file(&recording).unwrap();
+    }
+
+    #[test]
+    fn drop_removes_socket() {
+        let path = mock("drop").path().to_owned();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,149 @@
+use error::Error;
+use std::fmt;
+use std::str::FromStr;
+use super::Result;
+use wpactrl::{reply_error, WpaCtrl};
+
+/// A wpasupplicant or hostapd release, such as `2.10`
+///
+/// # Examples
+///
+/// ```
+/// use wpactrl::Version;
+/// let version: Version = "2.11-devel".parse().unwrap();
+/// assert_eq!(version, Version::new(2, 11));
+/// assert!(version > Version::new(2, 9));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Version {
+    /// The major version
+    pub major: u32,
+    /// The minor version
+    pub minor: u32,
+}
+
+impl Version {
+    /// A version from its parts
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Version { major, minor }
+    }
+}
+
+impl FromStr for Version {
+    type Err = Error;
+
+    /// Parse a version as reported by `GET version`, ignoring suffixes such as `-devel`
+    fn from_str(s: &str) -> Result<Self> {
+        let err = || Error::Parse(s.to_owned());
+        let release = s.trim_start_matches('v').split('-').next().unwrap_or("");
+        let mut parts = release.split('.');
+        let major = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        let minor = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        Ok(Version { major, minor })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// The release adding `GET version`
+const VERSION_SINCE: Version = Version::new(2, 5);
+
+/// Parse the reply to `GET version`, or None if it is not supported
+fn parse_version(reply: &str) -> Result<Option<Version>> {
+    match reply_error(reply) {
+        Some(Error::Failure) | Some(Error::UnknownCommand) => Ok(None),
+        Some(e) => Err(e),
+        None => reply.trim_end().parse().map(Some),
+    }
+}
+
+impl WpaCtrl {
+    /// The version of wpasupplicant or hostapd
+    ///
+    /// Returns None for releases too old to report their version. The
+    /// version is only asked for once per connection.
+    ///
+    /// Typed methods for commands added in 2.6 or later, such as
+    /// `dpp_bootstrap_gen`, `pmksa_get` and `mesh_peer_add`, use it to fail
+    /// with `Error::NotSupported` on older releases rather than `Error::Failure`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// match wpa.version().unwrap() {
+    ///     Some(version) => println!("wpasupplicant {}", version),
+    ///     None => println!("an old wpasupplicant"),
+    /// }
+    /// ```
+    pub fn version(&mut self) -> Result<Option<Version>> {
+        if let Some(version) = *self.known_version() {
+            return Ok(Some(version));
+        }
+        let version = parse_version(&self.request("GET version")?)?;
+        *self.known_version() = version;
+        Ok(version)
+    }
+
+    /// Turn a failure of a command added in `min` into `Error::NotSupported` on older releases
+    ///
+    /// Older releases reject such commands with a bare `FAIL` or `UNKNOWN
+    /// COMMAND`; the version is only looked up when that happens.
+    ///
+    /// This is applied to commands added from 2.6 on: DPP, `PMKSA_GET` and
+    /// `PMKSA_ADD`, `MESH_PEER_ADD` and `MESH_PEER_REMOVE`, and per-network
+    /// MAC addresses. Releases before `VERSION_SINCE` cannot be told apart,
+    /// so commands older than that, such as WPS ER, P2P, TDLS, NFC and
+    /// `MESH_GROUP_ADD`, keep their original error.
+    pub(crate) fn since<T>(&mut self, min: Version, result: Result<T>) -> Result<T> {
+        let e = match result {
+            Err(e @ Error::Failure) | Err(e @ Error::UnknownCommand) => e,
+            result => return result,
+        };
+        match self.version() {
+            Ok(Some(version)) if version >= min => Err(e),
+            Ok(Some(_)) => Err(Error::NotSupported),
+            Ok(None) if min >= VERSION_SINCE => Err(Error::NotSupported),
+            Ok(None) | Err(_) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn version() {
+        assert_eq!("2.10".parse::<Version>().unwrap(), Version::new(2, 10));
+        assert_eq!("v2.9-devel-hostap_2_9".parse::<Version>().unwrap(), Version::new(2, 9));
+        assert!("devel".parse::<Version>().is_err());
+        assert!(Version::new(2, 10) > Version::new(2, 9));
+        assert_eq!(Version::new(2, 10).to_string(), "2.10");
+        assert_eq!(parse_version("2.10\n").unwrap(), Some(Version::new(2, 10)));
+        assert_eq!(parse_version("UNKNOWN COMMAND\n").unwrap(), None);
+    }
+
+    #[test]
+    fn since() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_version_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("GET version", "2.9\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert!(matches!(wpa.since(Version::new(2, 10), Err::<(), _>(Error::Failure)), Err(Error::NotSupported)));
+        assert!(matches!(wpa.since(Version::new(2, 9), Err::<(), _>(Error::Failure)), Err(Error::Failure)));
+        assert!(matches!(wpa.since(Version::new(2, 10), Err::<(), _>(Error::Timeout)), Err(Error::Timeout)));
+        assert_eq!(wpa.since(Version::new(2, 10), Ok(1)).unwrap(), 1);
+        assert_eq!(mock.commands(), vec!["GET version"]);
+        mock.reply("GET version", "UNKNOWN COMMAND\n");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap();
+        assert!(matches!(wpa.since(Version::new(2, 6), Err::<(), _>(Error::Failure)), Err(Error::NotSupported)));
+        assert!(matches!(wpa.since(Version::new(2, 4), Err::<(), _>(Error::Failure)), Err(Error::Failure)));
+    }
+}
@@ -0,0 +1,168 @@
+use interfaces::{list_interfaces, PATH_DEFAULT_CTRL_DIR};
+use nix::libc;
+use nix::sys::select::*;
+use nix::sys::time::{TimeVal, TimeValLike};
+use std::collections::{BTreeSet, VecDeque};
+use std::ffi::CString;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use super::Result;
+
+/// Size of the fixed part of a `struct inotify_event`
+const EVENT_HEADER_LEN: usize = 16;
+
+/// A change to the interfaces of a control directory
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InterfaceEvent {
+    /// wpasupplicant started controlling the named interface
+    InterfaceAdded(String),
+    /// wpasupplicant stopped controlling the named interface
+    InterfaceRemoved(String),
+}
+
+/// Watches a control directory for interfaces coming and going
+///
+/// Reports when control sockets are created or removed, eg because a USB
+/// Wi-Fi adapter was plugged in or because wpasupplicant was restarted.
+/// Interfaces present when the watcher is created are not reported; use
+/// `interfaces` to get them. Iterating blocks until the next change.
+///
+/// # Examples
+///
+/// ```no_run
+/// use wpactrl::{InterfaceEvent, InterfaceWatcher};
+/// let mut watcher = InterfaceWatcher::new(None).unwrap();
+/// println!("Interfaces: {:?}", watcher.interfaces());
+/// for event in watcher {
+///     match event.unwrap() {
+///         InterfaceEvent::InterfaceAdded(name) => println!("{} added", name),
+///         InterfaceEvent::InterfaceRemoved(name) => println!("{} removed", name),
+///     }
+/// }
+/// ```
+pub struct InterfaceWatcher {
+    inotify: File,
+    dir: PathBuf,
+    known: BTreeSet<String>,
+    pending: VecDeque<InterfaceEvent>,
+    buffer: Vec<u8>,
+}
+
+impl InterfaceWatcher {
+    /// Start watching a control directory, by default `/var/run/wpa_supplicant`
+    pub fn new<'a, I: Into<Option<&'a Path>>>(dir: I) -> Result<Self> {
+        let dir = dir.into().unwrap_or_else(|| Path::new(PATH_DEFAULT_CTRL_DIR)).to_owned();
+        let path = CString::new(dir.as_os_str().as_bytes()).map_err(io::Error::from)?;
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        let inotify = unsafe { File::from_raw_fd(fd) };
+        let mask = libc::IN_CREATE | libc::IN_DELETE | libc::IN_MOVED_TO | libc::IN_MOVED_FROM;
+        if unsafe { libc::inotify_add_watch(fd, path.as_ptr(), mask) } < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        // Watch before listing, so an interface added in between is not missed
+        let known = list_interfaces(dir.as_path())?.into_iter().collect();
+        Ok(InterfaceWatcher { inotify, dir, known, pending: VecDeque::new(), buffer: vec![0; 4096] })
+    }
+
+    /// The interfaces currently in the directory, as far as the watcher has seen
+    pub fn interfaces(&self) -> Vec<String> {
+        self.known.iter().cloned().collect()
+    }
+
+    /// Wait up to `timeout` for the next change, returning None if there was none
+    pub fn next_timeout(&mut self, timeout: Duration) -> Result<Option<InterfaceEvent>> {
+        if self.pending.is_empty() && self.wait(Some(timeout))? {
+            self.read()?;
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Wait for the inotify descriptor to become readable
+    fn wait(&self, timeout: Option<Duration>) -> Result<bool> {
+        let fd = self.inotify.as_raw_fd();
+        let mut fd_set = FdSet::new();
+        fd_set.insert(fd);
+        let mut timeout = timeout.map(|t| TimeVal::microseconds(t.as_micros() as i64));
+        select(fd + 1, Some(&mut fd_set), None, None, timeout.as_mut())?;
+        Ok(fd_set.contains(fd))
+    }
+
+    /// Read the pending inotify events, queueing the changes they report
+    fn read(&mut self) -> Result<()> {
+        let len = self.inotify.read(&mut self.buffer)?;
+        let mut offset = 0;
+        while offset + EVENT_HEADER_LEN <= len {
+            let field = |i: usize| {
+                let start = offset + i * 4;
+                let mut bytes = [0; 4];
+                bytes.copy_from_slice(&self.buffer[start..start + 4]);
+                u32::from_ne_bytes(bytes)
+            };
+            let (mask, name_len) = (field(1), field(3) as usize);
+            let name = &self.buffer[offset + EVENT_HEADER_LEN..offset + EVENT_HEADER_LEN + name_len];
+            let name = String::from_utf8_lossy(name.split(|&b| b == 0).next().unwrap_or(&[])).into_owned();
+            offset += EVENT_HEADER_LEN + name_len;
+            if mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 {
+                let is_socket = fs::symlink_metadata(self.dir.join(&name)).map(|m| m.file_type().is_socket());
+                if is_socket.unwrap_or(false) && self.known.insert(name.clone()) {
+                    self.pending.push_back(InterfaceEvent::InterfaceAdded(name));
+                }
+            } else if mask & (libc::IN_DELETE | libc::IN_MOVED_FROM) != 0 && self.known.remove(&name) {
+                self.pending.push_back(InterfaceEvent::InterfaceRemoved(name));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for InterfaceWatcher {
+    type Item = Result<InterfaceEvent>;
+
+    fn next(&mut self) -> Option<Result<InterfaceEvent>> {
+        while self.pending.is_empty() {
+            if let Err(e) = self.wait(None).and_then(|_| self.read()) {
+                return Some(Err(e));
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+impl AsRawFd for InterfaceWatcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inotify.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn watch() {
+        let dir = ::std::env::temp_dir().join(format!("wpactrl_test_watch_{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let _wlan0 = UnixDatagram::bind(dir.join("wlan0")).unwrap();
+        let mut watcher = InterfaceWatcher::new(dir.as_path()).unwrap();
+        assert_eq!(watcher.interfaces(), vec!["wlan0"]);
+        let wlan1 = UnixDatagram::bind(dir.join("wlan1")).unwrap();
+        fs::write(dir.join("not-a-socket"), b"").unwrap();
+        fs::remove_file(dir.join("wlan0")).unwrap();
+        let timeout = Duration::from_secs(1);
+        assert_eq!(watcher.next_timeout(timeout).unwrap(), Some(InterfaceEvent::InterfaceAdded("wlan1".into())));
+        assert_eq!(watcher.next_timeout(timeout).unwrap(), Some(InterfaceEvent::InterfaceRemoved("wlan0".into())));
+        assert_eq!(watcher.next_timeout(Duration::from_millis(10)).unwrap(), None);
+        drop(wlan1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use macaddr::MacAddr;
+use parse::{event, event_arg};
+use super::Result;
+use wpactrl::{expect_ok, WpaCtrl};
+
+/// A BSS Transition Management response (802.11v)
+///
+/// Reported by the `WNM-BSS-TM-RESP` event, and by hostapd as
+/// `BSS-TM-RESP` for responses received from stations.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BssTmResponse {
+    /// The station that sent the response, as reported by hostapd
+    pub addr: Option<MacAddr>,
+    /// The dialog token of the request being answered
+    pub dialog_token: Option<u8>,
+    /// The status code; 0 means the transition is accepted
+    pub status_code: u8,
+    /// The BSS termination delay in minutes
+    pub bss_termination_delay: Option<u8>,
+    /// The BSSID of the chosen transition target, if any
+    pub target_bssid: Option<MacAddr>,
+}
+
+impl BssTmResponse {
+    /// Parse a BSS Transition Management response event, returning None for other messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::BssTmResponse;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((_, msg)) = wpa.recv().unwrap() {
+    ///     if let Some(resp) = BssTmResponse::parse(&msg) {
+    ///         println!("Transition to {:?}: status {}", resp.target_bssid, resp.status_code);
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        if event != "WNM-BSS-TM-RESP" && event != "BSS-TM-RESP" {
+            return None;
+        }
+        let num = |key| event_arg(body, key).and_then(|v| v.parse().ok());
+        Some(BssTmResponse {
+            addr: body.split(' ').next().and_then(|w| w.parse().ok()),
+            dialog_token: num("dialog_token"),
+            status_code: num("status_code")?,
+            bss_termination_delay: num("bss_termination_delay"),
+            target_bssid: event_arg(body, "target_bssid").and_then(|v| v.parse().ok()),
+        })
+    }
+}
+
+impl WpaCtrl {
+    /// Ask the access point for BSS transition candidates (802.11v)
+    ///
+    /// `reason` is the transition query reason code, eg 16 for "better AP
+    /// found". With `candidates`, the current scan results are included as
+    /// a list of preferred candidates. The access point answers with a BSS
+    /// transition request, and the response to it is reported as a
+    /// `BssTmResponse` event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.wnm_bss_query(16, true).unwrap();
+    /// ```
+    pub fn wnm_bss_query(&mut self, reason: u8, candidates: bool) -> Result<()> {
+        let cmd = if candidates {
+            format!("WNM_BSS_QUERY {} list", reason)
+        } else {
+            format!("WNM_BSS_QUERY {}", reason)
+        };
+        expect_ok(&self.request(&cmd)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bss_tm_resp() {
+        assert_eq!(BssTmResponse::parse("<3>WNM-BSS-TM-RESP dialog_token=1 status_code=0 bss_termination_delay=0 \
+                                         target_bssid=02:00:00:00:01:00"),
+                   Some(BssTmResponse {
+                       addr: None,
+                       dialog_token: Some(1),
+                       status_code: 0,
+                       bss_termination_delay: Some(0),
+                       target_bssid: "02:00:00:00:01:00".parse().ok(),
+                   }));
+        assert_eq!(BssTmResponse::parse("<3>BSS-TM-RESP 02:00:00:00:02:00 dialog_token=5 status_code=7 \
+                                         bss_termination_delay=0"),
+                   Some(BssTmResponse {
+                       addr: "02:00:00:00:02:00".parse().ok(),
+                       dialog_token: Some(5),
+                       status_code: 7,
+                       bss_termination_delay: Some(0),
+                       target_bssid: None,
+                   }));
+        assert_eq!(BssTmResponse::parse("<3>WNM-BSS-TM-RESP"), None);
+        assert_eq!(BssTmResponse::parse("<3>CTRL-EVENT-SCAN-STARTED "), None);
+    }
+}
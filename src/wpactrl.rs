@@ -1,30 +1,145 @@
 #![deny(missing_docs)]
 use nix::sys::select::*;
+use nix::sys::socket::{recv, MsgFlags};
 use nix::sys::time::{TimeVal, TimeValLike};
-use nix::unistd::getpid;
+use nix::unistd::{chown, getpid, Gid};
+use std::borrow::Cow;
+use std::collections::hash_map::RandomState;
 use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
 use std::os::unix::io::AsRawFd;
 use std::os::unix::net::UnixDatagram;
+use std::thread;
+use std::time::{Duration, Instant};
 use std;
 use super::Result;
+use error::Error;
+use interfaces::{connect_any, PATH_DEFAULT_CTRL_DIR};
+use level::{split_level, Level};
+use record::Recorder;
+use secret::{redact, redact_event, redact_reply};
+use version::Version;
 
 const BUF_SIZE: usize = 10_240;
 const PATH_DEFAULT_CLIENT: &str = "/tmp";
+const NAME_DEFAULT_CLIENT: &str = "wpa_ctrl_{pid}-{n}";
 const PATH_DEFAULT_SERVER: &str = "/var/run/wpa_supplicant/wlan0";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const AVAILABLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const OPEN_BACKOFF: Duration = Duration::from_millis(100);
 
-/// Error type used for some library functions
-#[derive(Debug, Fail, PartialEq)]
-enum WpaError {
-    #[fail(display = "Failed to execute the specified command")]
-    Failure,
+/// Source of request ids, shared by all connections in the process
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Source of client socket numbers, so connections in the process never share a socket
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Receive a datagram into `buf`, returning its full length even if it did not fit
+fn recv_full_len(socket: &UnixDatagram, buf: &mut [u8]) -> std::io::Result<usize> {
+    match recv(socket.as_raw_fd(), buf, MsgFlags::MSG_TRUNC) {
+        Ok(len) => Ok(len),
+        Err(nix::Error::Sys(errno)) => Err(std::io::Error::from_raw_os_error(errno as i32)),
+        Err(e) => Err(std::io::Error::other(e)),
+    }
+}
+
+/// Log a received event, with credentials redacted
+fn log_event(msg: &str) {
+    let msg = redact_event(msg.trim_end());
+    trace!("event: {}", msg);
+    #[cfg(feature = "tracing")]
+    ::tracing::trace!(event = %msg, "event");
+}
+
+/// Convert a socket error, reporting a vanished peer as `Error::ConnectionLost`
+fn socket_error(e: std::io::Error) -> Error {
+    match e.kind() {
+        std::io::ErrorKind::ConnectionRefused |
+        std::io::ErrorKind::ConnectionReset |
+        std::io::ErrorKind::NotConnected |
+        std::io::ErrorKind::NotFound => Error::ConnectionLost,
+        _ => e.into(),
+    }
+}
+
+/// Check that the reply to a command is a plain acknowledgment
+pub(crate) fn expect_ok(reply: &str) -> Result<()> {
+    match reply {
+        "OK\n" => Ok(()),
+        reply => Err(reply_error(reply).unwrap_or(Error::Failure)),
+    }
+}
+
+/// The error for a reply rejecting a command, or None if it is not a rejection
+pub(crate) fn reply_error(reply: &str) -> Option<Error> {
+    match reply.trim_end() {
+        "UNKNOWN COMMAND" => Some(Error::UnknownCommand),
+        "NOT-SUPPORTED" => Some(Error::NotSupported),
+        r if r == "FAIL" || r.starts_with("FAIL-") => Some(Error::Failure),
+        _ => None,
+    }
+}
+
+/// Connections that commands can be sent over, so typed helpers can be shared
+pub(crate) trait Request {
+    fn request(&mut self, cmd: &str) -> Result<String>;
+}
+
+/// Whether an error means the socket of wpasupplicant / hostapd went away
+fn is_connection_lost(e: &Error) -> bool {
+    matches!(*e, Error::ConnectionLost)
+}
+
+/// How to reconnect after wpasupplicant / hostapd restarts
+#[derive(Clone, Copy, Debug)]
+struct ReconnectPolicy {
+    attempts: u32,
+    backoff: Duration,
+}
+
+/// How to retry requests that time out
+#[derive(Clone, Copy, Debug)]
+struct RetryPolicy {
+    attempts: u32,
+    timeout: Duration,
+    backoff: Duration,
+}
+
+/// Whether sending `cmd` again has no effect beyond sending it once
+///
+/// Only queries qualify: a late reply to an earlier attempt is as good an
+/// answer as the reply to the retry.
+fn is_idempotent(cmd: &str) -> bool {
+    matches!(cmd.split(' ').next().unwrap_or(""),
+             "PING" | "STATUS" | "STATUS-VERBOSE" | "LIST_NETWORKS" | "GET_NETWORK" | "SCAN_RESULTS" | "BSS" |
+             "GET" | "GET_CAPABILITY" | "SIGNAL_POLL" | "PKTCNT_POLL" | "MIB" | "INTERFACES" | "INTERFACE_LIST" |
+             "STA" | "STA-FIRST" | "STA-NEXT" | "ALL_STA" | "GET_CONFIG" | "PMKSA" | "LIST_CREDS" | "GET_CRED" |
+             "P2P_PEER" | "P2P_PEERS" | "DRIVER_FLAGS" | "GET_PREF_FREQ_LIST")
 }
 
 /// Builder object used to construct a `WpaCtrl` session
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct WpaCtrlBuilder {
     cli_path: Option<PathBuf>,
+    cli_name: Option<String>,
+    cli_group: Option<u32>,
+    cli_mode: Option<u32>,
     ctrl_path: Option<PathBuf>,
+    reconnect: Option<ReconnectPolicy>,
+    retry: Option<RetryPolicy>,
+    any_interface: bool,
+    buffer_size: Option<usize>,
+    record: Option<PathBuf>,
+    lossy_events: bool,
+    open_retries: u32,
+    open_backoff: Option<Duration>,
+    keep_attached: bool,
+    socket: Option<Arc<UnixDatagram>>,
 }
 
 impl WpaCtrlBuilder {
@@ -45,6 +160,65 @@ impl WpaCtrlBuilder {
         self
     }
 
+    /// The file name of this application's UNIX domain socket, within `cli_path`
+    ///
+    /// `{pid}` is replaced with the process id and `{n}` with a number
+    /// unique within the process; `-{n}` is appended if the template does
+    /// not contain `{n}`. Defaults to `wpa_ctrl_{pid}-{n}`.
+    ///
+    /// The socket is removed when the connection is dropped, including
+    /// while unwinding from a panic. Sockets left behind by a process that
+    /// was killed are replaced when a later process with the same id
+    /// opens a connection; a distinctive name makes them easy to find.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .cli_path("/run/myapp")
+    ///             .cli_name("myapp-{pid}-{n}")
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn cli_name<S: Into<String>>(mut self, template: S) -> Self {
+        self.cli_name = Some(template.into());
+        self
+    }
+
+    /// The group id to give this application's UNIX domain socket
+    ///
+    /// With `ctrl_interface=DIR=/var/run/wpa_supplicant GROUP=netdev`, only
+    /// members of `netdev` may use the control interface. A daemon in that
+    /// group can give its socket the same group, together with `cli_mode`,
+    /// so that the socket stays usable by wpasupplicant and by the group
+    /// whatever the umask of the daemon.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::os::unix::fs::MetadataExt;
+    /// use wpactrl::WpaCtrl;
+    /// let group = std::fs::metadata("/var/run/wpa_supplicant").unwrap().gid();
+    /// let wpa = WpaCtrl::new()
+    ///             .cli_group(group)
+    ///             .cli_mode(0o660)
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn cli_group(mut self, gid: u32) -> Self {
+        self.cli_group = Some(gid);
+        self
+    }
+
+    /// The permissions to give this application's UNIX domain socket, eg `0o660`
+    ///
+    /// See `cli_group`.
+    pub fn cli_mode(mut self, mode: u32) -> Self {
+        self.cli_mode = Some(mode);
+        self
+    }
+
     /// A path-like object for the wpasupplicant / hostap UNIX domain sockets
     /// 
     /// # Examples
@@ -62,8 +236,240 @@ impl WpaCtrlBuilder {
         self
     }
 
+    /// Whether a path for the wpasupplicant / hostap sockets was given
+    pub(crate) fn has_ctrl_path(&self) -> bool {
+        self.ctrl_path.is_some()
+    }
+
+    /// Connect to the first live interface in the control directory
+    ///
+    /// With this option, `ctrl_path` names the control directory to scan
+    /// rather than a socket, and defaults to `/var/run/wpa_supplicant`.
+    /// If no live interface exists yet, `open` retries for about a second
+    /// before giving up. This suits devices with a single radio whose
+    /// interface name varies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .any_interface()
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn any_interface(mut self) -> Self {
+        self.any_interface = true;
+        self
+    }
+
+    /// Reconnect automatically if wpasupplicant / hostapd restarts
+    ///
+    /// When a request finds that the socket of wpasupplicant went away, the
+    /// connection is reestablished and the request is sent again. Up to
+    /// `attempts` attempts are made, waiting `backoff` before the first and
+    /// doubling the wait before each following one. Attached connections
+    /// are attached again after reconnecting, with the level set by
+    /// `WpaCtrlAttached::set_level`. They also reconnect when `recv` finds
+    /// that wpasupplicant replaced its socket, so the event stream resumes
+    /// after a restart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .auto_reconnect(5, Duration::from_millis(100))
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn auto_reconnect(mut self, attempts: u32, backoff: Duration) -> Self {
+        self.reconnect = Some(ReconnectPolicy { attempts, backoff });
+        self
+    }
+
+    /// Retry queries that wpasupplicant / hostapd does not answer in time
+    ///
+    /// By default, a request fails with `Error::Timeout` if no reply arrives
+    /// within 10 seconds. With this option, each attempt waits `timeout`
+    /// instead, and queries such as `STATUS` or `SCAN_RESULTS` are sent
+    /// again, up to `attempts` attempts in all. The wait before the first
+    /// retry is `backoff`, doubling before each following one. Commands
+    /// that change state are never retried, since the first attempt may
+    /// have taken effect even though its reply was lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .retry_timeouts(3, Duration::from_secs(2), Duration::from_millis(100))
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn retry_timeouts(mut self, attempts: u32, timeout: Duration, backoff: Duration) -> Self {
+        self.retry = Some(RetryPolicy { attempts, timeout, backoff });
+        self
+    }
+
+    /// The size of the largest reply or message that can be received, in bytes
+    ///
+    /// Defaults to 10240 bytes. Replies such as `SCAN_RESULTS` in dense
+    /// environments or `ALL_STA` on busy access points can be longer;
+    /// rather than being silently truncated, these fail with
+    /// `Error::Truncated`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .buffer_size(65_536)
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Record the traffic of the connection to a file
+    ///
+    /// Every command sent, reply received and event received is written
+    /// to `path`, one per line, with credentials redacted from all three.
+    /// `testing::MockSupplicant::replay` serves a recording back, so a
+    /// problem seen on a remote device can be reproduced locally. Failures
+    /// writing the recording are logged and otherwise ignored. Connections
+    /// opened with `try_clone` are not recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .record("/tmp/wpactrl.rec")
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn record<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.record = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Replace invalid UTF-8 in events instead of failing with `Error::Utf8`
+    ///
+    /// Events can carry raw bytes, eg the SSID of an access point, which
+    /// are not always valid UTF-8. By default such an event makes `recv`,
+    /// or the request during which it arrived, fail. With this option the
+    /// invalid bytes are replaced with U+FFFD instead, so one odd SSID
+    /// cannot stop a monitoring loop. `recv_into` returns events as bytes
+    /// either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .lossy_events()
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn lossy_events(mut self) -> Self {
+        self.lossy_events = true;
+        self
+    }
+
+    /// Use a socket that is already connected to the control socket
+    ///
+    /// For processes that cannot open the control socket themselves, eg
+    /// sandboxed helpers handed a socket by a privileged parent. `open`
+    /// then skips binding and connecting, and `socket` is used as is.
+    /// The path `socket` is bound to, if any, is left in place when the
+    /// connection is dropped. Reconnecting connects `socket` to its peer
+    /// address again, while `try_clone` opens a new socket to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::os::unix::net::UnixDatagram;
+    /// use wpactrl::WpaCtrl;
+    /// let socket = UnixDatagram::bind("/tmp/wpa_ctrl_helper").unwrap();
+    /// socket.connect("/var/run/wpa_supplicant/wlan0").unwrap();
+    /// let wpa = WpaCtrl::new()
+    ///             .from_socket(socket)
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn from_socket(mut self, socket: UnixDatagram) -> Self {
+        self.socket = Some(Arc::new(socket));
+        self
+    }
+
+    /// Whether dropping an attached connection sends `DETACH`, which it does by default
+    ///
+    /// Otherwise wpasupplicant keeps the connection registered as a monitor
+    /// until sending it an event fails, and meanwhile queues events for it.
+    /// The `DETACH` is sent without waiting for a reply, so dropping never
+    /// blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .detach_on_drop(false)
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn detach_on_drop(mut self, detach: bool) -> Self {
+        self.keep_attached = !detach;
+        self
+    }
+
+    /// Retry `open` up to `retries` times while wpasupplicant is not ready
+    ///
+    /// At boot, the control socket can exist before wpasupplicant accepts
+    /// connections on it. Opening then fails with `Error::NoSuchInterface`
+    /// or `Error::NoInterface`, and with this option is retried after a
+    /// wait, which starts at the `open_backoff` and doubles after each
+    /// attempt. Other errors are returned immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new()
+    ///             .open_retries(5)
+    ///             .open_backoff(Duration::from_millis(200))
+    ///             .open()
+    ///             .unwrap();
+    /// ```
+    pub fn open_retries(mut self, retries: u32) -> Self {
+        self.open_retries = retries;
+        self
+    }
+
+    /// How long to wait before the first retry of `open`, 100ms by default
+    ///
+    /// Each wait is shortened by a random amount of up to half, so that
+    /// services started together do not all retry at the same time. See
+    /// `open_retries`.
+    pub fn open_backoff(mut self, backoff: Duration) -> Self {
+        self.open_backoff = Some(backoff);
+        self
+    }
+
     /// Open a control interface to wpasupplicant.
     ///
+    /// The control socket is checked before connecting, so the common
+    /// problems are reported distinctly: `Error::NoSuchInterface` if
+    /// wpasupplicant is not running on the interface, `Error::PermissionDenied`
+    /// if this process may not use the socket, and `Error::NotASocket` if the
+    /// path is something else.
+    ///
     /// # Examples
     ///
     /// ```
@@ -71,124 +477,842 @@ impl WpaCtrlBuilder {
     /// let wpa = WpaCtrl::new().open().unwrap();
     /// ```
     pub fn open(self) -> Result<WpaCtrl> {
+        let mut backoff = self.open_backoff.unwrap_or(OPEN_BACKOFF);
+        for attempt in 0..self.open_retries {
+            match self.clone().connect() {
+                Err(ref e @ Error::NoSuchInterface(_)) | Err(ref e @ Error::NoInterface(_)) => {
+                    debug!("open failed ({}), retry {} of {}", e, attempt + 1, self.open_retries);
+                    thread::sleep(jitter(backoff));
+                    backoff *= 2;
+                }
+                r => return r,
+            }
+        }
+        self.connect()
+    }
+
+    /// Connect to the control socket once, see `open`
+    fn connect(mut self) -> Result<WpaCtrl> {
+        if let Some(socket) = self.socket.take() {
+            let socket = socket.try_clone()?;
+            let ctrl_path = match socket.peer_addr()?.as_pathname() {
+                Some(path) => path.to_owned(),
+                None => self.ctrl_path.clone().unwrap_or_else(||PATH_DEFAULT_SERVER.into()),
+            };
+            return self.connected(socket, None, ctrl_path);
+        }
+        if !self.any_interface {
+            check_ctrl_path(self.ctrl_path.as_deref().unwrap_or_else(||Path::new(PATH_DEFAULT_SERVER)))?;
+        }
+        let mut template = self.cli_name.as_deref().unwrap_or(NAME_DEFAULT_CLIENT).to_owned();
+        if !template.contains("{n}") {
+            template.push_str("-{n}");
+        }
+        let bind_filename = template
+            .replace("{pid}", &getpid().to_string())
+            .replace("{n}", &NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed).to_string());
+        let bind_filepath = self.cli_path.as_deref().unwrap_or_else(||Path::new(PATH_DEFAULT_CLIENT)).join(bind_filename);
         let mut counter = 0;
         loop {
             counter += 1;
-            let bind_filename = format!("wpa_ctrl_{}-{}", getpid(), counter);
-            let bind_filepath = self.cli_path.as_ref().map(|p|p.as_path()).unwrap_or_else(||Path::new(PATH_DEFAULT_CLIENT)).join(bind_filename);
             match UnixDatagram::bind(&bind_filepath) {
                 Ok(socket) => {
-                    socket.connect(self.ctrl_path.unwrap_or_else(||PATH_DEFAULT_SERVER.into()))?;
-                    socket.set_nonblocking(true)?;
-                    return Ok(WpaCtrl(WpaCtrlInternal {
-                        buffer: [0; BUF_SIZE],
-                        handle: socket,
-                        filepath: bind_filepath,
-                    }))
+                    let connected = self.set_cli_permissions(&bind_filepath).and_then(|()| if self.any_interface {
+                        let dir = self.ctrl_path.clone().unwrap_or_else(||PATH_DEFAULT_CTRL_DIR.into());
+                        connect_any(&socket, &dir)
+                    } else {
+                        let ctrl_path = self.ctrl_path.clone().unwrap_or_else(||PATH_DEFAULT_SERVER.into());
+                        match socket.connect(&ctrl_path) {
+                            Ok(()) => Ok(ctrl_path),
+                            Err(e) => Err(open_error(ctrl_path, e)),
+                        }
+                    });
+                    let r = connected.and_then(|ctrl_path| self.connected(socket, Some(bind_filepath.clone()), ctrl_path));
+                    if r.is_err() {
+                        let _ = std::fs::remove_file(&bind_filepath);
+                    }
+                    return r;
                 },
+                // Socket names are unique within the process, so this was
+                // left behind by an earlier process with the same id
                 Err(ref e) if counter < 2 && e.kind() == std::io::ErrorKind::AddrInUse => {
-                    std::fs::remove_file(bind_filepath)?;
+                    std::fs::remove_file(&bind_filepath)?;
                     continue;
                 },
                 Err(e) => Err(e)?,
             };
         }
     }
-}
 
-struct WpaCtrlInternal {
-    buffer: [u8; BUF_SIZE],
-    handle: UnixDatagram,
-    filepath: PathBuf,
+    /// Apply `cli_group` and `cli_mode` to the client socket at `path`
+    fn set_cli_permissions(&self, path: &Path) -> Result<()> {
+        if let Some(gid) = self.cli_group {
+            chown(path, None, Some(Gid::from_raw(gid)))?;
+        }
+        if let Some(mode) = self.cli_mode {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+        }
+        Ok(())
+    }
+
+    /// Set up a connection over `socket`, connected to `ctrl_path`
+    ///
+    /// `filepath` is the path `socket` is bound to, if it is to be removed
+    /// when the connection is dropped.
+    fn connected(self, socket: UnixDatagram, filepath: Option<PathBuf>, ctrl_path: PathBuf) -> Result<WpaCtrl> {
+        let recorder = match self.record {
+            Some(ref path) => Some(Recorder::create(path)?),
+            None => None,
+        };
+        let peer = socket_id(&ctrl_path);
+        socket.set_nonblocking(true)?;
+        Ok(WpaCtrl(WpaCtrlInternal {
+            // One spare byte to detect messages that do not fit
+            buffer: vec![0; self.buffer_size.unwrap_or(BUF_SIZE) + 1],
+            handle: socket,
+            filepath,
+            cli_path: self.cli_path,
+            cli_name: self.cli_name,
+            cli_group: self.cli_group,
+            cli_mode: self.cli_mode,
+            ctrl_path,
+            reconnect: self.reconnect,
+            retry: self.retry,
+            attached: false,
+            last_request_id: None,
+            recorder,
+            version: None,
+            lossy_events: self.lossy_events,
+            level: None,
+            peer,
+            detach_on_drop: !self.keep_attached,
+        }))
+    }
+
+    /// Open a control interface to wpasupplicant, waiting up to `timeout` for it to appear
+    ///
+    /// Services started in parallel with wpasupplicant at boot may find
+    /// that its control socket does not exist yet. This checks for the
+    /// socket every 100ms until it can be connected to, and fails as
+    /// `open` does if it is still missing once `timeout` has passed.
+    /// Other errors are returned immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use wpactrl::WpaCtrl;
+    /// let wpa = WpaCtrl::new().open_when_available(Duration::from_secs(30)).unwrap();
+    /// ```
+    pub fn open_when_available(self, timeout: Duration) -> Result<WpaCtrl> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.clone().open() {
+                Err(Error::NoSuchInterface(_)) | Err(Error::NoInterface(_)) if Instant::now() < deadline => {
+                    thread::sleep(AVAILABLE_POLL_INTERVAL);
+                }
+                r => return r,
+            }
+        }
+    }
+}
+
+/// Shorten `backoff` by a random amount of up to half
+fn jitter(backoff: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    backoff.mul_f64(0.5 + (random % 1000) as f64 / 2000.0)
+}
+
+/// Check that `path` is a control socket this process can reach
+fn check_ctrl_path(path: &Path) -> Result<()> {
+    match std::fs::metadata(path) {
+        Ok(ref metadata) if !metadata.file_type().is_socket() => Err(Error::NotASocket(path.to_owned())),
+        Ok(_) => Ok(()),
+        Err(e) => Err(open_error(path.to_owned(), e)),
+    }
+}
+
+/// Convert an error opening the control socket at `path`
+fn open_error(path: PathBuf, e: std::io::Error) -> Error {
+    match e.kind() {
+        // A socket left behind by a wpasupplicant that exited refuses connections
+        std::io::ErrorKind::NotFound |
+        std::io::ErrorKind::ConnectionRefused => Error::NoSuchInterface(path),
+        std::io::ErrorKind::PermissionDenied => Error::PermissionDenied(path),
+        _ => Error::Open(path, e),
+    }
+}
+
+/// Identify the socket at `path`, to notice when it is replaced by a new one
+fn socket_id(path: &Path) -> Option<(u64, u64, i64, i64)> {
+    std::fs::metadata(path).ok().map(|m| (m.dev(), m.ino(), m.ctime(), m.ctime_nsec()))
+}
+
+struct WpaCtrlInternal {
+    buffer: Vec<u8>,
+    handle: UnixDatagram,
+    /// The path the socket is bound to, removed on drop
+    filepath: Option<PathBuf>,
+    cli_path: Option<PathBuf>,
+    cli_name: Option<String>,
+    cli_group: Option<u32>,
+    cli_mode: Option<u32>,
+    ctrl_path: PathBuf,
+    reconnect: Option<ReconnectPolicy>,
+    retry: Option<RetryPolicy>,
+    attached: bool,
+    last_request_id: Option<u64>,
+    recorder: Option<Recorder>,
+    version: Option<Version>,
+    lossy_events: bool,
+    /// The level set with `LEVEL`, restored when attaching again
+    level: Option<Level>,
+    /// The control socket connected to, see `socket_id`
+    peer: Option<(u64, u64, i64, i64)>,
+    detach_on_drop: bool,
+}
+
+impl WpaCtrlInternal {
+    /// Open another connection to the same control socket, with the same options
+    fn try_clone(&self) -> Result<WpaCtrl> {
+        WpaCtrlBuilder {
+            cli_path: self.cli_path.clone(),
+            cli_name: self.cli_name.clone(),
+            cli_group: self.cli_group,
+            cli_mode: self.cli_mode,
+            ctrl_path: Some(self.ctrl_path.clone()),
+            reconnect: self.reconnect,
+            retry: self.retry,
+            any_interface: false,
+            buffer_size: Some(self.buffer.len() - 1),
+            record: None,
+            lossy_events: self.lossy_events,
+            open_retries: 0,
+            open_backoff: None,
+            keep_attached: !self.detach_on_drop,
+            socket: None,
+        }.open()
+    }
+
+    /// Check if any messages are available
+    pub fn pending(&mut self) -> Result<bool> {
+        self.pending_timeout(Duration::from_secs(0))
+    }
+
+    /// Check if any messages are available, waiting up to `timeout` for one
+    pub fn pending_timeout(&mut self, timeout: Duration) -> Result<bool> {
+        let mut fd_set = FdSet::new();
+        let raw_fd = self.handle.as_raw_fd();
+        fd_set.insert(raw_fd);
+        let mut timeout = TimeVal::microseconds(timeout.as_micros() as i64);
+        select(raw_fd+1, Some(&mut fd_set), None, None, Some(&mut timeout))?;
+        Ok(fd_set.contains(raw_fd))
+    }
+
+    /// Reconnect if wpasupplicant replaced its socket, so that an attached connection keeps receiving events
+    ///
+    /// A restart is not reported on datagram sockets; the old socket just
+    /// stays silent.
+    fn resume(&mut self) -> Result<()> {
+        if let Some(policy) = self.reconnect {
+            if self.attached && socket_id(&self.ctrl_path) != self.peer {
+                debug!("{} was replaced, reconnecting", self.ctrl_path.display());
+                self.reconnect(policy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive a message
+    pub fn recv(&mut self) -> Result<Option<String>> {
+        if !self.pending()? {
+            self.resume()?;
+        }
+        if self.pending()? {
+            let buf_len = self.handle.recv(&mut self.buffer).map_err(socket_error)?;
+            let buf_len = self.check_len(buf_len)?;
+            if let Some(ref mut recorder) = self.recorder {
+                recorder.event(&self.buffer[..buf_len]);
+            }
+            let msg = self.event(buf_len)?;
+            log_event(&msg);
+            Ok(Some(msg.into_owned()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Receive a message into `buf`, returning its length, or 0 if none is available
+    ///
+    /// A message longer than `buf` is dropped and reported as `Error::Truncated`.
+    pub fn recv_into(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if !self.pending()? {
+            self.resume()?;
+        }
+        if self.pending()? {
+            let len = recv_full_len(&self.handle, buf).map_err(socket_error)?;
+            if len > buf.len() {
+                return Err(Error::Truncated(buf.len()));
+            }
+            if let Some(ref mut recorder) = self.recorder {
+                recorder.event(&buf[..len]);
+            }
+            log_event(&String::from_utf8_lossy(&buf[..len]));
+            Ok(len)
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Send a command to wpasupplicant / hostapd, tagged with a new request id
+    ///
+    /// Returns the length of the reply, which is left in `buffer`. The id is
+    /// only logged and kept in `last_request_id`, errors are returned as is.
+    fn request<F: FnMut(&str)>(&mut self, cmd: &str, mut cb: F) -> Result<usize> {
+        let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        self.last_request_id = Some(id);
+        let name = cmd.split(' ').next().unwrap_or("");
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!("request", id, command = name).entered();
+        debug!("request {}: {}", id, name);
+        trace!("request {} sent: {}", id, redact(cmd));
+        #[cfg(feature = "tracing")]
+        ::tracing::trace!(command = %redact(cmd), "sent");
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.command(cmd);
+        }
+        let mut r = self.request_untagged(cmd, &mut cb);
+        if let Some(policy) = self.retry {
+            if is_idempotent(cmd) {
+                r = self.retry(policy, cmd, &mut cb, r);
+            }
+        }
+        if let Some(policy) = self.reconnect {
+            if r.as_ref().err().is_some_and(is_connection_lost) {
+                debug!("request {} ({}) lost the connection, reconnecting", id, name);
+                r = self.reconnect(policy).and_then(|()| self.request_untagged(cmd, &mut cb));
+            }
+        }
+        match r {
+            Ok(len) => {
+                let reply = String::from_utf8_lossy(&self.buffer[..len]);
+                trace!("request {} reply: {}", id, redact_reply(cmd, reply.trim_end()));
+                #[cfg(feature = "tracing")]
+                ::tracing::trace!(reply = %redact_reply(cmd, reply.trim_end()), "reply");
+                if let Some(ref mut recorder) = self.recorder {
+                    recorder.reply(cmd, &self.buffer[..len]);
+                }
+            },
+            Err(ref e) => {
+                debug!("request {} ({}) failed: {}", id, name, e);
+                #[cfg(feature = "tracing")]
+                ::tracing::debug!(error = %e, "failed");
+            }
+        }
+        r
+    }
+
+    /// Send `cmd` again while it times out, given the result `r` of the first attempt
+    fn retry<F: FnMut(&str)>(&mut self, policy: RetryPolicy, cmd: &str, cb: &mut F, mut r: Result<usize>) -> Result<usize> {
+        let mut backoff = policy.backoff;
+        let mut attempt = 1;
+        while attempt < policy.attempts && matches!(r, Err(Error::Timeout)) {
+            attempt += 1;
+            debug!("request {:?} timed out, attempt {} of {}", cmd.split(' ').next().unwrap_or(""), attempt, policy.attempts);
+            thread::sleep(backoff);
+            backoff *= 2;
+            // Drop any late reply to the previous attempt, passing on events
+            while self.pending()? {
+                let len = self.handle.recv(&mut self.buffer).map_err(socket_error)?;
+                let len = self.check_len(len)?;
+                if self.buffer[..len].starts_with(b"<") {
+                    cb(&self.event(len)?);
+                }
+            }
+            r = self.request_untagged(cmd, &mut *cb);
+        }
+        r
+    }
+
+    /// Reconnect to the control socket, and attach again with the same level if needed
+    fn reconnect(&mut self, policy: ReconnectPolicy) -> Result<()> {
+        let mut backoff = policy.backoff;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            thread::sleep(backoff);
+            let peer = socket_id(&self.ctrl_path);
+            let r = self.handle.connect(&self.ctrl_path).map_err(socket_error).and_then(|()| {
+                if self.attached {
+                    let len = self.request_untagged("ATTACH", |_: &str|())?;
+                    expect_ok(std::str::from_utf8(&self.buffer[..len])?)?;
+                    if let Some(level) = self.level {
+                        let len = self.request_untagged(&format!("LEVEL {}", level.priority()), |_: &str|())?;
+                        expect_ok(std::str::from_utf8(&self.buffer[..len])?)?;
+                    }
+                }
+                self.peer = peer;
+                Ok(())
+            });
+            match r {
+                Ok(()) => return Ok(()),
+                Err(ref e) if attempt < policy.attempts && is_connection_lost(e) => backoff *= 2,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn request_untagged<F: FnMut(&str)>(&mut self, cmd: &str, mut cb: F) -> Result<usize> {
+        self.handle.send(cmd.as_bytes()).map_err(socket_error)?;
+        loop {
+            let mut fd_set = FdSet::new();
+            fd_set.insert(self.handle.as_raw_fd());
+            let timeout = self.retry.map_or(REQUEST_TIMEOUT, |policy| policy.timeout);
+            let mut timeout = TimeVal::microseconds(timeout.as_micros() as i64);
+            select(self.handle.as_raw_fd()+1, Some(&mut fd_set), None, None, Some(&mut timeout))?;
+            if !fd_set.contains(self.handle.as_raw_fd()) {
+                return Err(Error::Timeout);
+            }
+            match self.handle.recv(&mut self.buffer) {
+                Ok(len) => {
+                    let len = self.check_len(len)?;
+                    if self.buffer[..len].starts_with(b"<") {
+                        if let Some(ref mut recorder) = self.recorder {
+                            recorder.event(&self.buffer[..len]);
+                        }
+                        let msg = self.event(len)?;
+                        log_event(&msg);
+                        cb(&msg)
+                    } else {
+                        return Ok(len);
+                    }
+                },
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(socket_error(e)),
+            }
+        }
+    }
+
+    /// Check that a message of `len` bytes fit in the buffer
+    fn check_len(&self, len: usize) -> Result<usize> {
+        if len < self.buffer.len() {
+            Ok(len)
+        } else {
+            Err(Error::Truncated(self.buffer.len() - 1))
+        }
+    }
+
+    /// The event of `len` bytes in the buffer, as a string
+    fn event(&self, len: usize) -> Result<Cow<'_, str>> {
+        if self.lossy_events {
+            Ok(String::from_utf8_lossy(&self.buffer[..len]))
+        } else {
+            Ok(Cow::Borrowed(std::str::from_utf8(&self.buffer[..len])?))
+        }
+    }
+
+    /// The reply of `len` bytes left in the buffer by `request`, as a string
+    fn reply(&self, len: usize) -> Result<String> {
+        Ok(std::str::from_utf8(&self.buffer[..len])?.to_owned())
+    }
+
+    /// Replace the contents of `reply` with the reply of `len` bytes left in the buffer
+    fn reply_into(&self, len: usize, reply: &mut String) -> Result<()> {
+        reply.clear();
+        reply.push_str(std::str::from_utf8(&self.buffer[..len])?);
+        Ok(())
+    }
+
+    /// The reply of `len` bytes left in the buffer by `request`, as bytes
+    fn reply_bytes(&self, len: usize) -> Vec<u8> {
+        self.buffer[..len].to_vec()
+    }
+
+    /// The reply of `len` bytes left in the buffer by `request`, replacing invalid UTF-8
+    fn reply_lossy(&self, len: usize) -> String {
+        String::from_utf8_lossy(&self.buffer[..len]).into_owned()
+    }
+}
+
+impl Drop for WpaCtrlInternal {
+    fn drop(&mut self) {
+        if self.attached && self.detach_on_drop {
+            if let Err(e) = self.handle.send(b"DETACH") {
+                debug!("Unable to detach {}", e);
+            }
+        }
+        if let Some(ref filepath) = self.filepath {
+            if let Err(e) = std::fs::remove_file(filepath) {
+                warn!("Unable to unlink {:?}", e);
+            }
+        }
+    }
+}
+
+/// A connection to wpasupplicant / hostap
+pub struct WpaCtrl(WpaCtrlInternal);
+
+impl WpaCtrl {
+    /// Creates a builder for a wpasupplicant / hostap connection
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// ```
+    pub fn new() -> WpaCtrlBuilder {
+        WpaCtrlBuilder::default()
+    }
+
+    /// Register as an event monitor for control interface messages
+    /// 
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let wpa_attached = wpa.attach().unwrap();
+    /// ```
+    pub fn attach(mut self) -> Result<WpaCtrlAttached> {
+        // FIXME: None closure would be better
+        let len = self.0.request("ATTACH", |_: &str|())?;
+        expect_ok(&String::from_utf8_lossy(&self.0.buffer[..len]))?;
+        self.0.attached = true;
+        Ok(WpaCtrlAttached(self.0, VecDeque::new(), None))
+    }
+
+    /// Send a command to wpa_supplicant/hostapd.
+    ///
+    /// Commands are generally identical to those used in wpa_cli,
+    /// except all uppercase (eg LIST_NETWORKS, SCAN, etc)
+    /// 
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+    /// ```
+    pub fn request(&mut self, cmd: &str) -> Result<String> {
+        self.raw_request(cmd)
+    }
+
+    /// Send a command to wpa_supplicant/hostapd and return the reply verbatim
+    ///
+    /// Unlike the typed methods, the reply is never interpreted, so this
+    /// can be used for any command, including ones newer than this crate.
+    /// This method is a permanent part of the API and will keep returning
+    /// unmodified replies regardless of how `request` evolves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// assert_eq!(wpa.raw_request("PING").unwrap(), "PONG\n");
+    /// ```
+    pub fn raw_request(&mut self, cmd: &str) -> Result<String> {
+        let len = self.0.request(cmd, |_: &str|())?;
+        self.0.reply(len)
+    }
+
+    /// Send a command to wpa_supplicant/hostapd and return the reply as bytes
+    ///
+    /// Unlike `request`, this does not fail if the reply is not valid
+    /// UTF-8, as is possible with raw SSIDs and some hex dumps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// assert_eq!(wpa.request_bytes("PING").unwrap(), b"PONG\n");
+    /// ```
+    pub fn request_bytes(&mut self, cmd: &str) -> Result<Vec<u8>> {
+        let len = self.0.request(cmd, |_: &str|())?;
+        Ok(self.0.reply_bytes(len))
+    }
+
+    /// Send a command to wpa_supplicant/hostapd, replacing invalid UTF-8 in the reply
+    ///
+    /// Invalid sequences are replaced with U+FFFD, instead of failing as
+    /// `request` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// println!("{}", wpa.request_lossy("SCAN_RESULTS").unwrap());
+    /// ```
+    pub fn request_lossy(&mut self, cmd: &str) -> Result<String> {
+        let len = self.0.request(cmd, |_: &str|())?;
+        Ok(self.0.reply_lossy(len))
+    }
+
+    /// Send a command to wpa_supplicant/hostapd, failing if it is rejected
+    ///
+    /// Unlike `request`, a `FAIL` reply (or `FAIL-` followed by a reason)
+    /// is returned as `Error::Failure`, `UNKNOWN COMMAND` as
+    /// `Error::UnknownCommand` and `NOT-SUPPORTED` as `Error::NotSupported`,
+    /// so a rejected command cannot be mistaken for success.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Error;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// match wpa.request_checked("NO_SUCH_COMMAND") {
+    ///     Err(Error::UnknownCommand) => println!("not supported by this wpasupplicant"),
+    ///     r => println!("{:?}", r),
+    /// }
+    /// ```
+    pub fn request_checked(&mut self, cmd: &str) -> Result<String> {
+        let reply = self.request(cmd)?;
+        match reply_error(&reply) {
+            Some(e) => Err(e),
+            None => Ok(reply),
+        }
+    }
+
+    /// Send a command to wpa_supplicant/hostapd, storing the reply in `reply`
+    ///
+    /// The previous contents of `reply` are replaced, and its allocation is
+    /// reused. The receive buffer itself is kept with the connection, so
+    /// polling in a loop with the same `String` allocates nothing once the
+    /// string has grown to fit the longest reply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let mut reply = String::new();
+    /// for _ in 0..10 {
+    ///     wpa.request_into("SIGNAL_POLL", &mut reply).unwrap();
+    ///     println!("{}", reply);
+    /// }
+    /// ```
+    pub fn request_into(&mut self, cmd: &str, reply: &mut String) -> Result<()> {
+        let len = self.0.request(cmd, |_: &str|())?;
+        self.0.reply_into(len, reply)
+    }
+
+    /// Open a second connection to the same interface
+    ///
+    /// The new connection has its own client socket and is configured with
+    /// the same options, so two parts of a program, eg a poller and a
+    /// command handler, can talk to wpasupplicant without sharing a
+    /// `&mut` handle. Each connection only receives the replies to its own
+    /// commands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// let mut poller = wpa.try_clone().unwrap();
+    /// let handle = thread::spawn(move || poller.request("SIGNAL_POLL").unwrap());
+    /// wpa.request("PING").unwrap();
+    /// println!("{}", handle.join().unwrap());
+    /// ```
+    pub fn try_clone(&self) -> Result<WpaCtrl> {
+        self.0.try_clone()
+    }
+
+    /// The id of the most recent request sent over this connection
+    ///
+    /// Request ids increase monotonically across all connections in the
+    /// process, and are included in this crate's log records. They are not
+    /// part of `Error`; when a request fails, this still returns its id, and
+    /// the debug record `request <id> (<command>) failed` links the two.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.request("PING").unwrap();
+    /// println!("PING was request {}", wpa.last_request_id().unwrap());
+    /// ```
+    pub fn last_request_id(&self) -> Option<u64> {
+        self.0.last_request_id
+    }
+
+    /// The version found by `version`, if it was asked for yet
+    pub(crate) fn known_version(&mut self) -> &mut Option<Version> {
+        &mut self.0.version
+    }
+
+    /// The path of the control socket connected to
+    pub(crate) fn ctrl_path(&self) -> &Path {
+        &self.0.ctrl_path
+    }
 }
 
-impl WpaCtrlInternal {
-    /// Check if any messages are available
-    pub fn pending(&mut self) -> Result<bool> {
-        let mut fd_set = FdSet::new();
-        let raw_fd = self.handle.as_raw_fd();
-        fd_set.insert(raw_fd);
-        select(raw_fd+1, Some(&mut fd_set), None, None, Some(&mut TimeVal::seconds(0)))?;
-        Ok(fd_set.contains(raw_fd))
+impl Request for WpaCtrl {
+    fn request(&mut self, cmd: &str) -> Result<String> {
+        WpaCtrl::request(self, cmd)
     }
+}
 
-    /// Receive a message
-    pub fn recv(&mut self) -> Result<Option<String>> {
-        if self.pending()? {
-            let buf_len = self.handle.recv(&mut self.buffer)?;
-            std::str::from_utf8(&self.buffer[0..buf_len]).map(|s|Some(s.to_owned())).map_err(|e|e.into())
-        } else {
-            Ok(None)
-        }
+/// A connection to wpasupplicant / hostap that receives status messages
+///
+/// Dropping the connection detaches it, unless disabled with
+/// `WpaCtrlBuilder::detach_on_drop`.
+pub struct WpaCtrlAttached(WpaCtrlInternal, VecDeque<String>, Option<DebugRoute>);
+
+/// Where `recv` sends messages that are too verbose to return, see `route_debug`
+struct DebugRoute {
+    below: Level,
+    sender: Sender<(Level, String)>,
+}
+
+impl WpaCtrlAttached {
+
+    /// Stop listening for control interface messages
+    ///
+    /// Any messages that have not been received yet, including those that
+    /// arrive before wpasupplicant acknowledges the detach, are returned
+    /// along with the detached connection, oldest first. No messages are
+    /// lost between an `attach` and the matching `detach`.
+    /// 
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// let (wpa, remaining) = wpa.detach().unwrap();
+    /// for msg in remaining {
+    ///     println!("{}", msg);
+    /// }
+    /// ```
+    pub fn detach(mut self) -> Result<(WpaCtrl, Vec<String>)> {
+        let mut messages: Vec<String> = self.1.drain(..).collect();
+        let len = self.0.request("DETACH", |s: &str|messages.push(s.into()))?;
+        expect_ok(&String::from_utf8_lossy(&self.0.buffer[..len]))?;
+        self.0.attached = false;
+        Ok((WpaCtrl(self.0), messages))
     }
 
-    /// Send a command to wpasupplicant / hostapd. 
-    fn request<F: FnMut(&str)>(&mut self, cmd: &str, mut cb: F) -> Result<String> {
-        self.handle.send(cmd.as_bytes())?;
+    /// Receive the next control interface message, along with its level
+    ///
+    /// Note that multiple control interface messages can be pending;
+    /// call this function repeatedly until it returns None to get all of them.
+    ///
+    /// The `<N>` priority prefix of the message is parsed into its `Level`
+    /// and removed; use `raw_recv` to keep it. Messages without a prefix
+    /// are returned whole, as `Level::Info`.
+    ///
+    /// Messages are returned in the order they were sent by wpasupplicant,
+    /// including those buffered while a `request` was waiting for its reply.
+    ///
+    /// If the socket of wpasupplicant went away, eg because it exited, this
+    /// fails with `Error::ConnectionLost` so that the connection can be
+    /// reopened.
+    /// 
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Level;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((level, msg)) = wpa.recv().unwrap() {
+    ///     if level >= Level::Warning {
+    ///         println!("{}", msg);
+    ///     }
+    /// }
+    /// ```
+    pub fn recv(&mut self) -> Result<Option<(Level, String)>> {
         loop {
-            let mut fd_set = FdSet::new();
-            fd_set.insert(self.handle.as_raw_fd());
-            select(self.handle.as_raw_fd()+1, Some(&mut fd_set), None, None, Some(&mut TimeVal::seconds(10)))?;
-            match self.handle.recv(&mut self.buffer) {
-                Ok(len) => {
-                    let s = std::str::from_utf8(&self.buffer[0..len])?;
-                    if s.starts_with('<') {
-                        cb(s)
-                    } else {
-                        return Ok(s.to_owned());
+            let msg = match self.raw_recv()? {
+                Some(msg) => msg,
+                None => return Ok(None),
+            };
+            let (level, text) = split_level(&msg);
+            let msg = (level, text.to_owned());
+            if let Some(ref route) = self.2 {
+                if level < route.below {
+                    match route.sender.send(msg) {
+                        Ok(()) => continue,
+                        // The receiver is gone, so stop routing
+                        Err(e) => {
+                            self.2 = None;
+                            return Ok(Some(e.0));
+                        }
                     }
-                },
-                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
-                Err(e) => return Err(e.into()),
+                }
             }
+            return Ok(Some(msg));
         }
     }
-}
 
-impl Drop for WpaCtrlInternal {
-    fn drop(&mut self) {
-        if let Err(e) = std::fs::remove_file(&self.filepath) {
-            warn!("Unable to unlink {:?}", e);
-        }
+    /// Send messages less important than `below` to a separate channel instead of returning them from `recv`
+    ///
+    /// With wpasupplicant logging at `Level::MsgDump` or `Level::Debug`
+    /// (see `WpaCtrl::set_log_level`), attached connections get a flood of
+    /// debug messages. Routing them to the returned channel lets a capture
+    /// tool collect them, while `recv` keeps returning only events. Once
+    /// the receiver is dropped, messages are returned by `recv` again.
+    /// Messages are routed as they are received, so a thread reading the
+    /// channel only gets them while `recv` is being called. `raw_recv` is
+    /// not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::Level;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// let debug = wpa.route_debug(Level::Info);
+    /// while let Some((_, event)) = wpa.recv().unwrap() {
+    ///     println!("{}", event);
+    /// }
+    /// for (level, msg) in debug.try_iter() {
+    ///     eprintln!("{}: {}", level, msg);
+    /// }
+    /// ```
+    pub fn route_debug(&mut self, below: Level) -> Receiver<(Level, String)> {
+        let (sender, receiver) = channel();
+        self.2 = Some(DebugRoute { below, sender });
+        receiver
     }
-}
 
-/// A connection to wpasupplicant / hostap
-pub struct WpaCtrl(WpaCtrlInternal);
-
-impl WpaCtrl {
-    /// Creates a builder for a wpasupplicant / hostap connection
+    /// Only receive messages of `level` or above
+    ///
+    /// wpasupplicant sends attached connections the messages at or above
+    /// their level, `Level::Info` by default. The level is set again when
+    /// the connection is attached again after reconnecting.
     ///
     /// # Examples
     ///
     /// ```
-    /// let wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// use wpactrl::Level;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// wpa.set_level(Level::Warning).unwrap();
     /// ```
-    pub fn new() -> WpaCtrlBuilder {
-        WpaCtrlBuilder::default()
+    pub fn set_level(&mut self, level: Level) -> Result<()> {
+        expect_ok(&self.request(&format!("LEVEL {}", level.priority()))?)?;
+        self.0.level = Some(level);
+        Ok(())
     }
 
-    /// Register as an event monitor for control interface messages
-    /// 
+    /// Connect to the control socket again and attach, eg after wpasupplicant restarted
+    ///
+    /// The level set with `set_level` is restored, and `recv` returns the
+    /// events sent after the connection was reestablished. This does the
+    /// same as `WpaCtrlBuilder::auto_reconnect`, once and without waiting.
+    ///
     /// # Examples
     ///
     /// ```
-    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
-    /// let wpa_attached = wpa.attach().unwrap();
+    /// use wpactrl::Error;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// if let Err(Error::ConnectionLost) = wpa.recv() {
+    ///     wpa.reconnect().unwrap();
+    /// }
     /// ```
-    pub fn attach(mut self) -> Result<WpaCtrlAttached> {
-        // FIXME: None closure would be better
-        if self.0.request("ATTACH", |_: &str|())? != "OK\n" {
-            Err(WpaError::Failure.into())
-        } else {
-            Ok(WpaCtrlAttached(self.0, VecDeque::new()))
-        }
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.0.reconnect(ReconnectPolicy { attempts: 1, backoff: Duration::from_secs(0) })
     }
 
     /// Send a command to wpa_supplicant/hostapd.
     ///
     /// Commands are generally identical to those used in wpa_cli,
     /// except all uppercase (eg LIST_NETWORKS, SCAN, etc)
+    ///
+    /// Control interface messages will be buffered as the command
+    /// runs, and will be returned on the next call to recv.
     /// 
     /// # Examples
     ///
@@ -197,71 +1321,188 @@ impl WpaCtrl {
     /// assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
     /// ```
     pub fn request(&mut self, cmd: &str) -> Result<String> {
-        self.0.request(cmd, |_: &str|())
+        self.raw_request(cmd)
     }
-}
-
-/// A connection to wpasupplicant / hostap that receives status messages
-pub struct WpaCtrlAttached(WpaCtrlInternal, VecDeque<String>);
-
-impl WpaCtrlAttached {
 
-    /// Stop listening for and discard any remaining control interface messages
-    /// 
+    /// Send a command to wpa_supplicant/hostapd and return the reply verbatim
+    ///
+    /// Control interface messages are buffered as with `request`.
+    /// See `WpaCtrl::raw_request` for details.
+    ///
     /// # Examples
     ///
     /// ```
     /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
-    /// wpa.detach().unwrap();
+    /// assert_eq!(wpa.raw_request("PING").unwrap(), "PONG\n");
     /// ```
-    pub fn detach(mut self) -> Result<WpaCtrl> {
-        if self.0.request("DETACH", |_: &str|())? != "OK\n" {
-            Err(WpaError::Failure.into())
-        } else {
-            Ok(WpaCtrl(self.0))
+    pub fn raw_request(&mut self, cmd: &str) -> Result<String> {
+        let len = self.request_buffered(cmd)?;
+        self.0.reply(len)
+    }
+
+    /// Send a command to wpa_supplicant/hostapd and return the reply as bytes
+    ///
+    /// Control interface messages are buffered as with `request`.
+    /// See `WpaCtrl::request_bytes` for details.
+    pub fn request_bytes(&mut self, cmd: &str) -> Result<Vec<u8>> {
+        let len = self.request_buffered(cmd)?;
+        Ok(self.0.reply_bytes(len))
+    }
+
+    /// Send a command to wpa_supplicant/hostapd, replacing invalid UTF-8 in the reply
+    ///
+    /// Control interface messages are buffered as with `request`.
+    /// See `WpaCtrl::request_lossy` for details.
+    pub fn request_lossy(&mut self, cmd: &str) -> Result<String> {
+        let len = self.request_buffered(cmd)?;
+        Ok(self.0.reply_lossy(len))
+    }
+
+    /// Send a command to wpa_supplicant/hostapd, failing if it is rejected
+    ///
+    /// Control interface messages are buffered as with `request`.
+    /// See `WpaCtrl::request_checked` for details.
+    pub fn request_checked(&mut self, cmd: &str) -> Result<String> {
+        let reply = self.request(cmd)?;
+        match reply_error(&reply) {
+            Some(e) => Err(e),
+            None => Ok(reply),
         }
     }
 
-    /// Receive the next control interface message.
+    /// Send a command to wpa_supplicant/hostapd, storing the reply in `reply`
+    ///
+    /// Control interface messages are buffered as with `request`.
+    /// See `WpaCtrl::request_into` for details.
+    pub fn request_into(&mut self, cmd: &str, reply: &mut String) -> Result<()> {
+        let len = self.request_buffered(cmd)?;
+        self.0.reply_into(len, reply)
+    }
+
+    /// Send a command, buffering messages received meanwhile, and return the reply length
+    fn request_buffered(&mut self, cmd: &str) -> Result<usize> {
+        let messages = &mut self.1;
+        self.0.request(cmd, |s: &str|{
+            messages.push_back(s.into())
+        })
+    }
+
+    /// Receive the next control interface message verbatim
+    ///
+    /// The message is returned exactly as sent by wpasupplicant, including
+    /// its `<N>` priority prefix. This method is a permanent part of the API
+    /// and will keep returning unmodified messages regardless of how `recv`
+    /// evolves.
     ///
-    /// Note that multiple control interface messages can be pending;
-    /// call this function repeatedly until it returns None to get all of them.
-    /// 
     /// # Examples
     ///
     /// ```
     /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
-    /// assert_eq!(wpa.recv().unwrap(), None);
+    /// while let Some(msg) = wpa.raw_recv().unwrap() {
+    ///     println!("{}", msg);
+    /// }
     /// ```
-    pub fn recv(&mut self) -> Result<Option<String>> {
-        if let Some(s) = self.1.pop_back() {
+    pub fn raw_recv(&mut self) -> Result<Option<String>> {
+        if let Some(s) = self.1.pop_front() {
             Ok(Some(s))
         } else {
             self.0.recv()
         }
     }
 
-    /// Send a command to wpa_supplicant/hostapd.
+    /// Receive the next control interface message verbatim into `buf`
     ///
-    /// Commands are generally identical to those used in wpa_cli,
-    /// except all uppercase (eg LIST_NETWORKS, SCAN, etc)
+    /// Returns the length of the message, or 0 if none is pending. Unlike
+    /// `raw_recv`, this does not allocate for messages read from the
+    /// socket, so a monitor can parse events in place from a long-lived
+    /// buffer. wpasupplicant events fit in 4096 bytes.
+    ///
+    /// A message longer than `buf` fails with `Error::Truncated`. A message
+    /// already queued by `request` stays queued, so it can be received
+    /// with a larger buffer, but one read from the socket is lost.
     ///
-    /// Control interface messages will be buffered as the command
-    /// runs, and will be returned on the next call to recv.
-    /// 
     /// # Examples
     ///
     /// ```
-    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
-    /// assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// let mut buf = [0; 4096];
+    /// loop {
+    ///     let len = wpa.recv_into(&mut buf).unwrap();
+    ///     if len == 0 {
+    ///         break;
+    ///     }
+    ///     println!("{}", String::from_utf8_lossy(&buf[..len]));
+    /// }
     /// ```
-    pub fn request(&mut self, cmd: &str) -> Result<String> {
-        let mut messages = VecDeque::new();
-        let r = self.0.request(cmd, |s: &str|{
-            messages.push_front(s.into())
-        });
-        self.1.extend(messages);
-        r
+    pub fn recv_into(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if let Some(len) = self.1.front().map(String::len) {
+            if len > buf.len() {
+                return Err(Error::Truncated(buf.len()));
+            }
+            let s = self.1.pop_front().unwrap_or_default();
+            buf[..len].copy_from_slice(s.as_bytes());
+            Ok(len)
+        } else {
+            self.0.recv_into(buf)
+        }
+    }
+
+    /// Open a second, unattached connection to the same interface
+    ///
+    /// This suits sending commands from one part of a program while another
+    /// monitors events. See `WpaCtrl::try_clone` for details.
+    pub fn try_clone(&self) -> Result<WpaCtrl> {
+        self.0.try_clone()
+    }
+
+    /// The id of the most recent request sent over this connection
+    ///
+    /// See `WpaCtrl::last_request_id` for details.
+    pub fn last_request_id(&self) -> Option<u64> {
+        self.0.last_request_id
+    }
+
+    /// The path of the control socket connected to
+    pub(crate) fn ctrl_path(&self) -> &Path {
+        &self.0.ctrl_path
+    }
+
+    /// The number of messages buffered but not yet received
+    pub(crate) fn queued(&self) -> usize {
+        self.1.len()
+    }
+
+    /// Wait up to `timeout` for a message for which `f` returns a value
+    ///
+    /// The first `from` buffered messages are not examined. Messages are
+    /// left in the buffer, so they are still returned by `recv` afterwards.
+    pub(crate) fn wait_for<T, F>(&mut self, from: usize, timeout: Duration, mut f: F) -> Result<Option<T>>
+        where F: FnMut(&str) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut next = from;
+        loop {
+            while next < self.1.len() {
+                if let Some(t) = f(&self.1[next]) {
+                    return Ok(Some(t));
+                }
+                next += 1;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            if self.0.pending_timeout(deadline - now)? {
+                if let Some(s) = self.0.recv()? {
+                    self.1.push_back(s);
+                }
+            }
+        }
+    }
+}
+
+impl Request for WpaCtrlAttached {
+    fn request(&mut self, cmd: &str) -> Result<String> {
+        WpaCtrlAttached::request(self, cmd)
     }
 }
 
@@ -273,9 +1514,425 @@ mod test {
         WpaCtrl::new().open().unwrap()
     }
 
+    /// Answer `count` commands on a fresh socket at `path`, like a minimal wpasupplicant
+    fn serve(path: &Path, count: usize) -> thread::JoinHandle<Vec<String>> {
+        let _ = std::fs::remove_file(path);
+        let server = UnixDatagram::bind(path).unwrap();
+        thread::spawn(move || {
+            let mut buf = [0; BUF_SIZE];
+            (0..count).map(|_| {
+                let (len, addr) = server.recv_from(&mut buf).unwrap();
+                let cmd = String::from_utf8(buf[..len].to_vec()).unwrap();
+                let reply: &[u8] = match &cmd[..] {
+                    "PING" => b"PONG\n",
+                    "LATIN1" => b"caf\xe9\n",
+                    _ => b"OK\n",
+                };
+                if cmd == "SCAN" {
+                    server.send_to(b"<3>CTRL-EVENT-SCAN-STARTED ", addr.as_pathname().unwrap()).unwrap();
+                }
+                if cmd == "LATIN1_EVENT" {
+                    server.send_to(b"<3>CTRL-EVENT-SSID caf\xe9", addr.as_pathname().unwrap()).unwrap();
+                }
+                server.send_to(reply, addr.as_pathname().unwrap()).unwrap();
+                cmd
+            }).collect()
+        })
+    }
+
+    #[test]
+    fn connection_lost() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_lost_{}", getpid()));
+        let server = serve(&path, 1);
+        let mut wpa = WpaCtrl::new().ctrl_path(path.clone()).open().unwrap();
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        server.join().unwrap();
+        let e = wpa.request("PING").unwrap_err();
+        assert!(matches!(e, Error::ConnectionLost));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn auto_reconnect() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_reconnect_{}", getpid()));
+        let server = serve(&path, 2);
+        let mut wpa = WpaCtrl::new()
+            .ctrl_path(path.clone())
+            .auto_reconnect(3, Duration::from_millis(10))
+            .open().unwrap()
+            .attach().unwrap();
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        assert_eq!(server.join().unwrap(), vec!["ATTACH", "PING"]);
+        let server = serve(&path, 2);
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        assert_eq!(server.join().unwrap(), vec!["ATTACH", "PING"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reattach() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_reattach_{}", getpid()));
+        let server = serve(&path, 2);
+        let mut wpa = WpaCtrl::new()
+            .ctrl_path(path.clone())
+            .auto_reconnect(3, Duration::from_millis(10))
+            .open().unwrap()
+            .attach().unwrap();
+        wpa.set_level(Level::Warning).unwrap();
+        assert_eq!(server.join().unwrap(), vec!["ATTACH", "LEVEL 4"]);
+        // wpasupplicant restarts, replacing its socket
+        let server = serve(&path, 2);
+        assert!(wpa.recv().unwrap().is_none());
+        assert_eq!(server.join().unwrap(), vec!["ATTACH", "LEVEL 4"]);
+        assert!(wpa.recv().unwrap().is_none());
+        let server = serve(&path, 2);
+        wpa.reconnect().unwrap();
+        assert_eq!(server.join().unwrap(), vec!["ATTACH", "LEVEL 4"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn retry_timeouts() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_retry_{}", getpid()));
+        let _ = std::fs::remove_file(&path);
+        let server = UnixDatagram::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let mut buf = [0; BUF_SIZE];
+            // Drop the SCAN and both attempts at PING, then answer the STATUS
+            let cmds: Vec<String> = (0..3).map(|_| {
+                let (len, _) = server.recv_from(&mut buf).unwrap();
+                String::from_utf8_lossy(&buf[..len]).into_owned()
+            }).collect();
+            let (len, addr) = server.recv_from(&mut buf).unwrap();
+            server.send_to(b"wpa_state=COMPLETED\n", addr.as_pathname().unwrap()).unwrap();
+            (cmds, String::from_utf8_lossy(&buf[..len]).into_owned())
+        });
+        let mut wpa = WpaCtrl::new()
+            .ctrl_path(path.clone())
+            .retry_timeouts(2, Duration::from_millis(50), Duration::from_millis(10))
+            .open().unwrap();
+        assert!(matches!(wpa.request("SCAN"), Err(Error::Timeout)));
+        assert!(matches!(wpa.request("PING"), Err(Error::Timeout)));
+        assert_eq!(wpa.request("STATUS").unwrap(), "wpa_state=COMPLETED\n");
+        let (cmds, last) = server.join().unwrap();
+        assert_eq!(cmds, vec!["SCAN", "PING", "PING"]);
+        assert_eq!(last, "STATUS");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn detach_on_drop() {
+        use testing::MockSupplicant;
+        let path = std::env::temp_dir().join(format!("wpactrl_test_detach_on_drop_{}", getpid()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        drop(WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap());
+        drop(WpaCtrl::new().ctrl_path(mock.path()).detach_on_drop(false).open().unwrap().attach().unwrap());
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while mock.commands().len() < 3 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(mock.commands(), vec!["ATTACH", "DETACH", "ATTACH"]);
+    }
+
+    #[test]
+    fn attach_rejected() {
+        use testing::MockSupplicant;
+        let path = std::env::temp_dir().join(format!("wpactrl_test_attach_rejected_{}", getpid()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("ATTACH", "UNKNOWN COMMAND\n");
+        assert!(matches!(WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach(), Err(Error::UnknownCommand)));
+        mock.reply("ATTACH", "OK\n");
+        mock.reply("DETACH", "FAIL\n");
+        let wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        assert!(matches!(wpa.detach(), Err(Error::Failure)));
+    }
+
+    #[test]
+    fn route_debug() {
+        use testing::MockSupplicant;
+        let path = std::env::temp_dir().join(format!("wpactrl_test_route_debug_{}", getpid()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        let debug = wpa.route_debug(Level::Info);
+        mock.event("<2>wlan0: State: SCANNING -> ASSOCIATING");
+        mock.event("<1>RX ctrl_iface - hexdump(len=4): 50 49 4e 47");
+        mock.event("<3>CTRL-EVENT-SCAN-STARTED ");
+        let event = loop {
+            if let Some(event) = wpa.recv().unwrap() {
+                break event;
+            }
+        };
+        assert_eq!(event, (Level::Info, "CTRL-EVENT-SCAN-STARTED ".to_owned()));
+        assert_eq!(debug.try_iter().count(), 2);
+        drop(debug);
+        mock.event("<2>wlan0: State: ASSOCIATING -> COMPLETED");
+        while wpa.recv().unwrap().is_none() {}
+        assert!(wpa.2.is_none());
+    }
+
+    #[test]
+    fn request_bytes() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_bytes_{}", getpid()));
+        let server = serve(&path, 3);
+        let mut wpa = WpaCtrl::new().ctrl_path(path.clone()).open().unwrap();
+        assert_eq!(wpa.request_bytes("LATIN1").unwrap(), b"caf\xe9\n");
+        assert_eq!(wpa.request_lossy("LATIN1").unwrap(), "caf\u{fffd}\n");
+        assert!(wpa.request("LATIN1").is_err());
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_when_available() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_available_{}", getpid()));
+        let _ = std::fs::remove_file(&path);
+        let builder = WpaCtrl::new().ctrl_path(path.clone());
+        assert!(matches!(builder.clone().open_when_available(Duration::from_millis(50)), Err(Error::NoSuchInterface(_))));
+        let server = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(200));
+                serve(&path, 1).join().unwrap()
+            })
+        };
+        let mut wpa = builder.open_when_available(Duration::from_secs(5)).unwrap();
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_retries() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_open_retries_{}", getpid()));
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(WpaCtrl::new().ctrl_path(path.clone()).open(), Err(Error::NoSuchInterface(_))));
+        let server = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(30));
+                serve(&path, 1).join().unwrap()
+            })
+        };
+        let mut wpa = WpaCtrl::new()
+            .ctrl_path(path.clone())
+            .open_retries(10)
+            .open_backoff(Duration::from_millis(10))
+            .open().unwrap();
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn jitter() {
+        let backoff = Duration::from_millis(100);
+        for _ in 0..10 {
+            let wait = super::jitter(backoff);
+            assert!(wait >= backoff / 2 && wait <= backoff);
+        }
+    }
+
+    #[test]
+    fn lossy_events() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_lossy_{}", getpid()));
+        let server = serve(&path, 2);
+        let mut wpa = WpaCtrl::new().ctrl_path(path.clone()).lossy_events().open().unwrap().attach().unwrap();
+        assert_eq!(wpa.request("LATIN1_EVENT").unwrap(), "OK\n");
+        assert_eq!(wpa.recv().unwrap(), Some((Level::Info, "CTRL-EVENT-SSID caf\u{fffd}".to_owned())));
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn request_into() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_into_{}", getpid()));
+        let server = serve(&path, 2);
+        let mut wpa = WpaCtrl::new().ctrl_path(path.clone()).open().unwrap();
+        let mut reply = String::from("stale");
+        wpa.request_into("PING", &mut reply).unwrap();
+        assert_eq!(reply, "PONG\n");
+        wpa.request_into("SCAN", &mut reply).unwrap();
+        assert_eq!(reply, "OK\n");
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reply_error() {
+        assert!(matches!(super::reply_error("FAIL\n"), Some(Error::Failure)));
+        assert!(matches!(super::reply_error("FAIL-BUSY\n"), Some(Error::Failure)));
+        assert!(matches!(super::reply_error("UNKNOWN COMMAND\n"), Some(Error::UnknownCommand)));
+        assert!(matches!(super::reply_error("NOT-SUPPORTED\n"), Some(Error::NotSupported)));
+        assert!(super::reply_error("FAILED\n").is_none());
+        assert!(super::reply_error("OK\n").is_none());
+        assert!(matches!(expect_ok("UNKNOWN COMMAND\n"), Err(Error::UnknownCommand)));
+        assert!(matches!(expect_ok("PONG\n"), Err(Error::Failure)));
+    }
+
+    #[test]
+    fn buffer_size() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_buffer_size_{}", getpid()));
+        let server = serve(&path, 2);
+        let mut wpa = WpaCtrl::new().ctrl_path(path.clone()).buffer_size(4).open().unwrap();
+        let e = wpa.request("PING").unwrap_err();
+        assert!(matches!(e, Error::Truncated(4)));
+        assert_eq!(wpa.request("RECONNECT").unwrap(), "OK\n");
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recv_into() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_recv_into_{}", getpid()));
+        let server = serve(&path, 2);
+        let mut wpa = WpaCtrl::new().ctrl_path(path.clone()).open().unwrap().attach().unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(wpa.recv_into(&mut buf).unwrap(), 0);
+        assert_eq!(wpa.request("SCAN").unwrap(), "OK\n");
+        assert!(matches!(wpa.recv_into(&mut buf), Err(Error::Truncated(8))));
+        let mut buf = [0; 64];
+        assert_eq!(wpa.recv_into(&mut buf).unwrap(), 27);
+        assert_eq!(&buf[..27], &b"<3>CTRL-EVENT-SCAN-STARTED "[..]);
+        assert_eq!(wpa.recv_into(&mut buf).unwrap(), 0);
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn recv_into_oversized() {
+        use testing::MockSupplicant;
+        let path = std::env::temp_dir().join(format!("wpactrl_test_recv_into_oversized_{}", getpid()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        let mut buf = [0; 32];
+        mock.event(&format!("<3>CTRL-EVENT-BSS-ADDED 0 {}", "02:00:00:00:01:00 ".repeat(4)));
+        mock.event("<3>CTRL-EVENT-SCAN-STARTED ");
+        let e = loop {
+            match wpa.recv_into(&mut buf) {
+                Ok(0) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(e, Err(Error::Truncated(32))));
+        let len = loop {
+            match wpa.recv_into(&mut buf).unwrap() {
+                0 => continue,
+                len => break len,
+            }
+        };
+        assert_eq!(&buf[..len], &b"<3>CTRL-EVENT-SCAN-STARTED "[..]);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<WpaCtrl>();
+        assert_send_sync::<WpaCtrlAttached>();
+        assert_send_sync::<::HostapdCtrl>();
+        assert_send_sync::<::GlobalCtrl>();
+        assert_send_sync::<Error>();
+    }
+
+    #[test]
+    fn cli_name() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_cli_name_{}", getpid()));
+        let _ = std::fs::remove_file(&path);
+        // Kept open for the whole test, as connecting fails once the server is gone
+        let _server = UnixDatagram::bind(&path).unwrap();
+        let open = || WpaCtrl::new()
+            .ctrl_path(path.clone())
+            .cli_path(std::env::temp_dir())
+            .cli_name("wpactrl_test_cli_{pid}")
+            .open().unwrap();
+        let (first, second) = (open(), open());
+        let (first_path, second_path) = (first.0.filepath.clone().unwrap(), second.0.filepath.clone().unwrap());
+        assert_ne!(first_path, second_path);
+        let name = first_path.file_name().unwrap().to_str().unwrap().to_owned();
+        assert!(name.starts_with(&format!("wpactrl_test_cli_{}-", getpid())));
+        drop(first);
+        assert!(!first_path.exists());
+        assert!(second_path.exists());
+        let unwound = std::panic::catch_unwind(move || {
+            let _second = second;
+            panic!("unwinding");
+        });
+        assert!(unwound.is_err());
+        assert!(!second_path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn from_socket() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_from_socket_{}", getpid()));
+        let client_path = std::env::temp_dir().join(format!("wpactrl_test_from_socket_client_{}", getpid()));
+        let _ = std::fs::remove_file(&client_path);
+        let server = serve(&path, 1);
+        let socket = UnixDatagram::bind(&client_path).unwrap();
+        socket.connect(&path).unwrap();
+        let mut wpa = WpaCtrl::new().from_socket(socket).open().unwrap();
+        assert_eq!(wpa.0.ctrl_path, path);
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        drop(wpa);
+        assert!(client_path.exists());
+        assert_eq!(server.join().unwrap(), vec!["PING"]);
+        std::fs::remove_file(&client_path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cli_permissions() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_cli_permissions_{}", getpid()));
+        let server = serve(&path, 1);
+        let gid = nix::unistd::getgid();
+        let mut wpa = WpaCtrl::new().ctrl_path(path.clone()).cli_group(gid.into()).cli_mode(0o660).open().unwrap();
+        let metadata = std::fs::metadata(wpa.0.filepath.as_ref().unwrap()).unwrap();
+        assert_eq!(metadata.gid(), u32::from(gid));
+        assert_eq!(metadata.mode() & 0o777, 0o660);
+        assert_eq!(wpa.request("PING").unwrap(), "PONG\n");
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn try_clone() {
+        let path = std::env::temp_dir().join(format!("wpactrl_test_try_clone_{}", getpid()));
+        let server = serve(&path, 3);
+        let wpa = WpaCtrl::new().ctrl_path(path.clone()).buffer_size(64).open().unwrap().attach().unwrap();
+        let mut clone = wpa.try_clone().unwrap();
+        assert_ne!(clone.0.filepath, wpa.0.filepath);
+        assert_eq!(clone.0.buffer.len(), 65);
+        assert!(!clone.0.attached);
+        assert_eq!(clone.request("PING").unwrap(), "PONG\n");
+        assert_eq!(clone.try_clone().unwrap().request("PING").unwrap(), "PONG\n");
+        assert_eq!(server.join().unwrap(), vec!["ATTACH", "PING", "PING"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_errors() {
+        let dir = std::env::temp_dir();
+        let missing = dir.join(format!("wpactrl_test_missing_{}", getpid()));
+        let e = WpaCtrl::new().ctrl_path(missing.clone()).open().err().unwrap();
+        assert!(matches!(e, Error::NoSuchInterface(ref path) if *path == missing));
+
+        let file = dir.join(format!("wpactrl_test_file_{}", getpid()));
+        std::fs::write(&file, "").unwrap();
+        let e = WpaCtrl::new().ctrl_path(file.clone()).open().err().unwrap();
+        assert!(matches!(e, Error::NotASocket(_)));
+        std::fs::remove_file(&file).unwrap();
+
+        let stale = dir.join(format!("wpactrl_test_stale_{}", getpid()));
+        let _ = std::fs::remove_file(&stale);
+        drop(UnixDatagram::bind(&stale).unwrap());
+        let e = WpaCtrl::new().ctrl_path(stale.clone()).open().err().unwrap();
+        assert!(matches!(e, Error::NoSuchInterface(_)));
+        std::fs::remove_file(&stale).unwrap();
+    }
+
     #[test]
     fn attach() {
-        wpa_ctrl().attach().unwrap().detach().unwrap().attach().unwrap().detach().unwrap();
+        wpa_ctrl().attach().unwrap().detach().unwrap().0.attach().unwrap().detach().unwrap();
     }
 
     #[test]
@@ -306,7 +1963,7 @@ mod test {
         loop {
             match wpa.recv().unwrap() {
                 Some(s) => {
-                    assert_eq!(&s[3..], "CTRL-EVENT-SCAN-STARTED ");
+                    assert_eq!(s, (Level::Info, "CTRL-EVENT-SCAN-STARTED ".to_owned()));
                     break;
                 }
                 None => std::thread::sleep(std::time::Duration::from_millis(10)),
@@ -314,4 +1971,61 @@ mod test {
         }
         wpa.detach().unwrap();
     }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing() {
+        use std::fmt::Write;
+        use std::sync::Mutex;
+        use testing::MockSupplicant;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Collects spans and events as text
+        #[derive(Default)]
+        struct Collector(Mutex<Vec<String>>);
+        struct Fields(String);
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                let _ = write!(self.0, " {}={:?}", field.name(), value);
+            }
+        }
+        impl Subscriber for Collector {
+            fn enabled(&self, _: &Metadata) -> bool { true }
+            fn new_span(&self, span: &Attributes) -> Id {
+                let mut fields = Fields(format!("span {}", span.metadata().name()));
+                span.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event) {
+                let mut fields = Fields(String::new());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let path = std::env::temp_dir().join(format!("wpactrl_test_tracing_{}", getpid()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("SET_NETWORK 0 psk \"hunter22\"", "OK\n");
+        let collector = Arc::new(Collector::default());
+        tracing::subscriber::with_default(collector.clone(), || {
+            let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+            wpa.request("SET_NETWORK 0 psk \"hunter22\"").unwrap();
+            mock.event("<3>CTRL-EVENT-SCAN-STARTED ");
+            while wpa.recv().unwrap().is_none() {}
+        });
+        let records = collector.0.lock().unwrap().join("\n");
+        assert!(records.contains("span request id="), "{}", records);
+        assert!(records.contains("command=\"SET_NETWORK\""), "{}", records);
+        assert!(records.contains("message=sent command=SET_NETWORK 0 psk [REDACTED]"), "{}", records);
+        assert!(records.contains("message=reply reply=OK"), "{}", records);
+        assert!(records.contains("message=event event=<3>CTRL-EVENT-SCAN-STARTED"), "{}", records);
+        assert!(!records.contains("hunter22"), "{}", records);
+    }
 }
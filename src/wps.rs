@@ -0,0 +1,377 @@
+use macaddr::MacAddr;
+use parse::{event, event_arg};
+use secret::REDACTED;
+use std::fmt;
+use std::time::Duration;
+use super::Result;
+use wpactrl::{expect_ok, reply_error, WpaCtrl, WpaCtrlAttached};
+
+/// The outcome of a WPS enrollment started by `wps_pbc` or `wps_pin`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WpsOutcome {
+    /// Credentials were received and the network was added
+    Success,
+    /// The exchange failed
+    Fail {
+        /// The WPS message during which the failure occurred (`msg=`)
+        msg: Option<u8>,
+        /// The configuration error reported by the registrar (`config_error=`)
+        config_error: Option<u16>,
+    },
+    /// More than one access point was in push-button mode at the same time
+    Overlap,
+    /// No enrollment happened before the timeout expired
+    Timeout,
+}
+
+impl WpsOutcome {
+    /// Whether the enrollment succeeded
+    pub fn is_success(&self) -> bool {
+        *self == WpsOutcome::Success
+    }
+}
+
+/// An event reported while running as a WPS External Registrar
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WpsErEvent {
+    /// An access point supporting external registrars was found (`WPS-ER-AP-ADD`)
+    ApAdd {
+        /// The UUID of the access point
+        uuid: String,
+        /// The MAC address of the access point
+        addr: MacAddr,
+        /// The friendly name of the access point
+        name: String,
+        /// The manufacturer of the access point
+        manufacturer: String,
+    },
+    /// An access point went away (`WPS-ER-AP-REMOVE`)
+    ApRemove {
+        /// The UUID of the access point
+        uuid: String,
+    },
+    /// A device asked to be enrolled (`WPS-ER-ENROLLEE-ADD`)
+    EnrolleeAdd {
+        /// The UUID of the enrollee
+        uuid: String,
+        /// The MAC address of the enrollee
+        addr: MacAddr,
+        /// The device name of the enrollee
+        name: String,
+        /// The manufacturer of the enrollee
+        manufacturer: String,
+        /// The device password id; 4 means push-button
+        dev_passwd_id: Option<u16>,
+    },
+    /// An enrollee went away (`WPS-ER-ENROLLEE-REMOVE`)
+    EnrolleeRemove {
+        /// The UUID of the enrollee
+        uuid: String,
+        /// The MAC address of the enrollee
+        addr: MacAddr,
+    },
+    /// The settings of an access point were learned (`WPS-ER-AP-SETTINGS`)
+    ApSettings {
+        /// The UUID of the access point
+        uuid: String,
+        /// The SSID of the access point
+        ssid: String,
+        /// The network key, if any
+        key: Option<String>,
+    },
+}
+
+/// Shows the network key of learned settings as `[REDACTED]`
+impl fmt::Debug for WpsErEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WpsErEvent::ApAdd { ref uuid, ref addr, ref name, ref manufacturer } => {
+                f.debug_struct("ApAdd")
+                    .field("uuid", uuid)
+                    .field("addr", addr)
+                    .field("name", name)
+                    .field("manufacturer", manufacturer)
+                    .finish()
+            }
+            WpsErEvent::ApRemove { ref uuid } => f.debug_struct("ApRemove").field("uuid", uuid).finish(),
+            WpsErEvent::EnrolleeAdd { ref uuid, ref addr, ref name, ref manufacturer, ref dev_passwd_id } => {
+                f.debug_struct("EnrolleeAdd")
+                    .field("uuid", uuid)
+                    .field("addr", addr)
+                    .field("name", name)
+                    .field("manufacturer", manufacturer)
+                    .field("dev_passwd_id", dev_passwd_id)
+                    .finish()
+            }
+            WpsErEvent::EnrolleeRemove { ref uuid, ref addr } => {
+                f.debug_struct("EnrolleeRemove").field("uuid", uuid).field("addr", addr).finish()
+            }
+            WpsErEvent::ApSettings { ref uuid, ref ssid, ref key } => {
+                f.debug_struct("ApSettings")
+                    .field("uuid", uuid)
+                    .field("ssid", ssid)
+                    .field("key", &key.as_ref().map(|_| REDACTED))
+                    .finish()
+            }
+        }
+    }
+}
+
+impl WpsErEvent {
+    /// Parse an External Registrar event, returning None for other messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wpactrl::WpsErEvent;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// while let Some((_, msg)) = wpa.recv().unwrap() {
+    ///     if let Some(event) = WpsErEvent::parse(&msg) {
+    ///         println!("{:?}", event);
+    ///     }
+    /// }
+    /// ```
+    pub fn parse(msg: &str) -> Option<Self> {
+        let (event, body) = event(msg);
+        let mut words = body.split(' ');
+        let mut word = || words.next().filter(|w| !w.is_empty()).map(str::to_owned);
+        // Device descriptions follow the arguments as `|name|manufacturer|...|`
+        let mut descr = body.split_once('|').map_or("", |(_, d)| d).split('|').map(str::to_owned);
+        match event {
+            "WPS-ER-AP-ADD" => Some(WpsErEvent::ApAdd {
+                uuid: word()?,
+                addr: word()?.parse().ok()?,
+                name: descr.next().unwrap_or_default(),
+                manufacturer: descr.next().unwrap_or_default(),
+            }),
+            "WPS-ER-AP-REMOVE" => Some(WpsErEvent::ApRemove { uuid: word()? }),
+            "WPS-ER-ENROLLEE-ADD" => Some(WpsErEvent::EnrolleeAdd {
+                uuid: word()?,
+                addr: word()?.parse().ok()?,
+                name: descr.next().unwrap_or_default(),
+                manufacturer: descr.next().unwrap_or_default(),
+                dev_passwd_id: event_arg(body, "dev_passwd_id").and_then(|v| v.parse().ok()),
+            }),
+            "WPS-ER-ENROLLEE-REMOVE" => Some(WpsErEvent::EnrolleeRemove { uuid: word()?, addr: word()?.parse().ok()? }),
+            "WPS-ER-AP-SETTINGS" => Some(WpsErEvent::ApSettings {
+                uuid: event_arg(body, "uuid")?.to_owned(),
+                ssid: event_arg(body, "ssid")?.to_owned(),
+                key: event_arg(body, "key").map(str::to_owned),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Interpret an event in the context of a WPS enrollment
+fn outcome(msg: &str) -> Option<WpsOutcome> {
+    let (event, body) = event(msg);
+    match event {
+        "WPS-SUCCESS" => Some(WpsOutcome::Success),
+        "WPS-FAIL" => Some(WpsOutcome::Fail {
+            msg: event_arg(body, "msg").and_then(|v| v.parse().ok()),
+            config_error: event_arg(body, "config_error").and_then(|v| v.parse().ok()),
+        }),
+        "WPS-OVERLAP-DETECTED" => Some(WpsOutcome::Overlap),
+        "WPS-TIMEOUT" => Some(WpsOutcome::Timeout),
+        _ => None,
+    }
+}
+
+impl WpaCtrlAttached {
+    /// Start a push-button enrollment and wait for it to complete
+    ///
+    /// `bssid` restricts the enrollment to one access point. wpasupplicant
+    /// gives up after two minutes; if `timeout` expires first, the
+    /// enrollment is cancelled and `WpsOutcome::Timeout` is returned.
+    /// Events received while waiting remain available through `recv`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// println!("{:?}", wpa.wps_pbc(None, Duration::from_secs(120)).unwrap());
+    /// ```
+    pub fn wps_pbc(&mut self, bssid: Option<MacAddr>, timeout: Duration) -> Result<WpsOutcome> {
+        let from = self.queued();
+        let cmd = match bssid {
+            Some(bssid) => format!("WPS_PBC {}", bssid),
+            None => "WPS_PBC".to_owned(),
+        };
+        expect_ok(&self.request(&cmd)?)?;
+        self.wps_wait(from, timeout)
+    }
+
+    /// Start a PIN enrollment and wait for it to complete
+    ///
+    /// `bssid` restricts the enrollment to one access point; with `None`
+    /// any access point will do. Timeouts are handled as for `wps_pbc`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap().attach().unwrap();
+    /// println!("{:?}", wpa.wps_pin(None, "12345670", Duration::from_secs(120)).unwrap());
+    /// ```
+    pub fn wps_pin(&mut self, bssid: Option<MacAddr>, pin: &str, timeout: Duration) -> Result<WpsOutcome> {
+        let from = self.queued();
+        let bssid = bssid.map_or_else(|| "any".to_owned(), |bssid| bssid.to_string());
+        let reply = self.request(&format!("WPS_PIN {} {}", bssid, pin))?;
+        if let Some(e) = reply_error(&reply) {
+            return Err(e);
+        }
+        self.wps_wait(from, timeout)
+    }
+
+    /// Wait for the outcome of a WPS enrollment, cancelling it on timeout
+    fn wps_wait(&mut self, from: usize, timeout: Duration) -> Result<WpsOutcome> {
+        match self.wait_for(from, timeout, outcome)? {
+            Some(outcome) => Ok(outcome),
+            None => {
+                expect_ok(&self.request("WPS_CANCEL")?)?;
+                Ok(WpsOutcome::Timeout)
+            }
+        }
+    }
+}
+
+impl WpaCtrl {
+    /// Start acting as a WPS External Registrar
+    ///
+    /// Access points and enrollees found are reported as `WpsErEvent`s.
+    /// `filter` restricts discovery to the network interface with that IP
+    /// address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.wps_er_start(None).unwrap();
+    /// ```
+    pub fn wps_er_start(&mut self, filter: Option<&str>) -> Result<()> {
+        let cmd = match filter {
+            Some(filter) => format!("WPS_ER_START {}", filter),
+            None => "WPS_ER_START".to_owned(),
+        };
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Stop acting as a WPS External Registrar
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.wps_er_stop().unwrap();
+    /// ```
+    pub fn wps_er_stop(&mut self) -> Result<()> {
+        expect_ok(&self.request("WPS_ER_STOP")?)
+    }
+
+    /// Allow an enrollee to join using its PIN
+    ///
+    /// `uuid` selects the enrollee; with `None` any enrollee using the PIN
+    /// is accepted. `addr` optionally restricts it to a MAC address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.wps_er_pin(None, "12345670", None).unwrap();
+    /// ```
+    pub fn wps_er_pin(&mut self, uuid: Option<&str>, pin: &str, addr: Option<MacAddr>) -> Result<()> {
+        let mut cmd = format!("WPS_ER_PIN {} {}", uuid.unwrap_or("any"), pin);
+        if let Some(addr) = addr {
+            cmd.push_str(&format!(" {}", addr));
+        }
+        expect_ok(&self.request(&cmd)?)
+    }
+
+    /// Accept a push-button enrollment from an enrollee, by UUID or MAC address
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.wps_er_pbc("00:11:22:33:44:55").unwrap();
+    /// ```
+    pub fn wps_er_pbc(&mut self, enrollee: &str) -> Result<()> {
+        expect_ok(&self.request(&format!("WPS_ER_PBC {}", enrollee))?)
+    }
+
+    /// Learn the settings of an access point, by UUID or BSSID, using its PIN
+    ///
+    /// The settings are reported with a `WpsErEvent::ApSettings` event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut wpa = wpactrl::WpaCtrl::new().open().unwrap();
+    /// wpa.wps_er_learn("00:11:22:33:44:55", "12345670").unwrap();
+    /// ```
+    pub fn wps_er_learn(&mut self, ap: &str, pin: &str) -> Result<()> {
+        expect_ok(&self.request(&format!("WPS_ER_LEARN {} {}", ap, pin))?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::Error;
+    use testing::MockSupplicant;
+
+    #[test]
+    fn outcomes() {
+        assert_eq!(outcome("<3>WPS-SUCCESS "), Some(WpsOutcome::Success));
+        assert_eq!(outcome("<3>WPS-FAIL msg=8 config_error=18"),
+                   Some(WpsOutcome::Fail { msg: Some(8), config_error: Some(18) }));
+        assert_eq!(outcome("<3>WPS-FAIL"), Some(WpsOutcome::Fail { msg: None, config_error: None }));
+        assert_eq!(outcome("<3>WPS-OVERLAP-DETECTED"), Some(WpsOutcome::Overlap));
+        assert_eq!(outcome("<3>WPS-TIMEOUT Requested operation timed out"), Some(WpsOutcome::Timeout));
+        assert_eq!(outcome("<3>WPS-PBC-ACTIVE"), None);
+    }
+
+    #[test]
+    fn pin_rejected() {
+        let path = ::std::env::temp_dir().join(format!("wpactrl_test_wps_pin_{}", ::std::process::id()));
+        let mock = MockSupplicant::new(&path).unwrap();
+        mock.reply("WPS_PIN any 12345670", "");
+        let mut wpa = WpaCtrl::new().ctrl_path(mock.path()).open().unwrap().attach().unwrap();
+        let r = wpa.wps_pin(None, "12345670", Duration::from_secs(1));
+        assert!(matches!(r, Err(Error::UnknownCommand)));
+    }
+
+    #[test]
+    fn er_events() {
+        assert_eq!(WpsErEvent::parse("<3>WPS-ER-AP-ADD 2050cd42-1a55-5c4e-9c4a-ab8de45bc1cd 02:00:00:00:00:01 \
+                                      pri_dev_type=6-0050F204-1 wps_state=2 |My AP|ACME|Router|R1|1|123|"),
+                   Some(WpsErEvent::ApAdd {
+                       uuid: "2050cd42-1a55-5c4e-9c4a-ab8de45bc1cd".into(),
+                       addr: "02:00:00:00:00:01".parse().unwrap(),
+                       name: "My AP".into(),
+                       manufacturer: "ACME".into(),
+                   }));
+        assert_eq!(WpsErEvent::parse("<3>WPS-ER-ENROLLEE-ADD 6b5d2a0e-6c2b-5c1c-8f4a-2e9fbd1f9a01 02:00:00:00:00:02 \
+                                      M1=1 config_methods=0x80 dev_passwd_id=4 pri_dev_type=1-0050F204-1 |Printer|ACME|P|1|2|"),
+                   Some(WpsErEvent::EnrolleeAdd {
+                       uuid: "6b5d2a0e-6c2b-5c1c-8f4a-2e9fbd1f9a01".into(),
+                       addr: "02:00:00:00:00:02".parse().unwrap(),
+                       name: "Printer".into(),
+                       manufacturer: "ACME".into(),
+                       dev_passwd_id: Some(4),
+                   }));
+        assert_eq!(WpsErEvent::parse("<3>WPS-ER-AP-REMOVE 2050cd42-1a55-5c4e-9c4a-ab8de45bc1cd"),
+                   Some(WpsErEvent::ApRemove { uuid: "2050cd42-1a55-5c4e-9c4a-ab8de45bc1cd".into() }));
+        assert_eq!(WpsErEvent::parse("<3>WPS-ER-AP-SETTINGS uuid=2050cd42 ssid=home auth_type=0x0020 \
+                                      encr_type=0x0008 key=secret"),
+                   Some(WpsErEvent::ApSettings { uuid: "2050cd42".into(), ssid: "home".into(), key: Some("secret".into()) }));
+        let settings = WpsErEvent::parse("<3>WPS-ER-AP-SETTINGS uuid=2050cd42 ssid=home key=secret").unwrap();
+        assert!(!format!("{:?}", settings).contains("secret"));
+        assert_eq!(WpsErEvent::parse("<3>WPS-ER-AP-REMOVE"), None);
+        assert_eq!(WpsErEvent::parse("<3>WPS-SUCCESS"), None);
+    }
+}